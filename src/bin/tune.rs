@@ -0,0 +1,43 @@
+//! Tunes the evaluation parameters against a file of labelled positions. It shares the engine's modules rather
+//! than linking to a library.
+
+use chess_state::chess_pieces::{piece_structs::*, PieceEnum};
+use env_logger::Builder;
+use std::io::Write;
+
+#[path = "../bitmask/mod.rs"]
+mod bitmask;
+// the tuner only reads positions and traces their evaluation, so the move generation, editing and caching that
+// the engine uses from these go unused here
+#[path = "../chess_state/mod.rs"]
+#[allow(dead_code)]
+mod chess_state;
+#[path = "../evaluation_engine/mod.rs"]
+#[allow(dead_code)]
+mod evaluation_engine;
+#[path = "../shared/mod.rs"]
+mod shared;
+#[path = "../tuner/mod.rs"]
+mod tuner;
+
+fn main() {
+    let mut builder = Builder::from_default_env();
+    builder
+        .format(|buf, record| {
+            writeln!(
+                buf,
+                "{} [{}] - {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                record.level(),
+                record.args()
+            )
+        })
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = tuner::command::TuneArguments::parse(&args).and_then(|arguments| tuner::command::run(&arguments));
+    match result {
+        Ok(_) => log::info!("Tuning finished"),
+        Err(error) => log::error!("Tuning stopped: {}", error),
+    }
+}
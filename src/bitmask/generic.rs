@@ -10,6 +10,7 @@ pub(crate) struct Bitmask<T> {
 }
 
 impl<T> Bitmask<T> {
+    #[cfg(test)]
     pub(crate) fn new() -> Self {
         Self {
             mask: 0,
@@ -24,11 +25,26 @@ impl<T> Bitmask<T> {
         }
     }
 
+    #[cfg(test)]
     pub(crate) fn to_u64(&self) -> u64 {
         self.mask
     }
+
+    /// Returns the number of set bits, i.e. how many pieces the bitmask holds
+    pub(crate) fn count(&self) -> u32 {
+        self.mask.count_ones()
+    }
+}
+
+// implemented by hand so that the marker types do not need to implement `PartialEq` themselves
+impl<T> PartialEq for Bitmask<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.mask == other.mask
+    }
 }
 
+impl<T> Eq for Bitmask<T> {}
+
 impl<T> From<u64> for Bitmask<T> {
     fn from(value: u64) -> Self {
         Self {
@@ -1,11 +1,19 @@
 use crate::{
-    bitmask::generic::Bitmask, BlackBishops, BlackKings, BlackKnights, BlackPawns, BlackPieces,
+    bitmask::generic::Bitmask, shared, BlackBishops, BlackKings, BlackKnights, BlackPawns, BlackPieces,
     BlackQueens, BlackRooks, Pieces, WhiteBishops, WhiteKings, WhiteKnights,
     WhitePawns, WhitePieces, WhiteQueens, WhiteRooks,
 };
 
-use super::{board_hash_map::BoardHashMap, coordinates::{YCoordinate, XCoordinate}};
+use super::{
+    board_hash_map::{BoardHashMap, BoardHashMapError},
+    chess_pieces::PieceEnum,
+    coordinate_point::CoordinatePosition,
+    coordinates::{XCoordinate, YCoordinate},
+};
+#[cfg(test)]
+use super::moves::chess_flip::ChessFlip;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct BoardBitmasks {
     pub all_pieces: Bitmask<Pieces>,
     pub white_pieces: Bitmask<WhitePieces>,
@@ -150,6 +158,59 @@ impl BoardBitmasks {
         });
         output
     }
+
+    /// Creates a BoardHashMap from the bitmasks, going from piece-to-location to location-to-piece. This is the
+    /// inverse of `from_board_hash_map`.
+    ///
+    /// # Errors
+    ///
+    /// * `BoardHashMapError::InsertedIntoFilledBoardPositionWithNoReplace` if two piece bitmasks share a square,
+    ///   which would mean the board has been corrupted.
+    pub(crate) fn to_board_hash_map(self) -> Result<BoardHashMap, BoardHashMapError> {
+        let mut map = BoardHashMap::new();
+        for piece in PieceEnum::ALL {
+            for index in shared::multi_bitmask_to_u8s(&self.piece_enum_to_bitmask(piece)) {
+                let position = CoordinatePosition::from_bitmask(1u64 << index)
+                    .expect("Single bit bitmasks are always valid positions");
+                map.insert(position, piece, false)?;
+            }
+        }
+        Ok(map)
+    }
+
+    /// Mirrors the board top to bottom and swaps the colours of every piece, so white's pieces stand where
+    /// black's did, seen from the other side of the board. The result is the same position for the other side.
+    #[cfg(test)]
+    pub(crate) fn colour_flipped(self) -> Self {
+        Self {
+            all_pieces: self.all_pieces.mask.flip_vertical().into(),
+            white_pieces: self.black_pieces.mask.flip_vertical().into(),
+            white_pawns: self.black_pawns.mask.flip_vertical().into(),
+            white_knights: self.black_knights.mask.flip_vertical().into(),
+            white_bishops: self.black_bishops.mask.flip_vertical().into(),
+            white_rooks: self.black_rooks.mask.flip_vertical().into(),
+            white_queens: self.black_queens.mask.flip_vertical().into(),
+            white_kings: self.black_kings.mask.flip_vertical().into(),
+            black_pieces: self.white_pieces.mask.flip_vertical().into(),
+            black_pawns: self.white_pawns.mask.flip_vertical().into(),
+            black_knights: self.white_knights.mask.flip_vertical().into(),
+            black_bishops: self.white_bishops.mask.flip_vertical().into(),
+            black_rooks: self.white_rooks.mask.flip_vertical().into(),
+            black_queens: self.white_queens.mask.flip_vertical().into(),
+            black_kings: self.white_kings.mask.flip_vertical().into(),
+        }
+    }
+
+    /// Returns the piece standing on `position`, or `None` if the square is empty
+    pub(crate) fn piece_at(&self, position: CoordinatePosition) -> Option<PieceEnum> {
+        let bitmask = position.to_bitmask();
+        if bitmask & self.all_pieces.mask == 0 {
+            return None;
+        }
+        PieceEnum::ALL
+            .into_iter()
+            .find(|&piece| self.piece_enum_to_bitmask(piece) & bitmask != 0)
+    }
 }
 
 #[cfg(test)]
@@ -182,7 +243,143 @@ mod tests {
         }
     }
 
-    mod from_board_hash_map {
-        // needs an almost integration test approach
+    mod round_trips {
+        use crate::chess_state::{
+            board_bitmask::BoardBitmasks,
+            board_hash_map::{BoardHashMap, BoardHashMapError},
+            chess_pieces::PieceEnum,
+            coordinate_point::CoordinatePosition,
+        };
+
+        /// A small xorshift generator, so the random placements are the same on every run
+        struct Xorshift(u64);
+
+        impl Xorshift {
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+        }
+
+        /// Places up to 32 random pieces on random squares
+        fn random_board_hash_map(rng: &mut Xorshift) -> BoardHashMap {
+            let mut map = BoardHashMap::new();
+            for _ in 0..rng.next() % 33 {
+                let position = CoordinatePosition::from_bitmask(1u64 << (rng.next() % 64))
+                    .expect("single bit bitmask");
+                let piece = PieceEnum::ALL[(rng.next() % 12) as usize];
+                map.insert(position, piece, true).expect("replace flag passed");
+            }
+            map
+        }
+
+        #[test]
+        fn completes_round_trip_when_given_starting_position() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act
+            let map = board.to_board_hash_map().expect("valid board");
+            // assert
+            assert_eq!(map.to_iter().count(), 32);
+            assert_eq!(BoardBitmasks::from_board_hash_map(&map), board)
+        }
+
+        #[test]
+        fn completes_round_trip_from_hash_map_when_given_random_placements() {
+            let mut rng = Xorshift(0x9E37_79B9_7F4A_7C15);
+            for _ in 0..1_000 {
+                // arrange
+                let map = random_board_hash_map(&mut rng);
+                // act
+                let output = BoardBitmasks::from_board_hash_map(&map).to_board_hash_map();
+                // assert
+                assert_eq!(output.expect("valid board"), map);
+            }
+        }
+
+        #[test]
+        fn completes_round_trip_from_bitmasks_when_given_random_placements() {
+            let mut rng = Xorshift(0xD1B5_4A32_D192_ED03);
+            for _ in 0..1_000 {
+                // arrange
+                let board = BoardBitmasks::from_board_hash_map(&random_board_hash_map(&mut rng));
+                // act
+                let map = board.to_board_hash_map().expect("valid board");
+                let output = BoardBitmasks::from_board_hash_map(&map);
+                // assert
+                assert_eq!(output, board);
+                assert_eq!(
+                    output.all_pieces.mask,
+                    output.white_pieces.mask | output.black_pieces.mask
+                );
+            }
+        }
+
+        #[test]
+        fn returns_correct_error_when_piece_bitmasks_overlap() {
+            // arrange
+            let mut board = BoardBitmasks::default();
+            // put a white knight on top of the pawn on e2
+            board.white_knights.mask |= board.white_pawns.mask & 0x00_00_00_00_00_00_08_00;
+            // act
+            let output = board.to_board_hash_map();
+            // assert
+            assert!(matches!(
+                output,
+                Err(BoardHashMapError::InsertedIntoFilledBoardPositionWithNoReplace(_, _))
+            ))
+        }
+    }
+
+    mod colour_flipped {
+        use crate::chess_state::{board_bitmask::BoardBitmasks, game_state::GameState};
+
+        #[test]
+        fn keeps_starting_position_when_flipped() {
+            // act + assert
+            assert_eq!(BoardBitmasks::default().colour_flipped(), BoardBitmasks::default())
+        }
+
+        #[test]
+        fn swaps_colours_and_ranks_when_flipped() {
+            // arrange
+            let board = GameState::from_fen("4k3/8/8/8/4P3/8/8/R3K3 w - - 0 1").expect("valid fen").board;
+            let expected = GameState::from_fen("r3k3/8/8/4p3/8/8/8/4K3 w - - 0 1").expect("valid fen").board;
+            // act
+            let output = board.colour_flipped();
+            // assert
+            assert_eq!(output, expected);
+            assert_eq!(output.colour_flipped(), board)
+        }
+    }
+
+    mod piece_at {
+        use crate::chess_state::{
+            board_bitmask::BoardBitmasks, chess_pieces::PieceEnum,
+            coordinate_point::CoordinatePosition,
+        };
+
+        #[test]
+        fn finds_pieces_and_empty_squares_when_in_starting_position() {
+            // arrange
+            let board = BoardBitmasks::default();
+            let squares = ["a1", "e1", "d8", "g7", "e4"];
+            let expected_output = vec![
+                Some(PieceEnum::WhiteRook),
+                Some(PieceEnum::WhiteKing),
+                Some(PieceEnum::BlackQueen),
+                Some(PieceEnum::BlackPawn),
+                None,
+            ];
+            // act
+            let output: Vec<Option<PieceEnum>> = squares
+                .iter()
+                .map(|s| board.piece_at(CoordinatePosition::from_str(s).expect("valid square")))
+                .collect();
+            // assert
+            assert_eq!(output, expected_output)
+        }
     }
 }
@@ -0,0 +1,368 @@
+use thiserror::Error;
+
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    board_hash_map::BoardHashMap,
+    chess_pieces::PieceEnum,
+    coordinate_point::CoordinatePosition,
+    coordinates::{CoordinateConversion, XCoordinate, YCoordinate},
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub(crate) enum BoardDiagramError {
+    #[error("Square {0} is not a piece letter, an empty square, or a marker")]
+    InvalidSquare(String),
+
+    #[error("Row {0} should describe exactly 8 squares")]
+    InvalidRowLength(String),
+
+    #[error("Rank label {0} is not a rank in the range 1-8")]
+    InvalidRankLabel(String),
+
+    #[error("Rank {0} is described more than once")]
+    DuplicateRank(char),
+
+    #[error("Diagram describes {0} ranks, but can describe at most 8")]
+    TooManyRanks(usize),
+}
+
+/// The result of parsing a board diagram: the position it shows, and any squares marked as expected targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BoardDiagram {
+    pub board: BoardBitmasks,
+    pub marked_squares: u64,
+}
+
+/// One square read from a diagram, before it is placed on the board
+enum DiagramSquare {
+    Empty,
+    Marked,
+    Piece(PieceEnum, bool),
+}
+
+/// Parses a board diagram into a `BoardDiagram`. Two layouts are accepted:
+///
+/// Grid rows, as drawn by `BoardBitmasks::display` and in this crate's doc comments. The file header is optional,
+/// but if present decides the file order, so boards drawn from black's side parse correctly. Rank labels are
+/// optional too; unlabelled rows are read downwards from the top rank, so partial diagrams only need the rows
+/// they care about:
+///
+/// ```text
+///      A   B   C   D   E   F   G   H
+///  8 |   |   |   |   |   |   |   |   |
+///  7 |   |   |   | x |   |   |   |   |
+///  6 |   |   | P | p |   |   |   |   |
+/// ```
+///
+/// Compact rows, either a whole FEN piece placement on one line (`rnbqkbnr/pppppppp/8/...`) or one rank per
+/// line, always from rank eight down to rank one.
+///
+/// In both layouts an `x` marks an empty square as an expected target, and a `*` after a piece letter (e.g. `p*`)
+/// marks an occupied square, such as a capture target. Blank lines and surrounding indentation are ignored.
+pub(crate) fn parse_board_diagram(diagram: &str) -> Result<BoardDiagram, BoardDiagramError> {
+    let lines: Vec<&str> = diagram
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let rows = match lines.iter().any(|line| line.contains('|')) {
+        true => parse_grid_rows(&lines)?,
+        false => parse_compact_rows(&lines)?,
+    };
+
+    let mut map = BoardHashMap::new();
+    let mut marked_squares: u64 = 0;
+    for (position, square) in rows {
+        match square {
+            DiagramSquare::Empty => {}
+            DiagramSquare::Marked => marked_squares |= position.to_bitmask(),
+            DiagramSquare::Piece(piece, marked) => {
+                map.insert(position, piece, false)
+                    .expect("Each square is only read once");
+                if marked {
+                    marked_squares |= position.to_bitmask();
+                }
+            }
+        }
+    }
+
+    Ok(BoardDiagram {
+        board: BoardBitmasks::from_board_hash_map(&map),
+        marked_squares,
+    })
+}
+
+fn parse_grid_square(cell: &str) -> Result<DiagramSquare, BoardDiagramError> {
+    let mut chars = cell.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (None, _, _) => Ok(DiagramSquare::Empty),
+        (Some('x'), None, _) => Ok(DiagramSquare::Marked),
+        (Some(ch), marker, None) if marker.is_none() || marker == Some('*') => {
+            let piece = PieceEnum::from_char(ch)
+                .ok_or(BoardDiagramError::InvalidSquare(cell.to_string()))?;
+            Ok(DiagramSquare::Piece(piece, marker.is_some()))
+        }
+        _ => Err(BoardDiagramError::InvalidSquare(cell.to_string())),
+    }
+}
+
+fn parse_grid_rows(
+    lines: &[&str],
+) -> Result<Vec<(CoordinatePosition, DiagramSquare)>, BoardDiagramError> {
+    let mut files: Vec<XCoordinate> = XCoordinate::ALL.to_vec();
+    let mut next_ranks: Vec<YCoordinate> = YCoordinate::ALL.into_iter().rev().collect();
+    let mut seen_ranks: Vec<YCoordinate> = Vec::with_capacity(8);
+    let mut output = Vec::with_capacity(64);
+
+    for line in lines {
+        let Some((label, squares)) = line.split_once('|') else {
+            // a header line of file letters, which also tells us which way round the board is drawn
+            let header: Vec<XCoordinate> = line
+                .split_whitespace()
+                .filter_map(|letter| letter.chars().next())
+                .filter_map(|ch| CoordinateConversion::<char>::try_from_value(ch).ok())
+                .collect();
+            if header.len() == 8 {
+                if header[0] == XCoordinate::H {
+                    next_ranks.reverse();
+                }
+                files = header;
+            }
+            continue;
+        };
+
+        let cells: Vec<&str> = squares
+            .strip_suffix('|')
+            .unwrap_or(squares)
+            .split('|')
+            .map(str::trim)
+            .collect();
+        if cells.len() != 8 {
+            return Err(BoardDiagramError::InvalidRowLength(line.to_string()));
+        }
+
+        let rank = match label.trim() {
+            "" => *next_ranks
+                .get(seen_ranks.len())
+                .ok_or(BoardDiagramError::TooManyRanks(seen_ranks.len() + 1))?,
+            label => {
+                let mut label_chars = label.chars();
+                match (label_chars.next(), label_chars.next()) {
+                    (Some(ch), None) => CoordinateConversion::<char>::try_from_value(ch)
+                        .map_err(|_| BoardDiagramError::InvalidRankLabel(label.to_string()))?,
+                    _ => return Err(BoardDiagramError::InvalidRankLabel(label.to_string())),
+                }
+            }
+        };
+        if seen_ranks.contains(&rank) {
+            return Err(BoardDiagramError::DuplicateRank(
+                CoordinateConversion::<char>::to_value(rank),
+            ));
+        }
+        seen_ranks.push(rank);
+
+        for (&x, cell) in files.iter().zip(cells) {
+            output.push((CoordinatePosition { x, y: rank }, parse_grid_square(cell)?));
+        }
+    }
+
+    Ok(output)
+}
+
+fn parse_compact_rows(
+    lines: &[&str],
+) -> Result<Vec<(CoordinatePosition, DiagramSquare)>, BoardDiagramError> {
+    let rows: Vec<&str> = lines.iter().flat_map(|line| line.split('/')).collect();
+    if rows.len() > 8 {
+        return Err(BoardDiagramError::TooManyRanks(rows.len()));
+    }
+
+    let mut output = Vec::with_capacity(64);
+    for (row, y) in rows.iter().zip(YCoordinate::ALL.into_iter().rev()) {
+        let mut squares: Vec<DiagramSquare> = Vec::with_capacity(8);
+        let mut chars = row.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '1'..='8' => (0..ch.to_digit(10).expect("Is a digit"))
+                    .for_each(|_| squares.push(DiagramSquare::Empty)),
+                'x' => squares.push(DiagramSquare::Marked),
+                _ => {
+                    let piece = PieceEnum::from_char(ch)
+                        .ok_or(BoardDiagramError::InvalidSquare(ch.to_string()))?;
+                    let marked = chars.next_if_eq(&'*').is_some();
+                    squares.push(DiagramSquare::Piece(piece, marked));
+                }
+            }
+        }
+        if squares.len() != 8 {
+            return Err(BoardDiagramError::InvalidRowLength(row.to_string()));
+        }
+        for (x, square) in XCoordinate::ALL.into_iter().zip(squares) {
+            output.push((CoordinatePosition { x, y }, square));
+        }
+    }
+
+    Ok(output)
+}
+
+/// Builds a `BoardDiagram` from a diagram string, panicking if it does not parse. Intended for tests, where a
+/// broken diagram is a bug in the test itself. See `parse_board_diagram` for the accepted layouts.
+#[macro_export]
+macro_rules! board_diagram {
+    ($diagram:expr) => {
+        $crate::chess_state::board_diagram::parse_board_diagram($diagram)
+            .expect("Board diagram should be valid")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    mod grid_rows {
+        use crate::{
+            assert_bitmask_eq,
+            chess_state::{
+                board_bitmask::BoardBitmasks,
+                board_diagram::{parse_board_diagram, BoardDiagramError},
+                board_display::{BoardOrientation, PieceStyle},
+                coordinates::{XCoordinate::*, YCoordinate::*},
+            },
+        };
+
+        #[test]
+        fn parses_starting_position_when_given_rendered_board() {
+            // arrange
+            let diagram = BoardBitmasks::default().to_string();
+            // act
+            let output = board_diagram!(&diagram);
+            // assert
+            assert_eq!(output.board, BoardBitmasks::default());
+            assert_eq!(output.marked_squares, 0)
+        }
+
+        #[test]
+        fn parses_board_drawn_from_blacks_side_when_header_is_reversed() {
+            // arrange
+            let diagram = BoardBitmasks::default()
+                .display(PieceStyle::Ascii, BoardOrientation::BlackAtBottom)
+                .to_string();
+            // act
+            let output = board_diagram!(&diagram);
+            // assert
+            assert_eq!(output.board, BoardBitmasks::default())
+        }
+
+        #[test]
+        fn reads_pieces_and_markers_when_given_partial_doc_comment_diagram() {
+            // act
+            let output = board_diagram!(
+                "
+                     A   B   C   D   E   F   G   H
+                 8 |   |   |   |   |   |   |   |   |
+                 7 |   |   |   | x |   |   |   |   |
+                 6 |   |   | P | p*|   |   |   |   |
+                 5 |   |   |   |   |   |   |   |   |
+                "
+            );
+            // assert
+            assert_bitmask_eq!(output.board.white_pawns.mask, C as u64 & Six as u64);
+            assert_bitmask_eq!(output.board.black_pawns.mask, D as u64 & Six as u64);
+            assert_bitmask_eq!(
+                output.marked_squares,
+                D as u64 & (Seven as u64 | Six as u64)
+            );
+        }
+
+        #[test]
+        fn places_unlabelled_rows_from_rank_eight_when_labels_are_left_off() {
+            // act
+            let output = board_diagram!(
+                "
+                | k |   |   |   |   |   |   |   |
+                |   |   |   |   |   |   |   | K |
+                "
+            );
+            // assert
+            assert_bitmask_eq!(output.board.black_kings.mask, A as u64 & Eight as u64);
+            assert_bitmask_eq!(output.board.white_kings.mask, H as u64 & Seven as u64);
+        }
+
+        #[test]
+        fn returns_correct_errors_when_given_invalid_rows() {
+            // act
+            let short_row = parse_board_diagram(" 8 | k |   |");
+            let bad_square = parse_board_diagram(" 8 | z |   |   |   |   |   |   |   |");
+            let bad_label = parse_board_diagram(" 9 |   |   |   |   |   |   |   |   |");
+            let duplicate = parse_board_diagram(
+                " 8 |   |   |   |   |   |   |   |   |\n 8 |   |   |   |   |   |   |   |   |",
+            );
+            // assert
+            assert_eq!(
+                short_row,
+                Err(BoardDiagramError::InvalidRowLength("8 | k |   |".into()))
+            );
+            assert_eq!(bad_square, Err(BoardDiagramError::InvalidSquare("z".into())));
+            assert_eq!(bad_label, Err(BoardDiagramError::InvalidRankLabel("9".into())));
+            assert_eq!(duplicate, Err(BoardDiagramError::DuplicateRank('8')));
+        }
+    }
+
+    mod compact_rows {
+        use crate::{
+            assert_bitmask_eq,
+            chess_state::{
+                board_bitmask::BoardBitmasks,
+                board_diagram::{parse_board_diagram, BoardDiagramError},
+                coordinates::{XCoordinate::*, YCoordinate::*},
+            },
+        };
+
+        #[test]
+        fn parses_starting_position_when_given_single_fen_line() {
+            // act
+            let output = board_diagram!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+            // assert
+            assert_eq!(output.board, BoardBitmasks::default())
+        }
+
+        #[test]
+        fn reads_one_rank_per_line_with_markers_when_given_multiple_lines() {
+            // act
+            let output = board_diagram!(
+                "
+                4k3
+                8
+                8
+                3p*4
+                4P3
+                8
+                8
+                4K3
+                "
+            );
+            // assert
+            assert_bitmask_eq!(output.board.black_pawns.mask, D as u64 & Five as u64);
+            assert_bitmask_eq!(output.board.white_pawns.mask, E as u64 & Four as u64);
+            assert_bitmask_eq!(output.marked_squares, D as u64 & Five as u64);
+        }
+
+        #[test]
+        fn marks_empty_squares_when_given_x() {
+            // act
+            let output = board_diagram!("8/8/8/8/8/8/4x3/4K3");
+            // assert
+            assert_bitmask_eq!(output.marked_squares, E as u64 & Two as u64);
+            assert_bitmask_eq!(output.board.all_pieces.mask, E as u64 & One as u64);
+        }
+
+        #[test]
+        fn returns_correct_errors_when_given_invalid_rows() {
+            // act
+            let long_row = parse_board_diagram("9");
+            let too_many = parse_board_diagram("8/8/8/8/8/8/8/8/8");
+            // assert
+            assert_eq!(long_row, Err(BoardDiagramError::InvalidSquare("9".into())));
+            assert_eq!(too_many, Err(BoardDiagramError::TooManyRanks(9)));
+        }
+    }
+}
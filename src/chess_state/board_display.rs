@@ -0,0 +1,287 @@
+use std::fmt;
+
+use crate::{
+    bitmask::generic::Bitmask,
+    chess_state::{
+        board_bitmask::BoardBitmasks,
+        chess_pieces::PieceEnum,
+        coordinate_point::CoordinatePosition,
+        coordinates::{CoordinateConversion, XCoordinate, YCoordinate},
+    },
+};
+
+/// Selects how pieces are drawn when rendering a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PieceStyle {
+    /// FEN letters, upper case for white and lower case for black (e.g. `K`, `q`)
+    Ascii,
+    /// Unicode chess figurines (e.g. `♔`, `♛`)
+    Unicode,
+}
+
+/// Selects which side of the board is drawn at the bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BoardOrientation {
+    /// Rank one at the bottom, with the A file on the left
+    WhiteAtBottom,
+    /// Rank eight at the bottom, with the H file on the left
+    BlackAtBottom,
+}
+
+/// A renderable view of a `BoardBitmasks`, created with `BoardBitmasks::display`
+pub(crate) struct BoardDisplay<'a> {
+    board: &'a BoardBitmasks,
+    style: PieceStyle,
+    orientation: BoardOrientation,
+}
+
+impl PieceEnum {
+    /// Returns the Unicode chess figurine for the piece
+    pub(crate) fn to_figurine(self) -> char {
+        use PieceEnum::*;
+        match self {
+            WhitePawn => '♙',
+            WhiteKnight => '♘',
+            WhiteBishop => '♗',
+            WhiteRook => '♖',
+            WhiteQueen => '♕',
+            WhiteKing => '♔',
+            BlackPawn => '♟',
+            BlackKnight => '♞',
+            BlackBishop => '♝',
+            BlackRook => '♜',
+            BlackQueen => '♛',
+            BlackKing => '♚',
+        }
+    }
+}
+
+impl BoardBitmasks {
+    /// Returns a view of the board that renders with the given piece style and orientation, e.g.
+    /// `println!("{}", board.display(PieceStyle::Unicode, BoardOrientation::BlackAtBottom))`
+    pub(crate) fn display(&self, style: PieceStyle, orientation: BoardOrientation) -> BoardDisplay<'_> {
+        BoardDisplay {
+            board: self,
+            style,
+            orientation,
+        }
+    }
+}
+
+/// Writes an 8x8 grid with file labels along the top and rank labels down the side, in the same layout as the
+/// board diagrams in this crate's doc comments:
+///
+/// ```text
+///      A   B   C   D   E   F   G   H
+///  8 |   |   |   |   |   |   |   |   |
+///  7 |   |   |   | x |   |   |   |   |
+/// ```
+///
+/// `square_char` decides what is drawn in each square.
+fn write_grid(
+    f: &mut fmt::Formatter<'_>,
+    orientation: BoardOrientation,
+    square_char: impl Fn(CoordinatePosition) -> char,
+) -> fmt::Result {
+    let (files, ranks): (Vec<XCoordinate>, Vec<YCoordinate>) = match orientation {
+        BoardOrientation::WhiteAtBottom => (
+            XCoordinate::ALL.to_vec(),
+            YCoordinate::ALL.into_iter().rev().collect(),
+        ),
+        BoardOrientation::BlackAtBottom => (
+            XCoordinate::ALL.into_iter().rev().collect(),
+            YCoordinate::ALL.to_vec(),
+        ),
+    };
+
+    write!(f, "   ")?;
+    for &x in &files {
+        write!(
+            f,
+            "  {} ",
+            CoordinateConversion::<char>::to_value(x).to_ascii_uppercase()
+        )?;
+    }
+    writeln!(f)?;
+
+    for &y in &ranks {
+        write!(f, " {} |", CoordinateConversion::<char>::to_value(y))?;
+        for &x in &files {
+            write!(f, " {} |", square_char(CoordinatePosition { x, y }))?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for BoardDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_grid(f, self.orientation, |position| {
+            match (self.board.piece_at(position), self.style) {
+                (Some(piece), PieceStyle::Ascii) => piece
+                    .to_string()
+                    .chars()
+                    .next()
+                    .expect("Every piece displays as one char"),
+                (Some(piece), PieceStyle::Unicode) => piece.to_figurine(),
+                (None, _) => ' ',
+            }
+        })
+    }
+}
+
+/// Draws the board with ASCII pieces and white at the bottom. Use `BoardBitmasks::display` for other styles.
+impl fmt::Display for BoardBitmasks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(PieceStyle::Ascii, BoardOrientation::WhiteAtBottom)
+            .fmt(f)
+    }
+}
+
+/// A renderable view of a single bitmask, such as a move mask, pin mask, or attack map, drawn as a grid with an
+/// `x` on every set square.
+pub(crate) struct BitmaskDisplay {
+    bitmask: u64,
+    orientation: BoardOrientation,
+}
+
+impl BitmaskDisplay {
+    pub(crate) fn new(bitmask: u64, orientation: BoardOrientation) -> Self {
+        Self {
+            bitmask,
+            orientation,
+        }
+    }
+}
+
+impl fmt::Display for BitmaskDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_grid(f, self.orientation, |position| {
+            match position.to_bitmask() & self.bitmask != 0 {
+                true => 'x',
+                false => ' ',
+            }
+        })
+    }
+}
+
+/// Draws the bitmask as a grid with white at the bottom. Raw `u64` attack maps can be drawn the same way
+/// through `BitmaskDisplay::new`.
+impl<T> fmt::Display for Bitmask<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        BitmaskDisplay::new(self.mask, BoardOrientation::WhiteAtBottom).fmt(f)
+    }
+}
+
+/// Asserts that two `u64` bitmasks are equal, drawing both as grids in the panic message if they are not,
+/// so that a failing move or pin mask test shows which squares differ.
+#[macro_export]
+macro_rules! assert_bitmask_eq {
+    ($left:expr, $right:expr) => {{
+        let (left, right): (u64, u64) = ($left, $right);
+        if left != right {
+            use $crate::chess_state::board_display::{BitmaskDisplay, BoardOrientation};
+            panic!(
+                "assertion `left == right` failed\n left:\n{}\nright:\n{}",
+                BitmaskDisplay::new(left, BoardOrientation::WhiteAtBottom),
+                BitmaskDisplay::new(right, BoardOrientation::WhiteAtBottom)
+            );
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    mod board_display {
+        use crate::chess_state::{
+            board_bitmask::BoardBitmasks,
+            board_display::{BoardOrientation, PieceStyle},
+        };
+
+        #[test]
+        fn draws_starting_position_when_using_display_trait() {
+            // arrange
+            let board = BoardBitmasks::default();
+            let expected_output = concat!(
+                "     A   B   C   D   E   F   G   H \n",
+                " 8 | r | n | b | q | k | b | n | r |\n",
+                " 7 | p | p | p | p | p | p | p | p |\n",
+                " 6 |   |   |   |   |   |   |   |   |\n",
+                " 5 |   |   |   |   |   |   |   |   |\n",
+                " 4 |   |   |   |   |   |   |   |   |\n",
+                " 3 |   |   |   |   |   |   |   |   |\n",
+                " 2 | P | P | P | P | P | P | P | P |\n",
+                " 1 | R | N | B | Q | K | B | N | R |\n",
+            );
+            // act
+            let output = board.to_string();
+            // assert
+            assert_eq!(output, expected_output)
+        }
+
+        #[test]
+        fn draws_figurines_from_blacks_side_when_flipped_with_unicode_style() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act
+            let output = board
+                .display(PieceStyle::Unicode, BoardOrientation::BlackAtBottom)
+                .to_string();
+            let lines: Vec<&str> = output.lines().collect();
+            // assert
+            assert_eq!(lines[0], "     H   G   F   E   D   C   B   A ");
+            assert_eq!(lines[1], " 1 | ♖ | ♘ | ♗ | ♔ | ♕ | ♗ | ♘ | ♖ |");
+            assert_eq!(lines[8], " 8 | ♜ | ♞ | ♝ | ♚ | ♛ | ♝ | ♞ | ♜ |");
+        }
+    }
+
+    mod bitmask_display {
+        use crate::{
+            bitmask::generic::Bitmask,
+            chess_state::{
+                board_display::{BitmaskDisplay, BoardOrientation},
+                coordinates::{XCoordinate::*, YCoordinate::*},
+            },
+            WhiteKnights,
+        };
+
+        #[test]
+        fn marks_set_squares_when_drawing_a_bitmask() {
+            // arrange
+            let knights = Bitmask::<WhiteKnights>::from_u64((B as u64 | G as u64) & One as u64);
+            // act
+            let output = knights.to_string();
+            let lines: Vec<&str> = output.lines().collect();
+            // assert
+            assert_eq!(lines.len(), 9);
+            assert_eq!(lines[8], " 1 |   | x |   |   |   |   | x |   |");
+            assert!(lines[1..8].iter().all(|line| !line.contains('x')));
+        }
+
+        #[test]
+        fn marks_same_squares_when_drawing_a_raw_attack_map_from_blacks_side() {
+            // arrange
+            let attack_map = (A as u64) & (Eight as u64);
+            // act
+            let output = BitmaskDisplay::new(attack_map, BoardOrientation::BlackAtBottom).to_string();
+            let lines: Vec<&str> = output.lines().collect();
+            // assert
+            assert_eq!(lines[8], " 8 |   |   |   |   |   |   |   | x |");
+        }
+    }
+
+    mod assert_bitmask_eq {
+        use crate::chess_state::coordinates::{XCoordinate::*, YCoordinate::*};
+
+        #[test]
+        fn passes_when_bitmasks_are_equal() {
+            assert_bitmask_eq!(A as u64 & One as u64, A as u64 & One as u64);
+        }
+
+        #[test]
+        #[should_panic(expected = " 1 | x |")]
+        fn draws_both_grids_when_bitmasks_differ() {
+            assert_bitmask_eq!(A as u64 & One as u64, H as u64 & One as u64);
+        }
+    }
+}
@@ -10,8 +10,12 @@ use crate::{
 pub(crate) enum BoardHashMapError {
     #[error("Tried to insert a piece to filled position {0}, containing {1}, without passing the replace flag")]
     InsertedIntoFilledBoardPositionWithNoReplace(CoordinatePosition, PieceEnum),
+
+    #[error("Tried to move a piece from empty position {0}")]
+    RelocatedFromEmptyBoardPosition(CoordinatePosition),
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct BoardHashMap {
     map: HashMap<u8, PieceEnum>,
 }
@@ -65,6 +69,33 @@ impl BoardHashMap {
         let key = shared::single_bit_bitmask_to_u8(&position.to_bitmask());
         self.map.get(&key)
     }
+
+    /// Removes and returns the piece at `position`, or `None` if it was already empty
+    pub(crate) fn remove(&mut self, position: CoordinatePosition) -> Option<PieceEnum> {
+        let key = shared::single_bit_bitmask_to_u8(&position.to_bitmask());
+        self.map.remove(&key)
+    }
+
+    /// Moves the piece at `from` to `to`. Uses the replace flag in the same way as `insert`, so a piece can only
+    /// land on an occupied position when replacing it is intended, like a capture. Returns the replaced piece, if any.
+    pub(crate) fn relocate(
+        &mut self,
+        from: CoordinatePosition,
+        to: CoordinatePosition,
+        replace: bool,
+    ) -> Result<Option<PieceEnum>, BoardHashMapError> {
+        use BoardHashMapError::RelocatedFromEmptyBoardPosition;
+
+        let piece = *self.get(from).ok_or(RelocatedFromEmptyBoardPosition(from))?;
+        if from == to {
+            return Ok(None);
+        }
+        let replaced = self.get(to).copied();
+        // check the destination before lifting the piece, so a failed move leaves the map untouched
+        self.insert(to, piece, replace)?;
+        self.remove(from);
+        Ok(replaced)
+    }
 }
 
 #[cfg(test)]
@@ -81,4 +112,108 @@ mod tests {
             assert!(new_board_hash_map.map.is_empty())
         }
     }
+
+    mod remove {
+        use crate::chess_state::{
+            board_hash_map::BoardHashMap, chess_pieces::PieceEnum,
+            coordinate_point::CoordinatePosition,
+        };
+
+        #[test]
+        fn returns_removed_piece_and_empties_position_when_position_is_filled() {
+            // arrange
+            let mut map = BoardHashMap::new();
+            let e4 = CoordinatePosition::from_str("e4").expect("valid position");
+            map.insert(e4, PieceEnum::WhiteKnight, false)
+                .expect("position is empty");
+
+            // act
+            let removed = map.remove(e4);
+            let removed_again = map.remove(e4);
+
+            // assert
+            assert_eq!(removed, Some(PieceEnum::WhiteKnight));
+            assert_eq!(removed_again, None);
+            assert_eq!(map.get(e4), None)
+        }
+    }
+
+    mod relocate {
+        use crate::chess_state::{
+            board_hash_map::{BoardHashMap, BoardHashMapError},
+            chess_pieces::PieceEnum,
+            coordinate_point::CoordinatePosition,
+        };
+
+        fn position(input: &str) -> CoordinatePosition {
+            CoordinatePosition::from_str(input).expect("valid position")
+        }
+
+        #[test]
+        fn moves_piece_when_destination_is_empty() {
+            // arrange
+            let mut map = BoardHashMap::new();
+            map.insert(position("g1"), PieceEnum::WhiteKnight, false)
+                .expect("position is empty");
+
+            // act
+            let replaced = map
+                .relocate(position("g1"), position("f3"), false)
+                .expect("destination is empty");
+
+            // assert
+            assert_eq!(replaced, None);
+            assert_eq!(map.get(position("g1")), None);
+            assert_eq!(map.get(position("f3")), Some(&PieceEnum::WhiteKnight))
+        }
+
+        #[test]
+        fn captures_piece_when_destination_is_filled_and_replace_flag_is_passed() {
+            // arrange
+            let mut map = BoardHashMap::new();
+            map.insert(position("e4"), PieceEnum::WhitePawn, false)
+                .expect("position is empty");
+            map.insert(position("d5"), PieceEnum::BlackPawn, false)
+                .expect("position is empty");
+
+            // act
+            let replaced = map
+                .relocate(position("e4"), position("d5"), true)
+                .expect("replace flag passed");
+
+            // assert
+            assert_eq!(replaced, Some(PieceEnum::BlackPawn));
+            assert_eq!(map.get(position("d5")), Some(&PieceEnum::WhitePawn));
+            assert_eq!(map.get(position("e4")), None)
+        }
+
+        #[test]
+        fn returns_correct_errors_and_leaves_map_unchanged_when_move_is_invalid() {
+            // arrange
+            let mut map = BoardHashMap::new();
+            map.insert(position("e4"), PieceEnum::WhitePawn, false)
+                .expect("position is empty");
+            map.insert(position("d5"), PieceEnum::BlackPawn, false)
+                .expect("position is empty");
+            let before = map.clone();
+
+            // act
+            let from_empty = map.relocate(position("a1"), position("a2"), true);
+            let onto_filled = map.relocate(position("e4"), position("d5"), false);
+
+            // assert
+            assert!(matches!(
+                from_empty,
+                Err(BoardHashMapError::RelocatedFromEmptyBoardPosition(_))
+            ));
+            assert!(matches!(
+                onto_filled,
+                Err(BoardHashMapError::InsertedIntoFilledBoardPositionWithNoReplace(
+                    _,
+                    PieceEnum::BlackPawn
+                ))
+            ));
+            assert_eq!(map, before)
+        }
+    }
 }
@@ -0,0 +1,386 @@
+use thiserror::Error;
+
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    coordinates::{CoordinateConversion, XCoordinate, YCoordinate},
+    moves::shared::CastleType,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub(crate) enum CastlingError {
+    #[error("char {0} is not a valid castling right, expects one of KQkq or a file letter a-h / A-H")]
+    InvalidCastlingChar(char),
+
+    #[error("Castling right {0} given, but there is no king on its back rank")]
+    NoKingOnBackRank(char),
+
+    #[error("Castling right {0} given, but there is no rook on the back rank to castle with")]
+    NoRookForCastlingRight(char),
+}
+
+/// Selects how castling rights are written into the castling field of a FEN string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CastlingNotation {
+    /// `KQkq` for the outermost rooks, falling back to the rook's file letter only when a right
+    /// refers to an inner rook. Identical to standard FEN for classical start positions.
+    XFen,
+    /// Always uses the rook's file letter, e.g. `HAha` for the classical start position
+    ShredderFen,
+}
+
+/// Tracks which castling moves are still available, storing the file of the rook each right belongs to.
+/// Storing the file rather than a flag is what lets Chess960 positions castle with rooks on any file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CastlingRights {
+    pub white_short: Option<XCoordinate>,
+    pub white_long: Option<XCoordinate>,
+    pub black_short: Option<XCoordinate>,
+    pub black_long: Option<XCoordinate>,
+}
+
+impl CastlingRights {
+    /// Initialises castling rights with no castling available for either side
+    pub(crate) fn none() -> Self {
+        Self {
+            white_short: None,
+            white_long: None,
+            black_short: None,
+            black_long: None,
+        }
+    }
+
+    /// Initialises full castling rights for the classical starting position, with rooks on the A and H files
+    pub(crate) fn standard() -> Self {
+        Self {
+            white_short: Some(XCoordinate::H),
+            white_long: Some(XCoordinate::A),
+            black_short: Some(XCoordinate::H),
+            black_long: Some(XCoordinate::A),
+        }
+    }
+
+    /// Returns the rook file for the given side (`white: bool`) and castle type, if that right is still held
+    pub(crate) fn get(&self, white: bool, castle_type: CastleType) -> Option<XCoordinate> {
+        match (white, castle_type) {
+            (true, CastleType::ShortCastle) => self.white_short,
+            (true, CastleType::LongCastle) => self.white_long,
+            (false, CastleType::ShortCastle) => self.black_short,
+            (false, CastleType::LongCastle) => self.black_long,
+        }
+    }
+
+    /// Sets (or with `None`, removes) the rook file for the given side and castle type
+    pub(crate) fn set(&mut self, white: bool, castle_type: CastleType, rook_file: Option<XCoordinate>) {
+        match (white, castle_type) {
+            (true, CastleType::ShortCastle) => self.white_short = rook_file,
+            (true, CastleType::LongCastle) => self.white_long = rook_file,
+            (false, CastleType::ShortCastle) => self.black_short = rook_file,
+            (false, CastleType::LongCastle) => self.black_long = rook_file,
+        }
+    }
+
+    /// Removes both castling rights for one side, e.g. after its king has moved
+    pub(crate) fn remove_for_side(&mut self, white: bool) {
+        self.set(white, CastleType::ShortCastle, None);
+        self.set(white, CastleType::LongCastle, None);
+    }
+
+    /// Removes any rights invalidated by a move touching the squares in `touched` (a bitmask of the
+    /// start and end squares of the move). `board` must be the position *before* the move is made.
+    ///
+    /// A king leaving its square loses both rights, while a castling rook that moves or is captured
+    /// loses only its own right.
+    pub(crate) fn update_for_move(&mut self, board: &BoardBitmasks, touched: u64) {
+        for white in [true, false] {
+            let (back_rank, king_bitmask) = match white {
+                true => (YCoordinate::One as u64, board.white_kings.mask),
+                false => (YCoordinate::Eight as u64, board.black_kings.mask),
+            };
+            if touched & king_bitmask & back_rank != 0 {
+                self.remove_for_side(white);
+                continue;
+            }
+            for castle_type in [CastleType::ShortCastle, CastleType::LongCastle] {
+                if let Some(rook_file) = self.get(white, castle_type) {
+                    if touched & (rook_file as u64) & back_rank != 0 {
+                        self.set(white, castle_type, None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses the castling field of a FEN string. Accepts standard FEN / X-FEN (`KQkq`), Shredder-FEN
+    /// (`HAha`), and mixtures of the two, so `board` is needed to find the king and rooks each right refers to.
+    pub(crate) fn from_fen_field(field: &str, board: &BoardBitmasks) -> Result<Self, CastlingError> {
+        let mut output = Self::none();
+        if field == "-" {
+            return Ok(output);
+        }
+
+        for ch in field.chars() {
+            let white = ch.is_ascii_uppercase();
+            let (back_rank, king_bitmask, rook_bitmask) = match white {
+                true => (
+                    YCoordinate::One as u64,
+                    board.white_kings.mask,
+                    board.white_rooks.mask,
+                ),
+                false => (
+                    YCoordinate::Eight as u64,
+                    board.black_kings.mask,
+                    board.black_rooks.mask,
+                ),
+            };
+            let king_on_back_rank = king_bitmask & back_rank;
+            if king_on_back_rank == 0 {
+                return Err(CastlingError::NoKingOnBackRank(ch));
+            }
+            let king_file: XCoordinate = CoordinateConversion::<u64>::try_from_value(
+                1u64 << king_on_back_rank.trailing_zeros(),
+            )
+            .expect("Single bit bitmask always has one x coordinate");
+
+            // files on the back rank holding a rook, ordered from the A file to the H file
+            let rook_files: Vec<XCoordinate> = XCoordinate::ALL
+                .into_iter()
+                .filter(|&file| rook_bitmask & (file as u64) & back_rank != 0)
+                .collect();
+
+            let (castle_type, rook_file) = match ch.to_ascii_lowercase() {
+                'k' => (
+                    CastleType::ShortCastle,
+                    // the outermost rook on the h-side of the king
+                    rook_files
+                        .iter()
+                        .rev()
+                        .find(|file| file.to_index() > king_file.to_index())
+                        .copied(),
+                ),
+                'q' => (
+                    CastleType::LongCastle,
+                    // the outermost rook on the a-side of the king
+                    rook_files
+                        .iter()
+                        .find(|file| file.to_index() < king_file.to_index())
+                        .copied(),
+                ),
+                'a'..='h' => {
+                    let file: XCoordinate = CoordinateConversion::<char>::try_from_value(ch)
+                        .map_err(|_| CastlingError::InvalidCastlingChar(ch))?;
+                    let castle_type = match file.to_index() > king_file.to_index() {
+                        true => CastleType::ShortCastle,
+                        false => CastleType::LongCastle,
+                    };
+                    (castle_type, rook_files.contains(&file).then_some(file))
+                }
+                _ => return Err(CastlingError::InvalidCastlingChar(ch)),
+            };
+
+            match rook_file {
+                Some(file) => output.set(white, castle_type, Some(file)),
+                None => return Err(CastlingError::NoRookForCastlingRight(ch)),
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Writes the castling field of a FEN string in the given `notation`, or `-` if no rights remain
+    pub(crate) fn to_fen_field(self, board: &BoardBitmasks, notation: CastlingNotation) -> String {
+        let mut output = String::new();
+        for (white, castle_type) in [
+            (true, CastleType::ShortCastle),
+            (true, CastleType::LongCastle),
+            (false, CastleType::ShortCastle),
+            (false, CastleType::LongCastle),
+        ] {
+            let Some(rook_file) = self.get(white, castle_type) else {
+                continue;
+            };
+
+            let (back_rank, rook_bitmask) = match white {
+                true => (YCoordinate::One as u64, board.white_rooks.mask),
+                false => (YCoordinate::Eight as u64, board.black_rooks.mask),
+            };
+            // a rook is outermost when no other rook sits between it and the edge of the board
+            let is_outermost = XCoordinate::ALL
+                .into_iter()
+                .filter(|file| match castle_type {
+                    CastleType::ShortCastle => file.to_index() > rook_file.to_index(),
+                    CastleType::LongCastle => file.to_index() < rook_file.to_index(),
+                })
+                .all(|file| rook_bitmask & (file as u64) & back_rank == 0);
+
+            let ch = match (notation, is_outermost, castle_type) {
+                (CastlingNotation::XFen, true, CastleType::ShortCastle) => 'k',
+                (CastlingNotation::XFen, true, CastleType::LongCastle) => 'q',
+                _ => CoordinateConversion::<char>::to_value(rook_file),
+            };
+            output.push(match white {
+                true => ch.to_ascii_uppercase(),
+                false => ch,
+            });
+        }
+
+        if output.is_empty() {
+            output.push('-');
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod from_fen_field {
+        use crate::chess_state::{
+            board_bitmask::BoardBitmasks,
+            castling::{CastlingError, CastlingRights},
+            coordinates::XCoordinate,
+        };
+
+        #[test]
+        fn returns_standard_rights_when_given_kqkq_for_starting_position() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act
+            let rights = CastlingRights::from_fen_field("KQkq", &board).expect("valid field");
+            // assert
+            assert_eq!(rights, CastlingRights::standard())
+        }
+
+        #[test]
+        fn returns_same_rights_when_given_shredder_fen_for_starting_position() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act
+            let rights = CastlingRights::from_fen_field("HAha", &board).expect("valid field");
+            // assert
+            assert_eq!(rights, CastlingRights::standard())
+        }
+
+        #[test]
+        fn resolves_rook_files_when_given_chess960_position() {
+            // arrange
+            // index 0 is BBQNNRKR, with the rooks on the F and H files
+            let board = BoardBitmasks::from_chess960_index(0).expect("valid index");
+            // act
+            let x_fen_rights = CastlingRights::from_fen_field("KQkq", &board).expect("valid field");
+            let shredder_rights =
+                CastlingRights::from_fen_field("HFhf", &board).expect("valid field");
+            // assert
+            assert_eq!(x_fen_rights.white_short, Some(XCoordinate::H));
+            assert_eq!(x_fen_rights.white_long, Some(XCoordinate::F));
+            assert_eq!(x_fen_rights, shredder_rights)
+        }
+
+        #[test]
+        fn returns_no_rights_when_given_dash() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act
+            let rights = CastlingRights::from_fen_field("-", &board).expect("valid field");
+            // assert
+            assert_eq!(rights, CastlingRights::none())
+        }
+
+        #[test]
+        fn returns_correct_errors_when_given_invalid_fields() {
+            // arrange
+            let board = BoardBitmasks::default();
+            let mut no_rooks = BoardBitmasks::default();
+            no_rooks.white_rooks.mask = 0;
+            // act
+            let invalid_char = CastlingRights::from_fen_field("KX", &board);
+            let missing_rook = CastlingRights::from_fen_field("K", &no_rooks);
+            let wrong_file = CastlingRights::from_fen_field("B", &board);
+            // assert
+            assert_eq!(invalid_char, Err(CastlingError::InvalidCastlingChar('X')));
+            assert_eq!(missing_rook, Err(CastlingError::NoRookForCastlingRight('K')));
+            assert_eq!(wrong_file, Err(CastlingError::NoRookForCastlingRight('B')))
+        }
+    }
+
+    mod to_fen_field {
+        use crate::{
+            board_diagram,
+            chess_state::{
+                board_bitmask::BoardBitmasks,
+                castling::{CastlingNotation, CastlingRights},
+                coordinates::XCoordinate::*,
+            },
+        };
+
+        #[test]
+        fn writes_kqkq_and_haha_when_in_starting_position() {
+            // arrange
+            let board = BoardBitmasks::default();
+            let rights = CastlingRights::standard();
+            // act
+            let x_fen = rights.to_fen_field(&board, CastlingNotation::XFen);
+            let shredder_fen = rights.to_fen_field(&board, CastlingNotation::ShredderFen);
+            // assert
+            assert_eq!(x_fen, "KQkq");
+            assert_eq!(shredder_fen, "HAha")
+        }
+
+        #[test]
+        fn writes_file_letter_when_rook_is_not_outermost() {
+            // arrange
+            // white king on e1 with rooks on f1 and h1, castling short with the inner f1 rook
+            let board = board_diagram!("8/8/8/8/8/8/8/4KR1R").board;
+            let mut rights = CastlingRights::none();
+            rights.white_short = Some(F);
+            // act
+            let x_fen = rights.to_fen_field(&board, CastlingNotation::XFen);
+            // assert
+            assert_eq!(x_fen, "F")
+        }
+
+        #[test]
+        fn writes_dash_when_no_rights_remain() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act
+            let output = CastlingRights::none().to_fen_field(&board, CastlingNotation::XFen);
+            // assert
+            assert_eq!(output, "-")
+        }
+    }
+
+    mod update_for_move {
+        use crate::chess_state::{
+            board_bitmask::BoardBitmasks,
+            castling::CastlingRights,
+            coordinates::{XCoordinate::*, YCoordinate::*},
+        };
+
+        #[test]
+        fn removes_both_rights_when_king_moves() {
+            // arrange
+            let board = BoardBitmasks::default();
+            let mut rights = CastlingRights::standard();
+            // act
+            rights.update_for_move(&board, (E as u64 & One as u64) | (E as u64 & Two as u64));
+            // assert
+            assert_eq!(rights.white_short, None);
+            assert_eq!(rights.white_long, None);
+            assert_eq!(rights.black_short, Some(H));
+            assert_eq!(rights.black_long, Some(A))
+        }
+
+        #[test]
+        fn removes_one_right_when_rook_is_captured() {
+            // arrange
+            let board = BoardBitmasks::default();
+            let mut rights = CastlingRights::standard();
+            // act
+            // something lands on h8, capturing the black rook
+            rights.update_for_move(&board, (G as u64 & Seven as u64) | (H as u64 & Eight as u64));
+            // assert
+            assert_eq!(rights.black_short, None);
+            assert_eq!(rights.black_long, Some(A));
+            assert_eq!(rights.white_short, Some(H))
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use thiserror::Error;
+
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    board_hash_map::BoardHashMap,
+    chess_pieces::PieceEnum::{self, WhiteBishop, WhiteKing, WhiteKnight, WhitePawn, WhiteQueen, WhiteRook},
+    coordinate_point::CoordinatePosition,
+    coordinates::{XCoordinate, YCoordinate},
+};
+
+/// The Chess960 index of the classical starting position, `RNBQKBNR`
+#[cfg(test)]
+pub(crate) const CLASSICAL_START_POSITION_INDEX: u16 = 518;
+
+/// The number of distinct Chess960 starting positions
+pub(crate) const CHESS960_START_POSITION_COUNT: u16 = 960;
+
+#[derive(Debug, Error, PartialEq)]
+pub(crate) enum Chess960Error {
+    #[error("Chess960 start position index {0} is out of range 0-959")]
+    IndexOutOfRange(u16),
+}
+
+/// Every way of placing the two knights onto the five squares left after the bishops and queen are placed,
+/// in the order used by the Scharnagl numbering scheme.
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// Calculates the white back rank, from the A file to the H file, for a Chess960 start position
+/// using the standard Scharnagl numbering, so index `518` is the classical `RNBQKBNR`.
+///
+/// # Errors
+///
+/// * `Chess960Error::IndexOutOfRange` if `index` is not in the range 0-959.
+pub(crate) fn chess960_back_rank(index: u16) -> Result<[PieceEnum; 8], Chess960Error> {
+    if index >= CHESS960_START_POSITION_COUNT {
+        return Err(Chess960Error::IndexOutOfRange(index));
+    }
+
+    let mut back_rank: [Option<PieceEnum>; 8] = [None; 8];
+    let index = index as usize;
+
+    // the light squared bishop goes on one of the b, d, f, or h files
+    back_rank[(index % 4) * 2 + 1] = Some(WhiteBishop);
+    let index = index / 4;
+    // the dark squared bishop goes on one of the a, c, e, or g files
+    back_rank[(index % 4) * 2] = Some(WhiteBishop);
+    let index = index / 4;
+
+    // the queen goes on the nth of the six remaining empty squares
+    let empty_files: Vec<usize> = (0..8).filter(|&file| back_rank[file].is_none()).collect();
+    back_rank[empty_files[index % 6]] = Some(WhiteQueen);
+    let index = index / 6;
+
+    // the knights take two of the five remaining squares
+    let empty_files: Vec<usize> = (0..8).filter(|&file| back_rank[file].is_none()).collect();
+    let (first_knight, second_knight) = KNIGHT_PLACEMENTS[index];
+    back_rank[empty_files[first_knight]] = Some(WhiteKnight);
+    back_rank[empty_files[second_knight]] = Some(WhiteKnight);
+
+    // the last three squares are always rook, king, rook, so the king sits between its rooks
+    let empty_files: Vec<usize> = (0..8).filter(|&file| back_rank[file].is_none()).collect();
+    back_rank[empty_files[0]] = Some(WhiteRook);
+    back_rank[empty_files[1]] = Some(WhiteKing);
+    back_rank[empty_files[2]] = Some(WhiteRook);
+
+    Ok(back_rank.map(|piece| piece.expect("Every file has been filled")))
+}
+
+impl BoardBitmasks {
+    /// Initialises a chess board in one of the 960 Fischer Random starting positions, with the black pieces
+    /// mirroring the white pieces and both sides' pawns on their usual ranks.
+    ///
+    /// # Errors
+    ///
+    /// * `Chess960Error::IndexOutOfRange` if `index` is not in the range 0-959.
+    pub(crate) fn from_chess960_index(index: u16) -> Result<Self, Chess960Error> {
+        let back_rank = chess960_back_rank(index)?;
+
+        let mut map = BoardHashMap::new();
+        for (file, piece) in XCoordinate::ALL.into_iter().zip(back_rank) {
+            for (y, piece) in [
+                (YCoordinate::One, piece),
+                (YCoordinate::Two, WhitePawn),
+                (YCoordinate::Seven, WhitePawn.swap_colour()),
+                (YCoordinate::Eight, piece.swap_colour()),
+            ] {
+                map.insert(CoordinatePosition { x: file, y }, piece, false)
+                    .expect("Each square is only filled once");
+            }
+        }
+
+        Ok(Self::from_board_hash_map(&map))
+    }
+
+    /// Finds the Chess960 index of this board if it is one of the 960 starting positions, or `None` otherwise
+    pub(crate) fn to_chess960_index(self) -> Option<u16> {
+        // read the white back rank, then undo each step of `chess960_back_rank`
+        let mut back_rank: Vec<Option<PieceEnum>> = XCoordinate::ALL
+            .into_iter()
+            .map(|x| self.piece_at(CoordinatePosition { x, y: YCoordinate::One }))
+            .collect();
+
+        let bishop_files: Vec<usize> = (0..8)
+            .filter(|&file| back_rank[file] == Some(WhiteBishop))
+            .collect();
+        let [first_bishop, second_bishop] = bishop_files[..] else {
+            return None;
+        };
+        let (light_bishop, dark_bishop) = match first_bishop % 2 {
+            1 => (first_bishop, second_bishop),
+            _ => (second_bishop, first_bishop),
+        };
+        if light_bishop % 2 != 1 || dark_bishop % 2 != 0 {
+            return None;
+        }
+        back_rank[light_bishop] = None;
+        back_rank[dark_bishop] = None;
+
+        // with the bishops lifted off, the queen and knights are numbered by their order among what is left
+        let mut remaining: Vec<PieceEnum> = back_rank.into_iter().flatten().collect();
+        let queen = remaining.iter().position(|&piece| piece == WhiteQueen)?;
+        remaining.remove(queen);
+
+        let knight_files: Vec<usize> = (0..remaining.len())
+            .filter(|&file| remaining[file] == WhiteKnight)
+            .collect();
+        let [first_knight, second_knight] = knight_files[..] else {
+            return None;
+        };
+        let knights = KNIGHT_PLACEMENTS
+            .iter()
+            .position(|&placement| placement == (first_knight, second_knight))?;
+
+        let index = (light_bishop / 2 + 4 * (dark_bishop / 2 + 4 * (queen + 6 * knights))) as u16;
+        // the rest of the board (rooks, king, pawns, and black pieces) still has to match exactly
+        match Self::from_chess960_index(index) {
+            Ok(board) if board == self => Some(index),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod chess960_back_rank {
+        use crate::chess_state::{
+            chess960::{chess960_back_rank, Chess960Error, CHESS960_START_POSITION_COUNT},
+            chess_pieces::PieceEnum::*,
+        };
+
+        #[test]
+        fn returns_classical_back_rank_when_given_index_518() {
+            // arrange
+            let expected_output = [
+                WhiteRook,
+                WhiteKnight,
+                WhiteBishop,
+                WhiteQueen,
+                WhiteKing,
+                WhiteBishop,
+                WhiteKnight,
+                WhiteRook,
+            ];
+            // act
+            let output = chess960_back_rank(518).expect("valid index");
+            // assert
+            assert_eq!(output, expected_output)
+        }
+
+        #[test]
+        fn returns_known_back_ranks_when_given_first_and_last_index() {
+            // arrange
+            let expected_first = [
+                WhiteBishop,
+                WhiteBishop,
+                WhiteQueen,
+                WhiteKnight,
+                WhiteKnight,
+                WhiteRook,
+                WhiteKing,
+                WhiteRook,
+            ];
+            let expected_last = [
+                WhiteRook,
+                WhiteKing,
+                WhiteRook,
+                WhiteKnight,
+                WhiteKnight,
+                WhiteQueen,
+                WhiteBishop,
+                WhiteBishop,
+            ];
+            // act
+            let first = chess960_back_rank(0).expect("valid index");
+            let last = chess960_back_rank(959).expect("valid index");
+            // assert
+            assert_eq!(first, expected_first);
+            assert_eq!(last, expected_last)
+        }
+
+        #[test]
+        fn places_bishops_on_opposite_colours_and_king_between_rooks_when_given_any_index() {
+            for index in 0..CHESS960_START_POSITION_COUNT {
+                // arrange + act
+                let back_rank = chess960_back_rank(index).expect("valid index");
+                let files_of = |piece| {
+                    (0..8)
+                        .filter(|&file| back_rank[file] == piece)
+                        .collect::<Vec<usize>>()
+                };
+                let bishops = files_of(WhiteBishop);
+                let rooks = files_of(WhiteRook);
+                let king = files_of(WhiteKing);
+                // assert
+                assert_eq!(bishops.len(), 2);
+                assert_ne!(bishops[0] % 2, bishops[1] % 2);
+                assert_eq!(king.len(), 1);
+                assert!(rooks[0] < king[0] && king[0] < rooks[1]);
+            }
+        }
+
+        #[test]
+        fn returns_correct_error_when_given_out_of_range_index() {
+            // act
+            let output = chess960_back_rank(960);
+            // assert
+            assert_eq!(output, Err(Chess960Error::IndexOutOfRange(960)))
+        }
+    }
+
+    mod from_chess960_index {
+        use crate::chess_state::{
+            board_bitmask::BoardBitmasks,
+            chess960::{CHESS960_START_POSITION_COUNT, CLASSICAL_START_POSITION_INDEX},
+        };
+
+        #[test]
+        fn matches_default_board_when_given_classical_index() {
+            // act
+            let board = BoardBitmasks::from_chess960_index(CLASSICAL_START_POSITION_INDEX)
+                .expect("valid index");
+            // assert
+            assert_eq!(board, BoardBitmasks::default())
+        }
+
+        #[test]
+        fn round_trips_through_to_chess960_index_when_given_every_index() {
+            for index in 0..CHESS960_START_POSITION_COUNT {
+                // act
+                let board = BoardBitmasks::from_chess960_index(index).expect("valid index");
+                // assert
+                assert_eq!(board.to_chess960_index(), Some(index));
+            }
+        }
+
+        #[test]
+        fn returns_no_index_when_board_is_not_a_start_position() {
+            // arrange
+            let mut board = BoardBitmasks::default();
+            // push the e pawn forward a square
+            board.white_pawns.mask ^= 0x00_00_00_00_00_08_08_00;
+            // act + assert
+            assert_eq!(board.to_chess960_index(), None)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum PieceEnum {
     WhitePawn,
     WhiteKnight,
@@ -16,6 +16,73 @@ pub(crate) enum PieceEnum {
     BlackKing,
 }
 
+impl PieceEnum {
+    /// Every piece type, white pieces first, in the same order as the `BoardBitmasks` fields
+    pub(crate) const ALL: [PieceEnum; 12] = [
+        PieceEnum::WhitePawn,
+        PieceEnum::WhiteKnight,
+        PieceEnum::WhiteBishop,
+        PieceEnum::WhiteRook,
+        PieceEnum::WhiteQueen,
+        PieceEnum::WhiteKing,
+        PieceEnum::BlackPawn,
+        PieceEnum::BlackKnight,
+        PieceEnum::BlackBishop,
+        PieceEnum::BlackRook,
+        PieceEnum::BlackQueen,
+        PieceEnum::BlackKing,
+    ];
+
+    /// Converts a FEN style piece letter (`PNBRQK` for white, `pnbrqk` for black) into a piece,
+    /// the reverse of the `Display` implementation
+    pub(crate) fn from_char(ch: char) -> Option<Self> {
+        use PieceEnum::*;
+        match ch {
+            'P' => Some(WhitePawn),
+            'N' => Some(WhiteKnight),
+            'B' => Some(WhiteBishop),
+            'R' => Some(WhiteRook),
+            'Q' => Some(WhiteQueen),
+            'K' => Some(WhiteKing),
+            'p' => Some(BlackPawn),
+            'n' => Some(BlackKnight),
+            'b' => Some(BlackBishop),
+            'r' => Some(BlackRook),
+            'q' => Some(BlackQueen),
+            'k' => Some(BlackKing),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for white pieces and `false` for black pieces
+    pub(crate) fn is_white(self) -> bool {
+        use PieceEnum::*;
+        matches!(
+            self,
+            WhitePawn | WhiteKnight | WhiteBishop | WhiteRook | WhiteQueen | WhiteKing
+        )
+    }
+
+    /// Returns the same piece type in the opposite colour, e.g. `WhiteKnight` becomes `BlackKnight`
+    pub(crate) fn swap_colour(self) -> Self {
+        use PieceEnum::*;
+        match self {
+            WhitePawn => BlackPawn,
+            WhiteKnight => BlackKnight,
+            WhiteBishop => BlackBishop,
+            WhiteRook => BlackRook,
+            WhiteQueen => BlackQueen,
+            WhiteKing => BlackKing,
+            BlackPawn => WhitePawn,
+            BlackKnight => WhiteKnight,
+            BlackBishop => WhiteBishop,
+            BlackRook => WhiteRook,
+            BlackQueen => WhiteQueen,
+            BlackKing => WhiteKing,
+        }
+    }
+}
+
 impl fmt::Display for PieceEnum {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use PieceEnum::*;
@@ -123,4 +190,33 @@ mod tests {
         // assert
         assert_eq!(output, expected_output)
     }
+
+    #[test]
+    fn round_trips_through_display_when_using_from_char() {
+        use crate::PieceEnum;
+        // act
+        let output: Vec<Option<PieceEnum>> = PieceEnum::ALL
+            .iter()
+            .map(|p| PieceEnum::from_char(p.to_string().chars().next().expect("one char")))
+            .collect();
+        // assert
+        assert_eq!(output, PieceEnum::ALL.map(Some).to_vec());
+        assert_eq!(PieceEnum::from_char('x'), None)
+    }
+
+    #[test]
+    fn swaps_to_same_piece_type_when_swapping_colour() {
+        use crate::PieceEnum::*;
+        // arrange
+        let white_pieces = [WhitePawn, WhiteKnight, WhiteBishop, WhiteRook, WhiteQueen, WhiteKing];
+        let black_pieces = [BlackPawn, BlackKnight, BlackBishop, BlackRook, BlackQueen, BlackKing];
+        // act
+        let swapped: Vec<_> = white_pieces.iter().map(|p| p.swap_colour()).collect();
+        let swapped_back: Vec<_> = swapped.iter().map(|p| p.swap_colour()).collect();
+        // assert
+        assert_eq!(swapped, black_pieces.to_vec());
+        assert_eq!(swapped_back, white_pieces.to_vec());
+        assert!(white_pieces.iter().all(|p| p.is_white()));
+        assert!(black_pieces.iter().all(|p| !p.is_white()));
+    }
 }
@@ -53,6 +53,46 @@ pub(crate) enum YCoordinate {
     Eight = 0xFF << (7 * 8),
 }
 
+impl XCoordinate {
+    /// Every file on the board, ordered from the A file to the H file
+    pub(crate) const ALL: [XCoordinate; 8] = [
+        XCoordinate::A,
+        XCoordinate::B,
+        XCoordinate::C,
+        XCoordinate::D,
+        XCoordinate::E,
+        XCoordinate::F,
+        XCoordinate::G,
+        XCoordinate::H,
+    ];
+
+    /// Returns the index of the file counting from the A file, so `A` is `0` and `H` is `7`
+    pub(crate) fn to_index(self) -> usize {
+        // the A file sits in the highest bit of each rank
+        7 - (self as u64).trailing_zeros() as usize
+    }
+}
+
+impl YCoordinate {
+    /// Every rank on the board, ordered from rank one to rank eight
+    pub(crate) const ALL: [YCoordinate; 8] = [
+        YCoordinate::One,
+        YCoordinate::Two,
+        YCoordinate::Three,
+        YCoordinate::Four,
+        YCoordinate::Five,
+        YCoordinate::Six,
+        YCoordinate::Seven,
+        YCoordinate::Eight,
+    ];
+
+    /// Returns the index of the rank counting from rank one, so `One` is `0` and `Eight` is `7`
+    #[cfg(test)]
+    pub(crate) fn to_index(self) -> usize {
+        (self as u64).trailing_zeros() as usize / 8
+    }
+}
+
 pub trait CoordinateConversion<T>: Sized {
     type Error;
 
@@ -460,4 +500,28 @@ mod coordinates_tests {
             assert_eq!(output, expected_output)
         }
     }
+
+    mod to_index {
+        use crate::chess_state::coordinates::{XCoordinate, YCoordinate};
+
+        #[test]
+        fn returns_file_indices_from_a_to_h_when_converting_x_coordinates() {
+            // arrange
+            let expected_output: Vec<usize> = (0..8).collect();
+            // act
+            let output: Vec<usize> = XCoordinate::ALL.iter().map(|x| x.to_index()).collect();
+            // assert
+            assert_eq!(output, expected_output)
+        }
+
+        #[test]
+        fn returns_rank_indices_from_one_to_eight_when_converting_y_coordinates() {
+            // arrange
+            let expected_output: Vec<usize> = (0..8).collect();
+            // act
+            let output: Vec<usize> = YCoordinate::ALL.iter().map(|y| y.to_index()).collect();
+            // assert
+            assert_eq!(output, expected_output)
+        }
+    }
 }
@@ -0,0 +1,275 @@
+use thiserror::Error;
+
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    board_hash_map::BoardHashMap,
+    castling::{CastlingError, CastlingNotation, CastlingRights},
+    chess_pieces::PieceEnum,
+    coordinate_point::CoordinatePosition,
+    coordinates::{CoordinateError, XCoordinate, YCoordinate},
+    game_state::GameState,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub(crate) enum FenError {
+    #[error("FEN {0} should have between 4 and 6 space separated fields")]
+    InvalidFieldCount(String),
+
+    #[error("Piece placement {0} should describe 8 ranks of 8 squares each")]
+    InvalidPiecePlacement(String),
+
+    #[error("char {0} is not a valid piece or empty square count in a FEN piece placement")]
+    InvalidPieceChar(char),
+
+    #[error("Side to move {0} should be w or b")]
+    InvalidSideToMove(String),
+
+    #[error("Castling error: {0}")]
+    CastlingError(#[from] CastlingError),
+
+    #[error("Coordinate error: {0}")]
+    CoordinateError(#[from] CoordinateError),
+
+    #[error("Clock {0} is not a valid move count")]
+    InvalidClock(String),
+}
+
+impl BoardBitmasks {
+    /// Parses the piece placement field of a FEN string (e.g. `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR`),
+    /// which lists the ranks from eight down to one, each from the A file to the H file.
+    pub(crate) fn from_fen_piece_placement(placement: &str) -> Result<Self, FenError> {
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::InvalidPiecePlacement(placement.to_string()));
+        }
+
+        let mut map = BoardHashMap::new();
+        for (rank, y) in ranks.iter().zip(YCoordinate::ALL.into_iter().rev()) {
+            let mut file_index: usize = 0;
+            for ch in rank.chars() {
+                if let Some(empty_squares) = ch.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                    file_index += empty_squares as usize;
+                    continue;
+                }
+                let piece = PieceEnum::from_char(ch).ok_or(FenError::InvalidPieceChar(ch))?;
+                let x = *XCoordinate::ALL
+                    .get(file_index)
+                    .ok_or(FenError::InvalidPiecePlacement(placement.to_string()))?;
+                map.insert(CoordinatePosition { x, y }, piece, false)
+                    .expect("Each square is only visited once");
+                file_index += 1;
+            }
+            if file_index != 8 {
+                return Err(FenError::InvalidPiecePlacement(placement.to_string()));
+            }
+        }
+
+        Ok(Self::from_board_hash_map(&map))
+    }
+
+    /// Writes the board as the piece placement field of a FEN string
+    pub(crate) fn to_fen_piece_placement(self) -> String {
+        let ranks: Vec<String> = YCoordinate::ALL
+            .into_iter()
+            .rev()
+            .map(|y| {
+                let mut rank = String::new();
+                let mut empty_squares = 0;
+                for x in XCoordinate::ALL {
+                    match self.piece_at(CoordinatePosition { x, y }) {
+                        Some(piece) => {
+                            if empty_squares > 0 {
+                                rank.push_str(&empty_squares.to_string());
+                                empty_squares = 0;
+                            }
+                            rank.push_str(&piece.to_string());
+                        }
+                        None => empty_squares += 1,
+                    }
+                }
+                if empty_squares > 0 {
+                    rank.push_str(&empty_squares.to_string());
+                }
+                rank
+            })
+            .collect();
+        ranks.join("/")
+    }
+}
+
+impl GameState {
+    /// Parses a FEN string into a full game state. The castling field may use standard FEN, X-FEN, or
+    /// Shredder-FEN notation, so Chess960 positions are supported. The two move clocks may be left off,
+    /// in which case they default to `0` and `1`.
+    pub(crate) fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if !(4..=6).contains(&fields.len()) {
+            return Err(FenError::InvalidFieldCount(fen.to_string()));
+        }
+
+        let board = BoardBitmasks::from_fen_piece_placement(fields[0])?;
+
+        let white_to_move = match fields[1] {
+            "w" => true,
+            "b" => false,
+            other => return Err(FenError::InvalidSideToMove(other.to_string())),
+        };
+
+        let castling_rights = CastlingRights::from_fen_field(fields[2], &board)?;
+
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => Some(CoordinatePosition::from_str(square)?),
+        };
+
+        let parse_clock = |field: Option<&&str>, default: u32| match field {
+            Some(clock) => clock
+                .parse::<u32>()
+                .map_err(|_| FenError::InvalidClock(clock.to_string())),
+            None => Ok(default),
+        };
+        let halfmove_clock = parse_clock(fields.get(4), 0)?;
+        let fullmove_number = parse_clock(fields.get(5), 1)?;
+
+        Ok(Self {
+            board,
+            white_to_move,
+            castling_rights,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+        })
+    }
+
+    /// Writes the game state as a FEN string, writing the castling field in the given `notation`
+    pub(crate) fn to_fen(self, notation: CastlingNotation) -> String {
+        let side_to_move = match self.white_to_move {
+            true => "w",
+            false => "b",
+        };
+        let en_passant = match self.en_passant {
+            Some(square) => square.to_string(),
+            None => "-".to_string(),
+        };
+        format!(
+            "{} {} {} {} {} {}",
+            self.board.to_fen_piece_placement(),
+            side_to_move,
+            self.castling_rights.to_fen_field(&self.board, notation),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod from_fen {
+        use crate::chess_state::{
+            castling::CastlingRights,
+            coordinate_point::CoordinatePosition,
+            fen::FenError,
+            game_state::GameState,
+        };
+
+        #[test]
+        fn matches_default_game_when_given_starting_fen() {
+            // act
+            let game =
+                GameState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                    .expect("valid fen");
+            // assert
+            assert_eq!(game, GameState::default())
+        }
+
+        #[test]
+        fn reads_every_field_when_given_midgame_fen() {
+            // act
+            let game = GameState::from_fen(
+                "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w Kq c6 3 12",
+            )
+            .expect("valid fen");
+            // assert
+            assert!(game.white_to_move);
+            assert_eq!(
+                game.en_passant,
+                Some(CoordinatePosition::from_str("c6").expect("valid square"))
+            );
+            assert_eq!(game.castling_rights.white_long, None);
+            assert_eq!(game.castling_rights.black_short, None);
+            assert_eq!(game.halfmove_clock, 3);
+            assert_eq!(game.fullmove_number, 12)
+        }
+
+        #[test]
+        fn defaults_move_clocks_when_they_are_left_off() {
+            // act
+            let game = GameState::from_fen("8/8/8/8/8/8/8/K6k b - -").expect("valid fen");
+            // assert
+            assert!(!game.white_to_move);
+            assert_eq!(game.castling_rights, CastlingRights::none());
+            assert_eq!(game.halfmove_clock, 0);
+            assert_eq!(game.fullmove_number, 1)
+        }
+
+        #[test]
+        fn returns_correct_errors_when_given_invalid_fens() {
+            // act
+            let too_few_fields = GameState::from_fen("8/8/8/8/8/8/8/8 w");
+            let short_rank = GameState::from_fen("8/8/8/8/8/8/8/7 w - -");
+            let bad_piece = GameState::from_fen("8/8/8/8/8/8/8/7x w - -");
+            let bad_side = GameState::from_fen("8/8/8/8/8/8/8/8 x - -");
+            // assert
+            assert_eq!(
+                too_few_fields,
+                Err(FenError::InvalidFieldCount("8/8/8/8/8/8/8/8 w".into()))
+            );
+            assert_eq!(
+                short_rank,
+                Err(FenError::InvalidPiecePlacement("8/8/8/8/8/8/8/7".into()))
+            );
+            assert_eq!(bad_piece, Err(FenError::InvalidPieceChar('x')));
+            assert_eq!(bad_side, Err(FenError::InvalidSideToMove("x".into())))
+        }
+    }
+
+    mod round_trips {
+        use crate::chess_state::{castling::CastlingNotation, game_state::GameState};
+
+        #[test]
+        fn completes_round_trip_when_to_and_from_fen() {
+            // arrange
+            let fens = [
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - e3 0 1",
+            ];
+            for fen in fens {
+                // act
+                let output = GameState::from_fen(fen)
+                    .expect("valid fen")
+                    .to_fen(CastlingNotation::XFen);
+                // assert
+                assert_eq!(output, fen)
+            }
+        }
+
+        #[test]
+        fn completes_round_trip_when_using_shredder_fen_for_chess960() {
+            // arrange
+            let game = GameState::from_chess960_index(0).expect("valid index");
+            // act
+            let shredder_fen = game.to_fen(CastlingNotation::ShredderFen);
+            let x_fen = game.to_fen(CastlingNotation::XFen);
+            // assert
+            assert_eq!(
+                shredder_fen,
+                "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+            );
+            assert_eq!(x_fen, "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+            assert_eq!(GameState::from_fen(&shredder_fen), Ok(game));
+            assert_eq!(GameState::from_fen(&x_fen), Ok(game))
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    castling::CastlingRights,
+    chess960::Chess960Error,
+    coordinate_point::CoordinatePosition,
+};
+#[cfg(test)]
+use crate::chess_state::moves::{chess_flip::ChessFlip, shared::CastleType};
+
+/// The full state of a game: the pieces on the board plus everything a FEN string records alongside them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct GameState {
+    pub board: BoardBitmasks,
+    pub white_to_move: bool,
+    pub castling_rights: CastlingRights,
+    pub en_passant: Option<CoordinatePosition>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+impl GameState {
+    /// Initialises a game with an empty board, white to move, and no castling rights
+    pub(crate) fn new() -> Self {
+        Self {
+            board: BoardBitmasks::new(),
+            white_to_move: true,
+            castling_rights: CastlingRights::none(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    /// Initialises a game in the classical starting position
+    pub(crate) fn default() -> Self {
+        Self {
+            board: BoardBitmasks::default(),
+            castling_rights: CastlingRights::standard(),
+            ..Self::new()
+        }
+    }
+
+    /// Initialises a game in one of the 960 Fischer Random starting positions, with full castling rights
+    /// tracked against whichever files the rooks start on.
+    pub(crate) fn from_chess960_index(index: u16) -> Result<Self, Chess960Error> {
+        let board = BoardBitmasks::from_chess960_index(index)?;
+        // the king always starts between the rooks, so the outermost rooks are the castling rooks
+        let castling_rights = CastlingRights::from_fen_field("KQkq", &board)
+            .expect("Every start position has a king between two rooks");
+        Ok(Self {
+            board,
+            castling_rights,
+            ..Self::new()
+        })
+    }
+
+    /// Returns the same position with the colours swapped: the board mirrored top to bottom with every piece
+    /// changing colour, the other side to move, and the castling rights and en passant square following their
+    /// pieces. The clocks are kept as they are.
+    #[cfg(test)]
+    pub(crate) fn colour_flipped(self) -> Self {
+        let mut castling_rights = self.castling_rights;
+        for castle_type in [CastleType::ShortCastle, CastleType::LongCastle] {
+            // rooks keep their files when the board is mirrored top to bottom
+            castling_rights.set(true, castle_type, self.castling_rights.get(false, castle_type));
+            castling_rights.set(false, castle_type, self.castling_rights.get(true, castle_type));
+        }
+        Self {
+            board: self.board.colour_flipped(),
+            white_to_move: !self.white_to_move,
+            castling_rights,
+            en_passant: self.en_passant.map(|position| {
+                CoordinatePosition::from_bitmask(position.to_bitmask().flip_vertical())
+                    .expect("Mirrored single bit bitmasks are always valid positions")
+            }),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod from_chess960_index {
+        use crate::chess_state::{
+            chess960::CLASSICAL_START_POSITION_INDEX,
+            coordinates::XCoordinate,
+            game_state::GameState,
+        };
+
+        #[test]
+        fn matches_default_game_when_given_classical_index() {
+            // act
+            let game = GameState::from_chess960_index(CLASSICAL_START_POSITION_INDEX)
+                .expect("valid index");
+            // assert
+            assert_eq!(game, GameState::default())
+        }
+
+        #[test]
+        fn tracks_rook_files_when_given_chess960_index() {
+            // act
+            // index 0 is BBQNNRKR
+            let game = GameState::from_chess960_index(0).expect("valid index");
+            // assert
+            assert_eq!(game.castling_rights.white_short, Some(XCoordinate::H));
+            assert_eq!(game.castling_rights.white_long, Some(XCoordinate::F));
+            assert_eq!(game.castling_rights.black_short, Some(XCoordinate::H));
+            assert_eq!(game.castling_rights.black_long, Some(XCoordinate::F));
+        }
+    }
+
+    mod colour_flipped {
+        use crate::chess_state::game_state::GameState;
+
+        #[test]
+        fn swaps_side_castling_rights_and_en_passant_when_flipped() {
+            // arrange
+            let game = GameState::from_fen("r3k2r/8/8/8/3pP3/8/8/R3K3 b Qk e3 0 1").expect("valid fen");
+            let expected = GameState::from_fen("r3k3/8/8/3Pp3/8/8/8/R3K2R w Kq e6 0 1").expect("valid fen");
+            // act
+            let output = game.colour_flipped();
+            // assert
+            assert_eq!(output, expected);
+            assert_eq!(output.colour_flipped(), game)
+        }
+    }
+}
@@ -2,5 +2,14 @@ pub(crate) mod coordinates;
 pub(crate) mod coordinate_point;
 pub(crate) mod chess_pieces;
 pub(crate) mod board_bitmask;
+#[cfg(test)]
+pub(crate) mod board_diagram;
+pub(crate) mod board_display;
 pub(crate) mod board_hash_map;
-pub(crate) mod moves;
\ No newline at end of file
+pub(crate) mod castling;
+pub(crate) mod chess960;
+pub(crate) mod fen;
+pub(crate) mod game_state;
+pub(crate) mod moves;
+pub(crate) mod position_editor;
+pub(crate) mod zobrist;
@@ -13,15 +13,34 @@ pub trait KnightAttackMaps {
 }
 
 pub trait BishopAttackMaps {
+    #[cfg(test)]
     fn calculate_unconstrained_bishop_attack_maps(self) -> Self;
+    fn calculate_bishop_attack_maps(self, occupied: Self) -> Self;
 }
 
 pub trait QueenAttackMaps {
+    #[cfg(test)]
     fn calculate_unconstrained_queen_attack_maps(self) -> Self;
+    fn calculate_queen_attack_maps(self, occupied: Self) -> Self;
 }
 
 pub trait RookAttackMaps {
+    #[cfg(test)]
     fn calculate_unconstrained_rook_attack_maps(self) -> Self;
+    fn calculate_rook_attack_maps(self, occupied: Self) -> Self;
+}
+
+/// Casts a ray from every set bit in `pieces` in `direction`, stopping each ray on the first square in `occupied`.
+/// The blocking square itself is included, since it can be attacked even though it cannot be passed through.
+fn calculate_ray_attack_map(pieces: u64, direction: ChessDirection, occupied: u64) -> u64 {
+    let mut ray = pieces.shift_move(direction);
+    let mut output = ray;
+    // only rays that landed on an empty square can continue
+    while ray & !occupied != 0 {
+        ray = (ray & !occupied).shift_move(direction);
+        output |= ray;
+    }
+    output
 }
 
 pub trait KingAttackMaps {
@@ -54,6 +73,7 @@ impl KnightAttackMaps for u64 {
 }
 
 impl BishopAttackMaps for u64 {
+    #[cfg(test)]
     fn calculate_unconstrained_bishop_attack_maps(self) -> Self {
         let up_right: u64 = (0..7).fold(self.shift_move(ChessDirection::UpRight), |current, _| {
             current | current.shift_move(ChessDirection::UpRight)
@@ -71,9 +91,17 @@ impl BishopAttackMaps for u64 {
         });
         up_right | down_right | down_left | up_left
     }
+
+    fn calculate_bishop_attack_maps(self, occupied: Self) -> Self {
+        calculate_ray_attack_map(self, ChessDirection::UpRight, occupied)
+            | calculate_ray_attack_map(self, ChessDirection::DownRight, occupied)
+            | calculate_ray_attack_map(self, ChessDirection::DownLeft, occupied)
+            | calculate_ray_attack_map(self, ChessDirection::UpLeft, occupied)
+    }
 }
 
 impl RookAttackMaps for u64 {
+    #[cfg(test)]
     fn calculate_unconstrained_rook_attack_maps(self) -> Self {
         let up: u64 = (0..7).fold(self.shift_move(ChessDirection::Up), |current, _| {
             current | current.shift_move(ChessDirection::Up)
@@ -89,9 +117,17 @@ impl RookAttackMaps for u64 {
         });
         up | right | down | left
     }
+
+    fn calculate_rook_attack_maps(self, occupied: Self) -> Self {
+        calculate_ray_attack_map(self, ChessDirection::Up, occupied)
+            | calculate_ray_attack_map(self, ChessDirection::Right, occupied)
+            | calculate_ray_attack_map(self, ChessDirection::Down, occupied)
+            | calculate_ray_attack_map(self, ChessDirection::Left, occupied)
+    }
 }
 
 impl QueenAttackMaps for u64 {
+    #[cfg(test)]
     fn calculate_unconstrained_queen_attack_maps(self) -> Self {
         let up: u64 = (0..7).fold(self.shift_move(ChessDirection::Up), |current, _| {
             current | current.shift_move(ChessDirection::Up)
@@ -121,6 +157,10 @@ impl QueenAttackMaps for u64 {
         });
         up | up_right | right | down_right | down | down_left | left | up_left
     }
+
+    fn calculate_queen_attack_maps(self, occupied: Self) -> Self {
+        self.calculate_rook_attack_maps(occupied) | self.calculate_bishop_attack_maps(occupied)
+    }
 }
 
 impl KingAttackMaps for u64 {
@@ -142,7 +182,8 @@ mod test {
         use crate::chess_state::{
             coordinates::{XCoordinate::*, YCoordinate::*},
             moves::attack_maps::{
-                BishopAttackMaps, BlackPawnAttackMaps, KnightAttackMaps, WhitePawnAttackMaps,
+                BishopAttackMaps, BlackPawnAttackMaps, KnightAttackMaps, QueenAttackMaps,
+                RookAttackMaps, WhitePawnAttackMaps,
             },
         };
 
@@ -214,5 +255,78 @@ mod test {
             // assert
             assert_eq!(attack_map, expected_attack_map)
         }
+
+        #[test]
+        fn rook_attack_map_stops_at_blockers_when_trait_used_on_u64() {
+            // arrange
+            let rook = (D as u64) & (Four as u64);
+            // blockers on d6 and f4, with a piece behind the d6 blocker that must not be reached
+            let occupied = ((D as u64) & (Six as u64))
+                | ((F as u64) & (Four as u64))
+                | ((D as u64) & (Seven as u64));
+            let expected_attack_map = ((D as u64) & (Five as u64))
+                | ((D as u64) & (Six as u64))
+                | ((E as u64) & (Four as u64))
+                | ((F as u64) & (Four as u64))
+                | ((D as u64) & (Three as u64))
+                | ((D as u64) & (Two as u64))
+                | ((D as u64) & (One as u64))
+                | ((C as u64) & (Four as u64))
+                | ((B as u64) & (Four as u64))
+                | ((A as u64) & (Four as u64));
+
+            // act
+            let attack_map = rook.calculate_rook_attack_maps(occupied);
+
+            // assert
+            assert_eq!(attack_map, expected_attack_map)
+        }
+
+        #[test]
+        fn bishop_attack_map_matches_unconstrained_map_when_board_is_empty() {
+            // arrange
+            let bishop = (F as u64) & (Four as u64);
+
+            // act
+            let attack_map = bishop.calculate_bishop_attack_maps(0);
+
+            // assert
+            assert_eq!(
+                attack_map,
+                bishop.calculate_unconstrained_bishop_attack_maps()
+            )
+        }
+
+        #[test]
+        fn queen_attack_map_matches_unconstrained_map_when_board_is_empty() {
+            // arrange
+            let queen = (C as u64) & (Three as u64);
+
+            // act
+            let attack_map = queen.calculate_queen_attack_maps(0);
+
+            // assert
+            assert_eq!(
+                attack_map,
+                queen.calculate_unconstrained_queen_attack_maps()
+            )
+        }
+
+        #[test]
+        fn queen_attack_map_combines_rook_and_bishop_maps_when_trait_used_on_u64() {
+            // arrange
+            let queen = (C as u64) & (Three as u64);
+            let occupied = ((C as u64) & (Five as u64)) | ((E as u64) & (Five as u64));
+
+            // act
+            let attack_map = queen.calculate_queen_attack_maps(occupied);
+
+            // assert
+            assert_eq!(
+                attack_map,
+                queen.calculate_rook_attack_maps(occupied)
+                    | queen.calculate_bishop_attack_maps(occupied)
+            )
+        }
     }
 }
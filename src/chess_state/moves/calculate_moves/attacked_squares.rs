@@ -0,0 +1,106 @@
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    moves::attack_maps::{
+        BishopAttackMaps, BlackPawnAttackMaps, KingAttackMaps, KnightAttackMaps, RookAttackMaps,
+        WhitePawnAttackMaps,
+    },
+};
+
+impl BoardBitmasks {
+    /// Returns a bitmask (`u64`) of every square attacked by one side, where `white: bool` picks the attacking side.
+    /// Sliding pieces are blocked by `occupied` rather than the board's own occupancy, so callers can look
+    /// "through" pieces that are about to move (e.g. the king and rook while castling).
+    pub(crate) fn calculate_attacked_squares(&self, white: bool, occupied: u64) -> u64 {
+        let (pawns, knights, bishops, rooks, queens, kings) = match white {
+            true => (
+                self.white_pawns.mask,
+                self.white_knights.mask,
+                self.white_bishops.mask,
+                self.white_rooks.mask,
+                self.white_queens.mask,
+                self.white_kings.mask,
+            ),
+            false => (
+                self.black_pawns.mask,
+                self.black_knights.mask,
+                self.black_bishops.mask,
+                self.black_rooks.mask,
+                self.black_queens.mask,
+                self.black_kings.mask,
+            ),
+        };
+
+        let pawn_attacks = match white {
+            true => pawns.calculate_unconstrained_white_pawn_attack_maps(),
+            false => pawns.calculate_unconstrained_black_pawn_attack_maps(),
+        };
+
+        pawn_attacks
+            | knights.calculate_unconstrained_knight_maps()
+            | (bishops | queens).calculate_bishop_attack_maps(occupied)
+            | (rooks | queens).calculate_rook_attack_maps(occupied)
+            | kings.calculate_unconstrained_king_attack_maps()
+    }
+
+    /// Checks if the white king (`white = true`) or the black king (`white = false`) is attacked by the opposing side.
+    pub(crate) fn is_king_in_check(&self, white: bool) -> bool {
+        let king_bitmask = match white {
+            true => self.white_kings.mask,
+            false => self.black_kings.mask,
+        };
+        self.calculate_attacked_squares(!white, self.all_pieces.mask) & king_bitmask != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod calculate_attacked_squares {
+        use crate::chess_state::{
+            board_bitmask::BoardBitmasks,
+            coordinates::{XCoordinate::*, YCoordinate::*},
+        };
+
+        #[test]
+        fn covers_ranks_three_and_six_when_in_starting_position() {
+            // arrange
+            let game_board = BoardBitmasks::default();
+
+            // act
+            let white_attacks =
+                game_board.calculate_attacked_squares(true, game_board.all_pieces.mask);
+            let black_attacks =
+                game_board.calculate_attacked_squares(false, game_board.all_pieces.mask);
+
+            // assert
+            assert_eq!(white_attacks & Three as u64, Three as u64);
+            assert_eq!(black_attacks & Six as u64, Six as u64);
+            // nothing can reach past the pawns yet
+            assert_eq!(white_attacks & (Four as u64 | Five as u64), 0);
+            // the corner squares are not defended by anything
+            assert_eq!(white_attacks & (A as u64 & One as u64), 0);
+        }
+    }
+
+    mod is_king_in_check {
+        use crate::board_diagram;
+
+        #[test]
+        fn detects_check_when_rook_has_open_file_to_king() {
+            // arrange
+            let game_board = board_diagram!("4r3/8/8/8/8/8/8/4K3").board;
+
+            // act + assert
+            assert!(game_board.is_king_in_check(true));
+            assert!(!game_board.is_king_in_check(false));
+        }
+
+        #[test]
+        fn finds_no_check_when_rook_is_blocked() {
+            // arrange
+            let game_board = board_diagram!("4r3/8/8/8/8/8/4P3/4K3").board;
+
+            // act + assert
+            assert!(!game_board.is_king_in_check(true));
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    castling::CastlingRights,
+    coordinates::{XCoordinate, YCoordinate},
+    moves::shared::CastleType,
+};
+#[cfg(test)]
+use crate::chess_state::moves::shared::{Move, MoveError};
+
+/// Returns the files the king and rook finish on for each castle type. These are the same in classical chess
+/// and Chess960: the king always ends on the G or C file, with the rook on the F or D file beside it.
+pub(crate) fn get_castling_destination_files(castle_type: CastleType) -> (XCoordinate, XCoordinate) {
+    match castle_type {
+        CastleType::ShortCastle => (XCoordinate::G, XCoordinate::F),
+        CastleType::LongCastle => (XCoordinate::C, XCoordinate::D),
+    }
+}
+
+/// Returns every square on a rank from `start` to `end` inclusive, where both are single bit bitmasks on the
+/// same rank. Order does not matter.
+fn get_rank_span(start: u64, end: u64) -> u64 {
+    let (low, high) = match start < end {
+        true => (start, end),
+        false => (end, start),
+    };
+    // `high - low` sets every bit from `low` up to, but not including, `high`
+    (high - low) | high
+}
+
+impl BoardBitmasks {
+    /// Checks if the white side (`white = true`) or black side (`white = false`) can legally castle right now.
+    ///
+    /// Works for any Chess960 king and rook placement, using the rook file stored in `castling_rights`:
+    /// * the side must still hold the right, with its king and that rook on the back rank,
+    /// * every square the king and rook cross, including where they land, must be empty apart from the king and
+    ///   rook themselves,
+    /// * the king must not be in check, pass through an attacked square, or land in check.
+    pub(crate) fn can_castle(
+        &self,
+        white: bool,
+        castle_type: CastleType,
+        castling_rights: &CastlingRights,
+    ) -> bool {
+        let Some(rook_file) = castling_rights.get(white, castle_type) else {
+            return false;
+        };
+
+        let (back_rank, king_bitmask, rook_bitmask) = match white {
+            true => (
+                YCoordinate::One as u64,
+                self.white_kings.mask,
+                self.white_rooks.mask,
+            ),
+            false => (
+                YCoordinate::Eight as u64,
+                self.black_kings.mask,
+                self.black_rooks.mask,
+            ),
+        };
+
+        let king_position = king_bitmask & back_rank;
+        let rook_position = rook_bitmask & (rook_file as u64) & back_rank;
+        if king_position == 0 || rook_position == 0 {
+            return false;
+        }
+
+        let (king_destination_file, rook_destination_file) =
+            get_castling_destination_files(castle_type);
+        let king_destination = king_destination_file as u64 & back_rank;
+        let rook_destination = rook_destination_file as u64 & back_rank;
+
+        let king_span = get_rank_span(king_position, king_destination);
+        let rook_span = get_rank_span(rook_position, rook_destination);
+        let other_pieces = self.all_pieces.mask & !(king_position | rook_position);
+        if (king_span | rook_span) & other_pieces != 0 {
+            return false;
+        }
+
+        // the king may not start in or pass through check...
+        let attacked_now = self.calculate_attacked_squares(!white, self.all_pieces.mask);
+        if (king_span & !king_destination) & attacked_now != 0 {
+            return false;
+        }
+
+        // ...or land in check once the rook has moved too, since the rook may have been blocking an attacker
+        let occupied_after = other_pieces | king_destination | rook_destination;
+        let attacked_after = self.calculate_attacked_squares(!white, occupied_after);
+        king_destination & attacked_after == 0
+    }
+
+    /// Calculates the castling moves available to the white side (`white = true`) or black side (`white = false`),
+    /// returning them as `Move::Castle`. The rook taking part is found from `castling_rights`. Only the first move
+    /// generator's tests use this, `legal_moves` checks `can_castle` itself.
+    #[cfg(test)]
+    pub(crate) fn calculate_castling_moves(
+        &self,
+        white: bool,
+        castling_rights: &CastlingRights,
+    ) -> Result<Vec<Move>, MoveError> {
+        Ok([CastleType::ShortCastle, CastleType::LongCastle]
+            .into_iter()
+            .filter(|&castle_type| self.can_castle(white, castle_type, castling_rights))
+            .map(Move::Castle)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod get_rank_span {
+        use crate::chess_state::{
+            coordinates::{XCoordinate::*, YCoordinate::*},
+            moves::calculate_moves::king_moves::get_rank_span,
+        };
+
+        #[test]
+        fn returns_inclusive_span_when_given_squares_in_either_order() {
+            // arrange
+            let b8 = B as u64 & Eight as u64;
+            let e8 = E as u64 & Eight as u64;
+            let expected_output = (B as u64 | C as u64 | D as u64 | E as u64) & Eight as u64;
+            // act + assert
+            assert_eq!(get_rank_span(b8, e8), expected_output);
+            assert_eq!(get_rank_span(e8, b8), expected_output);
+            assert_eq!(get_rank_span(e8, e8), e8)
+        }
+    }
+
+    mod can_castle {
+        use crate::chess_state::{
+            castling::CastlingRights, game_state::GameState, moves::shared::CastleType::*,
+        };
+
+        #[test]
+        fn allows_both_castles_when_back_rank_is_clear() {
+            // arrange
+            let game = GameState::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1")
+                .expect("valid fen");
+            // act + assert
+            for white in [true, false] {
+                assert!(game.board.can_castle(white, ShortCastle, &game.castling_rights));
+                assert!(game.board.can_castle(white, LongCastle, &game.castling_rights));
+            }
+        }
+
+        #[test]
+        fn refuses_castle_when_pieces_are_in_the_way() {
+            // arrange
+            let game = GameState::default();
+            // act + assert
+            assert!(!game.board.can_castle(true, ShortCastle, &game.castling_rights));
+            assert!(!game.board.can_castle(true, LongCastle, &game.castling_rights));
+        }
+
+        #[test]
+        fn refuses_castle_when_right_has_been_lost() {
+            // arrange
+            let game = GameState::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w - - 0 1")
+                .expect("valid fen");
+            // act + assert
+            assert!(!game.board.can_castle(true, ShortCastle, &CastlingRights::none()));
+            assert!(!game.board.can_castle(true, ShortCastle, &game.castling_rights));
+        }
+
+        #[test]
+        fn refuses_castle_when_king_is_in_or_passes_through_check() {
+            // arrange
+            // the black rook on e8 checks the white king
+            let in_check = GameState::from_fen("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1")
+                .expect("valid fen");
+            // the black rook on f8 covers f1, but not the squares the king crosses castling long
+            let through_check = GameState::from_fen("5rk1/8/8/8/8/8/8/R3K2R w KQ - 0 1")
+                .expect("valid fen");
+            // act + assert
+            assert!(!in_check.board.can_castle(true, ShortCastle, &in_check.castling_rights));
+            assert!(!in_check.board.can_castle(true, LongCastle, &in_check.castling_rights));
+            assert!(!through_check
+                .board
+                .can_castle(true, ShortCastle, &through_check.castling_rights));
+            assert!(through_check
+                .board
+                .can_castle(true, LongCastle, &through_check.castling_rights));
+        }
+
+        #[test]
+        fn allows_castle_when_only_the_rook_crosses_an_attacked_square() {
+            // arrange
+            // b1 is attacked, but only the rook passes over it when castling long
+            let game = GameState::from_fen("1r4k1/8/8/8/8/8/8/R3K3 w Q - 0 1").expect("valid fen");
+            // act + assert
+            assert!(game.board.can_castle(true, LongCastle, &game.castling_rights));
+        }
+
+        #[test]
+        fn handles_chess960_placements_when_king_and_rook_start_next_to_their_destinations() {
+            // arrange
+            // rook on c1 and king on d1 - castling long swaps them over onto d1 and c1
+            let swapped = GameState::from_fen("6k1/8/8/8/8/8/8/2RK4 w C - 0 1")
+                .expect("valid fen");
+            // king already on g1 with the rook on h1 - castling short only moves the rook
+            let king_in_place = GameState::from_fen("6kr/8/8/8/8/8/8/6KR w H - 0 1")
+                .expect("valid fen");
+            // act + assert
+            assert!(swapped.board.can_castle(true, LongCastle, &swapped.castling_rights));
+            assert!(king_in_place
+                .board
+                .can_castle(true, ShortCastle, &king_in_place.castling_rights));
+        }
+
+        #[test]
+        fn refuses_chess960_castle_when_moving_rook_uncovers_check_on_destination() {
+            // arrange
+            // the rook on b1 shields c1 from the black queen on a1; castling long would uncover it
+            let game = GameState::from_fen("6k1/8/8/8/8/8/8/qRK5 w B - 0 1").expect("valid fen");
+            // act + assert
+            assert!(!game.board.can_castle(true, LongCastle, &game.castling_rights));
+        }
+    }
+
+    mod calculate_castling_moves {
+        use crate::chess_state::{
+            game_state::GameState,
+            moves::shared::{CastleType, Move},
+        };
+
+        #[test]
+        fn returns_castle_moves_when_castling_is_legal() {
+            // arrange
+            let game = GameState::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w Kq - 0 1")
+                .expect("valid fen");
+            // act
+            let white_moves = game
+                .board
+                .calculate_castling_moves(true, &game.castling_rights)
+                .expect("no errors");
+            let black_moves = game
+                .board
+                .calculate_castling_moves(false, &game.castling_rights)
+                .expect("no errors");
+            // assert
+            assert_eq!(white_moves, vec![Move::Castle(CastleType::ShortCastle)]);
+            assert_eq!(black_moves, vec![Move::Castle(CastleType::LongCastle)]);
+        }
+    }
+}
@@ -1,10 +1,18 @@
+mod attacked_squares;
+pub(crate) mod king_moves;
+// the first move generator, replaced by `legal_moves` and only built for its own tests
+#[cfg(test)]
 mod diagonal_moves;
-mod king_moves;
+#[cfg(test)]
 mod knight_moves;
+#[cfg(test)]
 mod pawn_moves;
+#[cfg(test)]
 mod pinned_to_king;
+#[cfg(test)]
 mod straight_moves;
 
+#[cfg(test)]
 #[macro_export]
 macro_rules! log_move_generation {
     ($expr:expr, $function_name:expr) => {
@@ -5,6 +5,7 @@ use log::{debug, error, info};
 use crate::{
     chess_state::{
         board_bitmask::BoardBitmasks,
+        board_display::{BitmaskDisplay, BoardOrientation},
         chess_pieces::PieceEnum,
         coordinate_point::CoordinatePosition,
         coordinates::{XCoordinate, YCoordinate},
@@ -28,7 +29,10 @@ impl BoardBitmasks {
         let start_time = Instant::now();
         info!("Beginning calculation of pawn moves...");
         let occupied = self.all_pieces.mask;
-        debug!("Identified occupied squares: {:?}", occupied);
+        debug!(
+            "Identified occupied squares:\n{}",
+            BitmaskDisplay::new(occupied, BoardOrientation::WhiteAtBottom)
+        );
 
         let mut output: Vec<Move> = Vec::new();
 
@@ -705,14 +709,16 @@ mod tests {
         }
 
         mod capture_left_moves {
-            use crate::chess_state::{
-                board_bitmask::BoardBitmasks,
-                chess_pieces::PieceEnum,
-                coordinate_point::CoordinatePosition,
-                coordinates::{XCoordinate, YCoordinate},
-                moves::{
-                    shared::{CheckType, Move},
-                    standard_move::StandardMove,
+            use crate::{
+                board_diagram,
+                chess_state::{
+                    board_bitmask::BoardBitmasks,
+                    chess_pieces::PieceEnum,
+                    coordinate_point::CoordinatePosition,
+                    moves::{
+                        shared::{CheckType, Move},
+                        standard_move::StandardMove,
+                    },
                 },
             };
 
@@ -736,15 +742,7 @@ mod tests {
             #[test]
             fn identifies_valid_capture_when_caputurable_piece_to_the_left() {
                 // arrange
-                let mut game_board = BoardBitmasks::new();
-                let white_pawn_position = XCoordinate::E as u64 & YCoordinate::Two as u64;
-                let black_rook_position = XCoordinate::D as u64 & YCoordinate::Three as u64;
-                // update gameboard to respect this
-                game_board.white_pawns.mask = white_pawn_position;
-                game_board.white_pieces.mask = white_pawn_position;
-                game_board.black_rooks.mask = black_rook_position;
-                game_board.black_pieces.mask = black_rook_position;
-                game_board.all_pieces.mask = white_pawn_position | black_rook_position;
+                let game_board = board_diagram!("8/8/8/8/8/3r4/4P3/8").board;
 
                 let expected_capture = StandardMove {
                     start_position: CoordinatePosition::from_str("e2").expect("valid position"),
@@ -775,14 +773,16 @@ mod tests {
         }
 
         mod capture_right_moves {
-            use crate::chess_state::{
-                board_bitmask::BoardBitmasks,
-                chess_pieces::PieceEnum,
-                coordinate_point::CoordinatePosition,
-                coordinates::{XCoordinate, YCoordinate},
-                moves::{
-                    shared::{CheckType, Move},
-                    standard_move::StandardMove,
+            use crate::{
+                board_diagram,
+                chess_state::{
+                    board_bitmask::BoardBitmasks,
+                    chess_pieces::PieceEnum,
+                    coordinate_point::CoordinatePosition,
+                    moves::{
+                        shared::{CheckType, Move},
+                        standard_move::StandardMove,
+                    },
                 },
             };
 
@@ -806,15 +806,7 @@ mod tests {
             #[test]
             fn identifies_valid_capture_when_caputurable_piece_to_the_right() {
                 // arrange
-                let mut game_board = BoardBitmasks::new();
-                let white_pawn_position = XCoordinate::E as u64 & YCoordinate::Two as u64;
-                let black_rook_position = XCoordinate::F as u64 & YCoordinate::Three as u64;
-                // update gameboard to respect this
-                game_board.white_pawns.mask = white_pawn_position;
-                game_board.white_pieces.mask = white_pawn_position;
-                game_board.black_rooks.mask = black_rook_position;
-                game_board.black_pieces.mask = black_rook_position;
-                game_board.all_pieces.mask = white_pawn_position | black_rook_position;
+                let game_board = board_diagram!("8/8/8/8/8/5r2/4P3/8").board;
 
                 let expected_capture = StandardMove {
                     start_position: CoordinatePosition::from_str("e2").expect("valid position"),
@@ -843,5 +835,44 @@ mod tests {
                 assert_eq!(capture.clone(), expected_capture)
             }
         }
+
+        mod captures_from_diagram {
+            use crate::{assert_bitmask_eq, board_diagram, chess_state::moves::shared::Move};
+
+            #[test]
+            fn captures_every_marked_piece_when_pawn_can_take_both_ways() {
+                // arrange
+                let diagram = board_diagram!(
+                    "
+                         A   B   C   D   E   F   G   H
+                     5 |   |   |   |   |   |   |   |   |
+                     4 |   | n*| p | r*|   |   |   |   |
+                     3 |   |   | P |   |   |   |   |   |
+                    "
+                );
+
+                // act
+                let mut captures = diagram
+                    .board
+                    .calculate_white_pawn_moves_capture_left()
+                    .expect("should capture the knight");
+                captures.extend(
+                    diagram
+                        .board
+                        .calculate_white_pawn_moves_capture_right()
+                        .expect("should capture the rook"),
+                );
+                let output_bitmask = captures.iter().fold(0, |bitmask: u64, m: &Move| match m {
+                    Move::StandardMove(move_details) => {
+                        bitmask | move_details.end_position.to_bitmask()
+                    }
+                    _ => panic!("No non-standard moves here!"),
+                });
+
+                // assert
+                assert_eq!(captures.len(), 2);
+                assert_bitmask_eq!(output_bitmask, diagram.marked_squares)
+            }
+        }
     }
 }
@@ -319,45 +319,26 @@ mod tests {
     }
 
     mod get_pieces_pinned_to_king {
-        use crate::chess_state::{
-            board_bitmask::BoardBitmasks,
-            coordinates::{XCoordinate, YCoordinate},
-        };
+        use crate::{assert_bitmask_eq, board_diagram};
 
         #[test]
         fn can_detect_pinned_pieces_when_in_simple_pin() {
-            use XCoordinate::*;
-            use YCoordinate::*;
-
             // arrange
             // set up a simple pin - Black bishop onto pawn onto king
-            let game_board = BoardBitmasks {
-                all_pieces: ((E as u64 & Four as u64)
-                    | (F as u64 & Five as u64)
-                    | (G as u64 & Six as u64))
-                    .into(),
-                white_pieces: ((E as u64 & Four as u64) | (F as u64 & Five as u64)).into(),
-                white_pawns: (F as u64 & Five as u64).into(),
-                white_knights: 0.into(),
-                white_bishops: 0.into(),
-                white_rooks: 0.into(),
-                white_queens: 0.into(),
-                white_kings: (E as u64 & Four as u64).into(),
-                black_pieces: (G as u64 & Six as u64).into(),
-                black_pawns: 0.into(),
-                black_knights: 0.into(),
-                black_bishops: (G as u64 & Six as u64).into(),
-                black_rooks: 0.into(),
-                black_queens: 0.into(),
-                black_kings: 0.into(),
-            };
-            let expected_pin = F as u64 & Five as u64;
+            let diagram = board_diagram!(
+                "
+                     A   B   C   D   E   F   G   H
+                 6 |   |   |   |   |   |   | b |   |
+                 5 |   |   |   |   |   | P*|   |   |
+                 4 |   |   |   |   | K |   |   |   |
+                "
+            );
 
             // act
-            let pin = game_board.get_pieces_pinned_to_king(true);
+            let pin = diagram.board.get_pieces_pinned_to_king(true);
 
             // assert
-            assert_eq!(pin, expected_pin)
+            assert_bitmask_eq!(pin, diagram.marked_squares)
         }
     }
 }
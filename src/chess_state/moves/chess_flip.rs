@@ -1,10 +1,13 @@
 pub(crate) trait ChessFlip {
+    #[cfg(test)]
     fn flip_horizontal(self) -> Self;
     fn flip_vertical(self) -> Self;
+    #[cfg(test)]
     fn flip(self) -> Self;
 }
 
 impl ChessFlip for u64 {
+    #[cfg(test)]
     fn flip_horizontal(self) -> Self {
         fn invert_byte(byte: u8) -> u8 {
             let mut new_byte: u8 = 0;
@@ -27,6 +30,7 @@ impl ChessFlip for u64 {
         self.swap_bytes()
     }
 
+    #[cfg(test)]
     fn flip(self) -> Self {
         self.flip_horizontal().flip_vertical()
     }
@@ -0,0 +1,559 @@
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    chess_pieces::PieceEnum,
+    coordinate_point::CoordinatePosition,
+    coordinates::{XCoordinate, YCoordinate},
+    game_state::GameState,
+    moves::{
+        attack_maps::{
+            BishopAttackMaps, BlackPawnAttackMaps, KingAttackMaps, KnightAttackMaps, QueenAttackMaps,
+            RookAttackMaps, WhitePawnAttackMaps,
+        },
+        calculate_moves::king_moves::get_castling_destination_files,
+        chess_move::{
+            ChessDirection::{Down, DownLeft, DownRight, Up, UpLeft, UpRight},
+            ChessShiftMove,
+        },
+        shared::{CastleType, CheckType, Move},
+        standard_move::StandardMove,
+    },
+};
+
+/// The pieces a pawn can promote to, strongest first
+const WHITE_PROMOTIONS: [PieceEnum; 4] = [
+    PieceEnum::WhiteQueen,
+    PieceEnum::WhiteRook,
+    PieceEnum::WhiteBishop,
+    PieceEnum::WhiteKnight,
+];
+const BLACK_PROMOTIONS: [PieceEnum; 4] = [
+    PieceEnum::BlackQueen,
+    PieceEnum::BlackRook,
+    PieceEnum::BlackBishop,
+    PieceEnum::BlackKnight,
+];
+
+/// Finds the squares a piece attacks from a single bit bitmask, given the occupied squares
+type AttackMap = fn(u64, u64) -> u64;
+
+/// Converts a single bit bitmask into its position
+fn position(bitmask: u64) -> CoordinatePosition {
+    CoordinatePosition::from_bitmask(bitmask).expect("Single bit bitmasks are always valid positions")
+}
+
+/// Splits a bitmask into one single bit bitmask per set bit, lowest bit first
+fn single_bits(mut bitmask: u64) -> impl Iterator<Item = u64> {
+    std::iter::from_fn(move || {
+        let bit = bitmask & bitmask.wrapping_neg();
+        bitmask ^= bit;
+        (bit != 0).then_some(bit)
+    })
+}
+
+impl BoardBitmasks {
+    /// Adds `piece` to every square in `bitmask` that does not hold it, and removes it from every square that
+    /// does, keeping the colour and occupancy bitmasks in step
+    pub(crate) fn toggle_piece(&mut self, piece: PieceEnum, bitmask: u64) {
+        let piece_bitmask = match piece {
+            PieceEnum::WhitePawn => &mut self.white_pawns.mask,
+            PieceEnum::WhiteKnight => &mut self.white_knights.mask,
+            PieceEnum::WhiteBishop => &mut self.white_bishops.mask,
+            PieceEnum::WhiteRook => &mut self.white_rooks.mask,
+            PieceEnum::WhiteQueen => &mut self.white_queens.mask,
+            PieceEnum::WhiteKing => &mut self.white_kings.mask,
+            PieceEnum::BlackPawn => &mut self.black_pawns.mask,
+            PieceEnum::BlackKnight => &mut self.black_knights.mask,
+            PieceEnum::BlackBishop => &mut self.black_bishops.mask,
+            PieceEnum::BlackRook => &mut self.black_rooks.mask,
+            PieceEnum::BlackQueen => &mut self.black_queens.mask,
+            PieceEnum::BlackKing => &mut self.black_kings.mask,
+        };
+        *piece_bitmask ^= bitmask;
+        match piece.is_white() {
+            true => self.white_pieces.mask ^= bitmask,
+            false => self.black_pieces.mask ^= bitmask,
+        }
+        self.all_pieces.mask ^= bitmask;
+    }
+}
+
+impl GameState {
+    /// Checks if the side to move is in check
+    pub(crate) fn is_in_check(&self) -> bool {
+        self.board.is_king_in_check(self.white_to_move)
+    }
+
+    /// Pushes a standard move for every square in `targets`, capturing whatever stands there
+    fn push_piece_moves(&self, moves: &mut Vec<Move>, piece: PieceEnum, start: u64, targets: u64) {
+        let start_position = position(start);
+        for target in single_bits(targets) {
+            let end_position = position(target);
+            moves.push(Move::StandardMove(StandardMove::new(
+                start_position,
+                end_position,
+                piece,
+                None,
+                None,
+                self.board.piece_at(end_position).map(|taken| (end_position, taken)),
+                CheckType::None,
+            )));
+        }
+    }
+
+    /// Pushes the pawn moves for the side to move, including double steps, promotions and en passant
+    fn push_pawn_moves(&self, moves: &mut Vec<Move>, captures_only: bool) {
+        let white = self.white_to_move;
+        let (pawn, pawns, enemies, forward, backward, captures, double_step_rank, promotions) = match white {
+            true => (
+                PieceEnum::WhitePawn,
+                self.board.white_pawns.mask,
+                self.board.black_pieces.mask,
+                Up,
+                Down,
+                [(UpLeft, DownRight), (UpRight, DownLeft)],
+                YCoordinate::Three as u64,
+                WHITE_PROMOTIONS,
+            ),
+            false => (
+                PieceEnum::BlackPawn,
+                self.board.black_pawns.mask,
+                self.board.white_pieces.mask,
+                Down,
+                Up,
+                [(DownLeft, UpRight), (DownRight, UpLeft)],
+                YCoordinate::Six as u64,
+                BLACK_PROMOTIONS,
+            ),
+        };
+        let empty = !self.board.all_pieces.mask;
+        let promotion_ranks = YCoordinate::One as u64 | YCoordinate::Eight as u64;
+
+        let mut push = |start: u64, end: u64, en_passant_target: Option<u64>, takes: Option<(u64, PieceEnum)>| {
+            let (start_position, end_position) = (position(start), position(end));
+            let takes = takes.map(|(square, piece)| (position(square), piece));
+            let promotion_pieces: &[PieceEnum] = match end & promotion_ranks != 0 {
+                true => &promotions,
+                false => &[],
+            };
+            if promotion_pieces.is_empty() {
+                moves.push(Move::StandardMove(StandardMove::new(
+                    start_position,
+                    end_position,
+                    pawn,
+                    en_passant_target.map(position),
+                    None,
+                    takes,
+                    CheckType::None,
+                )));
+            }
+            for &promotion in promotion_pieces {
+                moves.push(Move::StandardMove(StandardMove::new(
+                    start_position,
+                    end_position,
+                    pawn,
+                    None,
+                    Some(promotion),
+                    takes,
+                    CheckType::None,
+                )));
+            }
+        };
+
+        let single_steps = pawns.shift_move(forward) & empty;
+        // quiet promotions are as forcing as captures, so they are kept when only captures are asked for
+        let quiet_targets = match captures_only {
+            true => single_steps & promotion_ranks,
+            false => single_steps,
+        };
+        for end in single_bits(quiet_targets) {
+            push(end.shift_move(backward), end, None, None);
+        }
+        if !captures_only {
+            let double_steps = (single_steps & double_step_rank).shift_move(forward) & empty;
+            for end in single_bits(double_steps) {
+                let passed = end.shift_move(backward);
+                push(passed.shift_move(backward), end, Some(passed), None);
+            }
+        }
+
+        for (direction, reverse) in captures {
+            for end in single_bits(pawns.shift_move(direction) & enemies) {
+                let taken = self.board.piece_at(position(end)).map(|piece| (end, piece));
+                push(end.shift_move(reverse), end, None, taken);
+            }
+        }
+
+        if let Some(en_passant) = self.en_passant {
+            let target = en_passant.to_bitmask();
+            // the pawns able to take en passant are those a pawn of the other colour on the target would attack
+            let attackers = match white {
+                true => target.calculate_unconstrained_black_pawn_attack_maps(),
+                false => target.calculate_unconstrained_white_pawn_attack_maps(),
+            } & pawns;
+            let taken_square = target.shift_move(backward);
+            if let Some(taken) = self.board.piece_at(position(taken_square)) {
+                for start in single_bits(attackers) {
+                    push(start, target, None, Some((taken_square, taken)));
+                }
+            }
+        }
+    }
+
+    /// Generates every move for the side to move without checking whether it leaves its own king in check.
+    /// With `captures_only`, only captures and promotions are generated.
+    fn pseudo_legal_moves(&self, captures_only: bool) -> Vec<Move> {
+        let white = self.white_to_move;
+        let board = &self.board;
+        let (own, enemies) = match white {
+            true => (board.white_pieces.mask, board.black_pieces.mask),
+            false => (board.black_pieces.mask, board.white_pieces.mask),
+        };
+        let occupied = board.all_pieces.mask;
+        let allowed = match captures_only {
+            true => enemies,
+            false => !own,
+        };
+
+        let mut moves = Vec::with_capacity(64);
+        self.push_pawn_moves(&mut moves, captures_only);
+
+        let pieces: [(PieceEnum, AttackMap); 5] = match white {
+            true => [
+                (PieceEnum::WhiteKnight, |piece, _| piece.calculate_unconstrained_knight_maps()),
+                (PieceEnum::WhiteBishop, |piece, occupied| piece.calculate_bishop_attack_maps(occupied)),
+                (PieceEnum::WhiteRook, |piece, occupied| piece.calculate_rook_attack_maps(occupied)),
+                (PieceEnum::WhiteQueen, |piece, occupied| piece.calculate_queen_attack_maps(occupied)),
+                (PieceEnum::WhiteKing, |piece, _| piece.calculate_unconstrained_king_attack_maps()),
+            ],
+            false => [
+                (PieceEnum::BlackKnight, |piece, _| piece.calculate_unconstrained_knight_maps()),
+                (PieceEnum::BlackBishop, |piece, occupied| piece.calculate_bishop_attack_maps(occupied)),
+                (PieceEnum::BlackRook, |piece, occupied| piece.calculate_rook_attack_maps(occupied)),
+                (PieceEnum::BlackQueen, |piece, occupied| piece.calculate_queen_attack_maps(occupied)),
+                (PieceEnum::BlackKing, |piece, _| piece.calculate_unconstrained_king_attack_maps()),
+            ],
+        };
+        for (piece, attacks) in pieces {
+            for start in single_bits(board.piece_enum_to_bitmask(piece)) {
+                self.push_piece_moves(&mut moves, piece, start, attacks(start, occupied) & allowed);
+            }
+        }
+
+        if !captures_only {
+            for castle_type in [CastleType::ShortCastle, CastleType::LongCastle] {
+                if board.can_castle(white, castle_type, &self.castling_rights) {
+                    moves.push(Move::Castle(castle_type));
+                }
+            }
+        }
+        moves
+    }
+
+    /// Keeps the moves that do not leave the mover's own king in check
+    fn retain_legal(&self, mut moves: Vec<Move>) -> Vec<Move> {
+        moves.retain(|chess_move| !self.make_move(chess_move).board.is_king_in_check(self.white_to_move));
+        moves
+    }
+
+    /// Generates every legal move for the side to move
+    pub(crate) fn legal_moves(&self) -> Vec<Move> {
+        self.retain_legal(self.pseudo_legal_moves(false))
+    }
+
+    /// Generates the legal captures and promotions for the side to move, the moves a quiescence search looks at
+    pub(crate) fn legal_captures(&self) -> Vec<Move> {
+        self.retain_legal(self.pseudo_legal_moves(true))
+    }
+
+    /// Returns the position after `chess_move` is played. The move is trusted to come from `legal_moves`, so it
+    /// is not checked.
+    pub(crate) fn make_move(&self, chess_move: &Move) -> GameState {
+        let white = self.white_to_move;
+        let mut next = *self;
+        match chess_move {
+            Move::StandardMove(standard_move) => {
+                let start = standard_move.start_position.to_bitmask();
+                let end = standard_move.end_position.to_bitmask();
+                if let Some((square, taken)) = standard_move.takes {
+                    next.board.toggle_piece(taken, square.to_bitmask());
+                }
+                next.board.toggle_piece(standard_move.piece, start);
+                next.board
+                    .toggle_piece(standard_move.promotion.unwrap_or(standard_move.piece), end);
+                next.castling_rights.update_for_move(&self.board, start | end);
+                next.en_passant = standard_move.en_passant_target;
+                let is_pawn = matches!(standard_move.piece, PieceEnum::WhitePawn | PieceEnum::BlackPawn);
+                next.halfmove_clock = match is_pawn || standard_move.takes.is_some() {
+                    true => 0,
+                    false => self.halfmove_clock + 1,
+                };
+            }
+            Move::Castle(castle_type) => {
+                let (king, rook, back_rank) = match white {
+                    true => (PieceEnum::WhiteKing, PieceEnum::WhiteRook, YCoordinate::One as u64),
+                    false => (PieceEnum::BlackKing, PieceEnum::BlackRook, YCoordinate::Eight as u64),
+                };
+                let rook_file = self
+                    .castling_rights
+                    .get(white, *castle_type)
+                    .expect("Castling moves are only generated while the right is held");
+                let (king_file, rook_destination_file) = get_castling_destination_files(*castle_type);
+                // both pieces are lifted before either is placed, since in Chess960 they can land on each
+                // other's start squares
+                next.board
+                    .toggle_piece(king, self.board.piece_enum_to_bitmask(king) & back_rank);
+                next.board.toggle_piece(rook, rook_file as u64 & back_rank);
+                next.board.toggle_piece(king, king_file as u64 & back_rank);
+                next.board.toggle_piece(rook, rook_destination_file as u64 & back_rank);
+                next.castling_rights.remove_for_side(white);
+                next.en_passant = None;
+                next.halfmove_clock = self.halfmove_clock + 1;
+            }
+        }
+        next.white_to_move = !white;
+        if !white {
+            next.fullmove_number += 1;
+        }
+        next
+    }
+
+    /// Returns the position with the turn passed to the other side without a move being played, for null-move
+    /// pruning. The en passant square is lost, as it would be after any real move.
+    pub(crate) fn make_null_move(&self) -> GameState {
+        GameState {
+            white_to_move: !self.white_to_move,
+            en_passant: None,
+            halfmove_clock: self.halfmove_clock + 1,
+            fullmove_number: self.fullmove_number + u32::from(!self.white_to_move),
+            ..*self
+        }
+    }
+
+    /// Writes a move in UCI long algebraic notation, e.g. `e2e4`, `e7e8q`, or `e1g1` for castling, where the
+    /// king's start and end squares are given
+    pub(crate) fn move_to_uci(&self, chess_move: &Move) -> String {
+        match chess_move {
+            Move::StandardMove(standard_move) => {
+                let promotion = standard_move
+                    .promotion
+                    .map(|piece| piece.to_string().to_lowercase())
+                    .unwrap_or_default();
+                format!(
+                    "{}{}{}",
+                    standard_move.start_position, standard_move.end_position, promotion
+                )
+            }
+            Move::Castle(castle_type) => {
+                let (king, back_rank) = match self.white_to_move {
+                    true => (self.board.white_kings.mask, YCoordinate::One as u64),
+                    false => (self.board.black_kings.mask, YCoordinate::Eight as u64),
+                };
+                let (king_file, _) = get_castling_destination_files(*castle_type);
+                let classical_rook_file = match castle_type {
+                    CastleType::ShortCastle => XCoordinate::H,
+                    CastleType::LongCastle => XCoordinate::A,
+                };
+                // Chess960 castling is written as the king taking its own rook, as the king may not move at all
+                // or may land on a square it could also reach with a normal move
+                let destination = match self.castling_rights.get(self.white_to_move, *castle_type) {
+                    Some(rook_file) if king & XCoordinate::E as u64 == 0 || rook_file != classical_rook_file => {
+                        rook_file
+                    }
+                    _ => king_file,
+                };
+                format!(
+                    "{}{}",
+                    position(king & back_rank),
+                    position(destination as u64 & back_rank)
+                )
+            }
+        }
+    }
+
+    /// Finds the legal move written as `uci`. Classical castling may be given either by the king's destination
+    /// or, as Chess960 GUIs do, as the king capturing its own rook.
+    pub(crate) fn find_uci_move(&self, uci: &str) -> Option<Move> {
+        let uci = uci.trim().to_lowercase();
+        self.legal_moves().into_iter().find(|chess_move| {
+            if self.move_to_uci(chess_move) == uci {
+                return true;
+            }
+            let Move::Castle(castle_type) = chess_move else {
+                return false;
+            };
+            let back_rank = match self.white_to_move {
+                true => YCoordinate::One as u64,
+                false => YCoordinate::Eight as u64,
+            };
+            self.castling_rights
+                .get(self.white_to_move, *castle_type)
+                .is_some_and(|rook_file| {
+                    let king = self.move_to_uci(chess_move);
+                    uci == format!("{}{}", &king[..2], position(rook_file as u64 & back_rank))
+                })
+        })
+    }
+
+    /// Counts the leaf positions `depth` moves deep, the standard check of a move generator against known
+    /// totals
+    #[cfg(test)]
+    pub(crate) fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .iter()
+            .map(|chess_move| self.make_move(chess_move).perft(depth - 1))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod perft {
+        use crate::chess_state::game_state::GameState;
+
+        #[test]
+        fn matches_known_totals_when_given_standard_test_positions() {
+            // arrange
+            // totals from the Chess Programming Wiki's perft results
+            let positions = [
+                ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 3, 8_902),
+                ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 2, 2_039),
+                ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3, 2_812),
+                ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 2, 264),
+                ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 2, 1_486),
+            ];
+            for (fen, depth, expected_output) in positions {
+                let game = GameState::from_fen(fen).expect("valid fen");
+                // act + assert
+                assert_eq!(game.perft(depth), expected_output, "{}", fen);
+            }
+        }
+
+        #[test]
+        fn matches_known_total_when_given_chess960_position() {
+            // arrange
+            let game = GameState::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+                .expect("valid fen");
+            // act + assert
+            assert_eq!(game.perft(3), 12_189)
+        }
+    }
+
+    mod legal_captures {
+        use crate::chess_state::{game_state::GameState, moves::shared::Move};
+
+        #[test]
+        fn matches_captures_and_promotions_among_legal_moves() {
+            // arrange
+            let fens = [
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            ];
+            for fen in fens {
+                let game = GameState::from_fen(fen).expect("valid fen");
+                let expected_output: Vec<Move> = game
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|chess_move| {
+                        matches!(chess_move, Move::StandardMove(standard_move)
+                            if standard_move.takes.is_some() || standard_move.promotion.is_some())
+                    })
+                    .collect();
+                // act
+                let mut output = game.legal_captures();
+                // assert
+                assert!(!output.is_empty());
+                output.retain(|chess_move| expected_output.contains(chess_move));
+                assert_eq!(output.len(), expected_output.len(), "{}", fen);
+                assert_eq!(game.legal_captures().len(), expected_output.len(), "{}", fen)
+            }
+        }
+    }
+
+    mod make_move {
+        use crate::chess_state::{castling::CastlingNotation, coordinates::XCoordinate, game_state::GameState};
+
+        #[test]
+        fn updates_rights_clocks_and_en_passant_when_moves_are_played() {
+            // arrange
+            let mut game = GameState::default();
+            // act
+            for uci in ["e2e4", "g8f6", "e4e5", "d7d5", "e5d6", "e7d6", "g1f3", "f8e7", "f1e2", "e8g8"] {
+                let chess_move = game.find_uci_move(uci).expect("legal move");
+                game = game.make_move(&chess_move);
+            }
+            // assert
+            assert_eq!(
+                game.to_fen(CastlingNotation::XFen),
+                "rnbq1rk1/ppp1bppp/3p1n2/8/8/5N2/PPPPBPPP/RNBQK2R w KQ - 4 6"
+            );
+            assert_eq!(game.castling_rights.white_short, Some(XCoordinate::H))
+        }
+    }
+
+    mod make_null_move {
+        use crate::chess_state::{castling::CastlingNotation, game_state::GameState};
+
+        #[test]
+        fn passes_the_turn_and_clears_en_passant() {
+            // arrange
+            let game = GameState::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 3 20").expect("valid fen");
+            // act
+            let output = game.make_null_move();
+            // assert
+            assert_eq!(output.to_fen(CastlingNotation::XFen), "4k3/8/8/8/3pP3/8/8/4K3 w - - 4 21")
+        }
+    }
+
+    mod move_to_uci {
+        use crate::chess_state::{game_state::GameState, moves::shared::Move};
+
+        #[test]
+        fn round_trips_every_legal_move_through_find_uci_move() {
+            // arrange
+            let game = GameState::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1")
+                .expect("valid fen");
+            for chess_move in game.legal_moves() {
+                // act
+                let uci = game.move_to_uci(&chess_move);
+                // assert
+                assert_eq!(game.find_uci_move(&uci), Some(chess_move), "{}", uci);
+            }
+            assert!(game.find_uci_move("b2a1q").is_some());
+            assert!(game.find_uci_move("e8a8").is_some());
+            assert_eq!(game.find_uci_move("e8a8"), game.find_uci_move("e8c8"));
+            assert!(game.find_uci_move("e8e7").is_none())
+        }
+
+        #[test]
+        fn writes_chess960_castling_as_king_taking_rook() {
+            // arrange
+            // the king already stands on g1, where short castling leaves it
+            let game = GameState::from_fen("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").expect("valid fen");
+            // act
+            let castling: Vec<String> = game
+                .legal_moves()
+                .iter()
+                .filter(|chess_move| matches!(chess_move, Move::Castle(_)))
+                .map(|chess_move| game.move_to_uci(chess_move))
+                .collect();
+            // assert
+            assert_eq!(castling.len(), 2);
+            assert!(castling.contains(&"g1h1".to_string()));
+            assert!(castling.contains(&"g1b1".to_string()));
+            for uci in castling {
+                let chess_move = game.find_uci_move(&uci).expect("legal castling");
+                assert!(matches!(chess_move, Move::Castle(_)), "{}", uci);
+            }
+            assert!(game.find_uci_move("g1g1").is_none());
+            assert!(matches!(game.find_uci_move("g1f1"), Some(Move::StandardMove(_))))
+        }
+    }
+}
@@ -1,7 +1,10 @@
-mod chess_move;
-mod chess_flip;
-mod shared;
+pub(crate) mod chess_move;
+pub(crate) mod chess_flip;
+pub(crate) mod shared;
 mod standard_move;
+// only used by the first move generator in `calculate_moves`, which is built for its tests alone
+#[cfg(test)]
 mod temp_move;
-mod calculate_moves;
-mod attack_maps;
\ No newline at end of file
+pub(crate) mod calculate_moves;
+pub(crate) mod attack_maps;
+pub(crate) mod legal_moves;
\ No newline at end of file
@@ -10,7 +10,7 @@ use crate::chess_state::{
 
 use super::chess_move::ChessDirection;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub(crate) enum CastleType {
     ShortCastle,
     LongCastle,
@@ -19,11 +19,14 @@ pub(crate) enum CastleType {
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub(crate) enum CheckType {
     None,
+    // `legal_moves` does not work out whether a move gives check, so only hand-built moves in tests carry these
+    #[cfg(test)]
     Check,
+    #[cfg(test)]
     Checkmate,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub(crate) enum Move {
     StandardMove(StandardMove),
     Castle(CastleType),
@@ -48,6 +51,7 @@ pub(crate) enum MoveError {
 }
 
 impl BoardBitmasks {
+    #[cfg(test)]
     pub(crate) fn get_piece_type_for_capture(
         &self,
         capture_position: CoordinatePosition,
@@ -34,6 +34,7 @@ impl StandardMove {
         }
     }
 
+    #[cfg(test)]
     pub(crate) fn get_uci_move(&self) -> String {
         let x = match self.takes {
             Some(_) => "x",
@@ -0,0 +1,455 @@
+use thiserror::Error;
+
+use crate::chess_state::{
+    board_bitmask::BoardBitmasks,
+    board_hash_map::{BoardHashMap, BoardHashMapError},
+    castling::{CastlingError, CastlingRights},
+    chess_pieces::PieceEnum,
+    coordinate_point::CoordinatePosition,
+    coordinates::{CoordinateConversion, XCoordinate, YCoordinate},
+    game_state::GameState,
+    moves::shared::CastleType,
+};
+
+#[derive(Debug, Error)]
+pub(crate) enum PositionEditorError {
+    #[error("Board hash map error: {0}")]
+    BoardHashMapError(#[from] BoardHashMapError),
+
+    #[error("Castling error: {0}")]
+    CastlingError(#[from] CastlingError),
+
+    #[error("{} should have exactly one king, but has {count}", side_name(*.white))]
+    WrongKingCount { white: bool, count: u32 },
+
+    #[error("{} has {count} pawns, but can have at most 8", side_name(*.white))]
+    TooManyPawns { white: bool, count: u32 },
+
+    #[error("{} has {count} pieces, but can have at most 16", side_name(*.white))]
+    TooManyPieces { white: bool, count: u32 },
+
+    #[error("Pawn on {0}, but pawns can never stand on the first or eighth rank")]
+    PawnOnBackRank(CoordinatePosition),
+
+    #[error("{} is in check, but it is not their move", side_name(*.0))]
+    SideNotToMoveInCheck(bool),
+
+    #[error("{} holds a castling right with a rook on the {} file, but its king and rook are not in place", side_name(*.white), CoordinateConversion::<char>::to_value(*.rook_file))]
+    InvalidCastlingRight {
+        white: bool,
+        castle_type: CastleType,
+        rook_file: XCoordinate,
+    },
+
+    #[error("{0} is not a valid en passant square, as no pawn has just moved two squares past it")]
+    InvalidEnPassantSquare(CoordinatePosition),
+}
+
+fn side_name(white: bool) -> &'static str {
+    match white {
+        true => "White",
+        false => "Black",
+    }
+}
+
+/// Builds up a position one edit at a time, for setting up puzzles and test positions by hand.
+///
+/// Pieces are kept in a `BoardHashMap`, so placing a piece on a filled square is refused unless the `replace`
+/// flag is passed. Edits are never checked on their own, since a position is usually illegal halfway through
+/// being set up; instead `finalize` checks the whole position before handing back a `GameState`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PositionEditor {
+    map: BoardHashMap,
+    white_to_move: bool,
+    castling_rights: CastlingRights,
+    en_passant: Option<CoordinatePosition>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl PositionEditor {
+    /// Starts editing from an empty board, with white to move and no castling rights
+    pub(crate) fn new() -> Self {
+        Self::from_game_state(GameState::new())
+            .expect("An empty board has no overlapping pieces")
+    }
+
+    /// Starts editing from an existing game state
+    pub(crate) fn from_game_state(game: GameState) -> Result<Self, PositionEditorError> {
+        Ok(Self {
+            map: game.board.to_board_hash_map()?,
+            white_to_move: game.white_to_move,
+            castling_rights: game.castling_rights,
+            en_passant: game.en_passant,
+            halfmove_clock: game.halfmove_clock,
+            fullmove_number: game.fullmove_number,
+        })
+    }
+
+    /// Places `piece` on `position`. Refuses to cover another piece unless `replace` is passed.
+    pub(crate) fn place_piece(
+        &mut self,
+        position: CoordinatePosition,
+        piece: PieceEnum,
+        replace: bool,
+    ) -> Result<(), PositionEditorError> {
+        Ok(self.map.insert(position, piece, replace)?)
+    }
+
+    /// Removes and returns the piece on `position`, or `None` if it was already empty
+    pub(crate) fn clear_square(&mut self, position: CoordinatePosition) -> Option<PieceEnum> {
+        self.map.remove(position)
+    }
+
+    /// Moves the piece on `from` to `to`, returning whatever it replaced. Refuses to cover another piece unless
+    /// `replace` is passed.
+    pub(crate) fn move_piece(
+        &mut self,
+        from: CoordinatePosition,
+        to: CoordinatePosition,
+        replace: bool,
+    ) -> Result<Option<PieceEnum>, PositionEditorError> {
+        Ok(self.map.relocate(from, to, replace)?)
+    }
+
+    /// Removes every piece, along with the castling rights and en passant square that depended on them
+    pub(crate) fn clear_board(&mut self) {
+        self.map = BoardHashMap::new();
+        self.castling_rights = CastlingRights::none();
+        self.en_passant = None;
+    }
+
+    pub(crate) fn set_side_to_move(&mut self, white: bool) {
+        self.white_to_move = white;
+    }
+
+    /// Removes the castling right if it is held, or grants it otherwise. A granted right uses the outermost rook
+    /// on that side of the king, as with `KQkq` in X-FEN.
+    ///
+    /// # Errors
+    ///
+    /// * `PositionEditorError::CastlingError` if the right is being granted, but the king or a rook to castle
+    ///   with is missing from the back rank.
+    pub(crate) fn toggle_castling_right(
+        &mut self,
+        white: bool,
+        castle_type: CastleType,
+    ) -> Result<(), PositionEditorError> {
+        if self.castling_rights.get(white, castle_type).is_some() {
+            self.castling_rights.set(white, castle_type, None);
+            return Ok(());
+        }
+
+        let right = match castle_type {
+            CastleType::ShortCastle => "k",
+            CastleType::LongCastle => "q",
+        };
+        let right = match white {
+            true => right.to_ascii_uppercase(),
+            false => right.to_string(),
+        };
+        let rook_file = CastlingRights::from_fen_field(&right, &self.board())?.get(white, castle_type);
+        self.castling_rights.set(white, castle_type, rook_file);
+        Ok(())
+    }
+
+    /// Sets (or with `None`, removes) the square a pawn can be taken on en passant
+    pub(crate) fn set_en_passant(&mut self, square: Option<CoordinatePosition>) {
+        self.en_passant = square;
+    }
+
+    /// Returns the board as it currently stands, which may not yet be legal
+    pub(crate) fn board(&self) -> BoardBitmasks {
+        BoardBitmasks::from_board_hash_map(&self.map)
+    }
+
+    /// Returns the game state as it currently stands, without checking it
+    pub(crate) fn to_game_state_unchecked(&self) -> GameState {
+        GameState {
+            board: self.board(),
+            white_to_move: self.white_to_move,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        }
+    }
+
+    /// Checks the position is one that could be reached in a game and, if so, returns it as a `GameState`:
+    /// * each side has exactly one king, at most 8 pawns, and at most 16 pieces,
+    /// * no pawns stand on the first or eighth rank,
+    /// * the side that has just moved is not left in check,
+    /// * every castling right has its king and rook on the back rank,
+    /// * any en passant square sits behind a pawn that could have just moved two squares.
+    pub(crate) fn finalize(&self) -> Result<GameState, PositionEditorError> {
+        let game = self.to_game_state_unchecked();
+        let board = game.board;
+
+        for white in [true, false] {
+            let (kings, pawns, pieces) = match white {
+                true => (board.white_kings.mask, board.white_pawns.mask, board.white_pieces.mask),
+                false => (board.black_kings.mask, board.black_pawns.mask, board.black_pieces.mask),
+            };
+            if kings.count_ones() != 1 {
+                let count = kings.count_ones();
+                return Err(PositionEditorError::WrongKingCount { white, count });
+            }
+            if pawns.count_ones() > 8 {
+                let count = pawns.count_ones();
+                return Err(PositionEditorError::TooManyPawns { white, count });
+            }
+            if pieces.count_ones() > 16 {
+                let count = pieces.count_ones();
+                return Err(PositionEditorError::TooManyPieces { white, count });
+            }
+        }
+
+        let pawns_on_back_ranks = (board.white_pawns.mask | board.black_pawns.mask)
+            & (YCoordinate::One as u64 | YCoordinate::Eight as u64);
+        if pawns_on_back_ranks != 0 {
+            let position = CoordinatePosition::from_bitmask(1u64 << pawns_on_back_ranks.trailing_zeros())
+                .expect("Single bit bitmask");
+            return Err(PositionEditorError::PawnOnBackRank(position));
+        }
+
+        if board.is_king_in_check(!game.white_to_move) {
+            return Err(PositionEditorError::SideNotToMoveInCheck(!game.white_to_move));
+        }
+
+        for white in [true, false] {
+            for castle_type in [CastleType::ShortCastle, CastleType::LongCastle] {
+                let Some(rook_file) = game.castling_rights.get(white, castle_type) else {
+                    continue;
+                };
+                // reading the right back as a Shredder-FEN file letter finds the same king and rook
+                let file_char = CoordinateConversion::<char>::to_value(rook_file);
+                let right = match white {
+                    true => file_char.to_ascii_uppercase(),
+                    false => file_char,
+                };
+                let found = CastlingRights::from_fen_field(&right.to_string(), &board)
+                    .ok()
+                    .and_then(|rights| rights.get(white, castle_type));
+                if found != Some(rook_file) {
+                    return Err(PositionEditorError::InvalidCastlingRight {
+                        white,
+                        castle_type,
+                        rook_file,
+                    });
+                }
+            }
+        }
+
+        if let Some(square) = game.en_passant {
+            let bitmask = square.to_bitmask();
+            // the square the pawn skipped, the square it started on, and the square it stopped on
+            let (rank, start, pawn, pawns) = match game.white_to_move {
+                true => (YCoordinate::Six, bitmask << 8, bitmask >> 8, board.black_pawns.mask),
+                false => (YCoordinate::Three, bitmask >> 8, bitmask << 8, board.white_pawns.mask),
+            };
+            let valid = square.y == rank
+                && (bitmask | start) & board.all_pieces.mask == 0
+                && pawn & pawns != 0;
+            if !valid {
+                return Err(PositionEditorError::InvalidEnPassantSquare(square));
+            }
+        }
+
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod place_piece {
+        use crate::chess_state::{
+            board_hash_map::BoardHashMapError, chess_pieces::PieceEnum,
+            coordinate_point::CoordinatePosition, position_editor::{PositionEditor, PositionEditorError},
+        };
+
+        #[test]
+        fn refuses_to_cover_piece_unless_replace_flag_is_passed() {
+            // arrange
+            let mut editor = PositionEditor::new();
+            let e4 = CoordinatePosition::from_str("e4").expect("valid square");
+            editor
+                .place_piece(e4, PieceEnum::WhitePawn, false)
+                .expect("square is empty");
+            // act
+            let without_replace = editor.place_piece(e4, PieceEnum::BlackKnight, false);
+            let with_replace = editor.place_piece(e4, PieceEnum::BlackKnight, true);
+            // assert
+            assert!(matches!(
+                without_replace,
+                Err(PositionEditorError::BoardHashMapError(
+                    BoardHashMapError::InsertedIntoFilledBoardPositionWithNoReplace(_, PieceEnum::WhitePawn)
+                ))
+            ));
+            assert!(with_replace.is_ok());
+            assert_eq!(editor.board().piece_at(e4), Some(PieceEnum::BlackKnight))
+        }
+    }
+
+    mod move_piece {
+        use crate::chess_state::{
+            board_hash_map::BoardHashMapError, chess_pieces::PieceEnum,
+            coordinate_point::CoordinatePosition, position_editor::{PositionEditor, PositionEditorError},
+        };
+
+        #[test]
+        fn refuses_to_cover_piece_unless_replace_flag_is_passed() {
+            // arrange
+            let mut editor = PositionEditor::new();
+            let e4 = CoordinatePosition::from_str("e4").expect("valid square");
+            let d5 = CoordinatePosition::from_str("d5").expect("valid square");
+            editor.place_piece(e4, PieceEnum::WhitePawn, false).expect("square is empty");
+            editor.place_piece(d5, PieceEnum::BlackKnight, false).expect("square is empty");
+            // act
+            let without_replace = editor.move_piece(e4, d5, false);
+            let with_replace = editor.move_piece(e4, d5, true);
+            let from_empty = editor.move_piece(e4, d5, true);
+            // assert
+            assert!(matches!(
+                without_replace,
+                Err(PositionEditorError::BoardHashMapError(
+                    BoardHashMapError::InsertedIntoFilledBoardPositionWithNoReplace(_, PieceEnum::BlackKnight)
+                ))
+            ));
+            assert_eq!(with_replace.expect("replace flag passed"), Some(PieceEnum::BlackKnight));
+            assert!(matches!(
+                from_empty,
+                Err(PositionEditorError::BoardHashMapError(
+                    BoardHashMapError::RelocatedFromEmptyBoardPosition(_)
+                ))
+            ));
+            assert_eq!(editor.board().piece_at(d5), Some(PieceEnum::WhitePawn));
+            assert_eq!(editor.board().piece_at(e4), None)
+        }
+    }
+
+    mod toggle_castling_right {
+        use crate::chess_state::{
+            coordinates::XCoordinate, game_state::GameState, moves::shared::CastleType,
+            position_editor::{PositionEditor, PositionEditorError},
+        };
+
+        #[test]
+        fn removes_then_restores_right_when_toggled_twice() {
+            // arrange
+            let mut editor = PositionEditor::from_game_state(GameState::default()).expect("valid game");
+            // act
+            editor
+                .toggle_castling_right(false, CastleType::LongCastle)
+                .expect("right is held");
+            let removed = editor.finalize().expect("legal position").castling_rights;
+            editor
+                .toggle_castling_right(false, CastleType::LongCastle)
+                .expect("king and rook are in place");
+            let restored = editor.finalize().expect("legal position").castling_rights;
+            // assert
+            assert_eq!(removed.black_long, None);
+            assert_eq!(restored.black_long, Some(XCoordinate::A))
+        }
+
+        #[test]
+        fn returns_error_when_granting_right_without_a_rook() {
+            // arrange
+            let mut editor = PositionEditor::new();
+            // act
+            let output = editor.toggle_castling_right(true, CastleType::ShortCastle);
+            // assert
+            assert!(matches!(output, Err(PositionEditorError::CastlingError(_))))
+        }
+    }
+
+    mod finalize {
+        use crate::chess_state::{
+            castling::CastlingNotation, chess_pieces::PieceEnum, coordinate_point::CoordinatePosition,
+            game_state::GameState, position_editor::{PositionEditor, PositionEditorError},
+        };
+
+        fn square(input: &str) -> CoordinatePosition {
+            CoordinatePosition::from_str(input).expect("valid square")
+        }
+
+        fn editor_from_fen(fen: &str) -> PositionEditor {
+            PositionEditor::from_game_state(GameState::from_fen(fen).expect("valid fen"))
+                .expect("valid game")
+        }
+
+        #[test]
+        fn returns_game_state_when_position_is_built_up_from_empty_board() {
+            // arrange
+            let mut editor = PositionEditor::new();
+            for (position, piece) in [
+                ("g1", PieceEnum::WhiteKing),
+                ("b7", PieceEnum::WhitePawn),
+                ("a8", PieceEnum::BlackKing),
+            ] {
+                editor
+                    .place_piece(square(position), piece, false)
+                    .expect("square is empty");
+            }
+            editor.set_side_to_move(false);
+            // act
+            let output = editor.finalize().expect("legal position");
+            // assert
+            assert_eq!(
+                output.to_fen(CastlingNotation::XFen),
+                "k7/1P6/8/8/8/8/8/6K1 b - - 0 1"
+            )
+        }
+
+        #[test]
+        fn returns_correct_errors_when_position_is_illegal() {
+            // arrange
+            let cases = [
+                "8/8/8/8/8/8/8/4K3 w - - 0 1",
+                "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+                "4k3/8/8/8/8/8/8/4K2R b - - 0 1",
+                "4k3/4r3/8/8/8/8/8/4K3 b - - 0 1",
+            ];
+            // act
+            let outputs: Vec<_> = cases
+                .iter()
+                .map(|fen| editor_from_fen(fen).finalize())
+                .collect();
+            // assert
+            assert!(matches!(
+                outputs[0],
+                Err(PositionEditorError::WrongKingCount { white: false, count: 0 })
+            ));
+            assert!(matches!(outputs[1], Err(PositionEditorError::PawnOnBackRank(_))));
+            assert!(outputs[2].is_ok());
+            assert!(matches!(outputs[3], Err(PositionEditorError::SideNotToMoveInCheck(true))))
+        }
+
+        #[test]
+        fn refuses_castling_right_when_rook_has_been_cleared() {
+            // arrange
+            let mut editor = PositionEditor::from_game_state(GameState::default()).expect("valid game");
+            editor.clear_square(square("h1"));
+            // act
+            let output = editor.finalize();
+            // assert
+            assert!(matches!(
+                output,
+                Err(PositionEditorError::InvalidCastlingRight { white: true, .. })
+            ))
+        }
+
+        #[test]
+        fn checks_en_passant_square_against_pawn_that_just_moved() {
+            // arrange
+            let mut editor = editor_from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+            // act
+            editor.set_en_passant(Some(square("e3")));
+            let valid = editor.finalize();
+            editor.set_en_passant(Some(square("d3")));
+            let invalid = editor.finalize();
+            // assert
+            assert!(valid.is_ok());
+            assert!(matches!(invalid, Err(PositionEditorError::InvalidEnPassantSquare(_))))
+        }
+    }
+}
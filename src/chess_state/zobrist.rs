@@ -0,0 +1,158 @@
+use crate::chess_state::{
+    chess_pieces::PieceEnum,
+    game_state::GameState,
+    moves::{
+        attack_maps::{BlackPawnAttackMaps, WhitePawnAttackMaps},
+        shared::CastleType,
+    },
+};
+
+/// Random keys for every part of a position, generated at compile time so hashes are the same on every run
+struct ZobristKeys {
+    pieces: [[u64; 64]; 12],
+    /// Indexed by side (white first), castle type (short first), and the bit index of the rook's file
+    castling: [[[u64; 8]; 2]; 2],
+    /// Indexed by the bit index of the en passant square's file
+    en_passant: [u64; 8],
+    black_to_move: u64,
+}
+
+/// Steps a splitmix64 generator, returning the next state and its output
+const fn next_random(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let value = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, value ^ (value >> 31))
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut keys = ZobristKeys {
+        pieces: [[0; 64]; 12],
+        castling: [[[0; 8]; 2]; 2],
+        en_passant: [0; 8],
+        black_to_move: 0,
+    };
+    let mut state = 0x2545_F491_4F6C_DD1D;
+    let mut value;
+
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            (state, value) = next_random(state);
+            keys.pieces[piece][square] = value;
+            square += 1;
+        }
+        piece += 1;
+    }
+    let mut side = 0;
+    while side < 2 {
+        let mut castle_type = 0;
+        while castle_type < 2 {
+            let mut file = 0;
+            while file < 8 {
+                (state, value) = next_random(state);
+                keys.castling[side][castle_type][file] = value;
+                file += 1;
+            }
+            castle_type += 1;
+        }
+        side += 1;
+    }
+    let mut file = 0;
+    while file < 8 {
+        (state, value) = next_random(state);
+        keys.en_passant[file] = value;
+        file += 1;
+    }
+    (_, keys.black_to_move) = next_random(state);
+    keys
+}
+
+static ZOBRIST_KEYS: ZobristKeys = generate_keys();
+
+impl GameState {
+    /// Hashes the position into 64 bits, for the transposition table. Positions that play the same get the same
+    /// hash, so the en passant square only counts when a pawn is actually there to take en passant, and the
+    /// clocks are left out entirely.
+    pub(crate) fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (piece_index, piece) in PieceEnum::ALL.into_iter().enumerate() {
+            let mut bitmask = self.board.piece_enum_to_bitmask(piece);
+            while bitmask != 0 {
+                hash ^= ZOBRIST_KEYS.pieces[piece_index][bitmask.trailing_zeros() as usize];
+                bitmask &= bitmask - 1;
+            }
+        }
+
+        for (side, white) in [true, false].into_iter().enumerate() {
+            for (castle_type_index, castle_type) in [CastleType::ShortCastle, CastleType::LongCastle]
+                .into_iter()
+                .enumerate()
+            {
+                if let Some(rook_file) = self.castling_rights.get(white, castle_type) {
+                    let file = (rook_file as u64).trailing_zeros() as usize;
+                    hash ^= ZOBRIST_KEYS.castling[side][castle_type_index][file];
+                }
+            }
+        }
+
+        if let Some(en_passant) = self.en_passant {
+            let target = en_passant.to_bitmask();
+            let capturers = match self.white_to_move {
+                true => target.calculate_unconstrained_black_pawn_attack_maps() & self.board.white_pawns.mask,
+                false => target.calculate_unconstrained_white_pawn_attack_maps() & self.board.black_pawns.mask,
+            };
+            if capturers != 0 {
+                hash ^= ZOBRIST_KEYS.en_passant[(target.trailing_zeros() % 8) as usize];
+            }
+        }
+
+        if !self.white_to_move {
+            hash ^= ZOBRIST_KEYS.black_to_move;
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod zobrist_hash {
+        use crate::chess_state::game_state::GameState;
+
+        fn hash(fen: &str) -> u64 {
+            GameState::from_fen(fen).expect("valid fen").zobrist_hash()
+        }
+
+        #[test]
+        fn matches_when_position_is_reached_by_different_move_orders() {
+            // arrange
+            let play = |moves: &[&str]| {
+                moves.iter().fold(GameState::default(), |game, uci| {
+                    game.make_move(&game.find_uci_move(uci).expect("legal move"))
+                })
+            };
+            // act
+            let first = play(&["g1f3", "g8f6", "b1c3", "b8c6"]);
+            let second = play(&["b1c3", "b8c6", "g1f3", "g8f6"]);
+            // assert
+            assert_eq!(first.zobrist_hash(), second.zobrist_hash());
+            assert_ne!(first.zobrist_hash(), GameState::default().zobrist_hash())
+        }
+
+        #[test]
+        fn differs_by_side_to_move_castling_rights_and_en_passant() {
+            // act + assert
+            let base = hash("r3k2r/8/8/8/3pP3/8/8/R3K2R b KQkq - 0 1");
+            assert_ne!(base, hash("r3k2r/8/8/8/3pP3/8/8/R3K2R w KQkq - 0 1"));
+            assert_ne!(base, hash("r3k2r/8/8/8/3pP3/8/8/R3K2R b Kkq - 0 1"));
+            assert_ne!(base, hash("r3k2r/8/8/8/3pP3/8/8/R3K2R b KQkq e3 0 1"));
+            // the clocks do not count, and nor does an en passant square no pawn can take on
+            assert_eq!(base, hash("r3k2r/8/8/8/3pP3/8/8/R3K2R b KQkq - 12 40"));
+            assert_eq!(
+                hash("r3k2r/8/8/8/4P3/8/8/R3K2R b KQkq e3 0 1"),
+                hash("r3k2r/8/8/8/4P3/8/8/R3K2R b KQkq - 0 1")
+            )
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::io::{self, Write};
+
+use thiserror::Error;
+
+use crate::{
+    chess_state::{fen::FenError, game_state::GameState},
+    evaluation_engine::{params::EvalParams, Evaluator},
+};
+
+#[derive(Debug, Error)]
+pub(crate) enum EvaluateCommandError {
+    #[error("FEN error: {0}")]
+    FenError(#[from] FenError),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// Prints the evaluation of a FEN position with `params` term by term, followed by the final score from white's
+/// point of view and then from the side to move's, which is the score the search works with
+pub(crate) fn run(fen: &str, params: &EvalParams, output: &mut impl Write) -> Result<(), EvaluateCommandError> {
+    let game = GameState::from_fen(fen)?;
+    let mut evaluator = Evaluator::with_params(params.clone());
+    writeln!(output, "{}", evaluator.evaluate_with_trace(&game.board))?;
+    let side_to_move = match game.white_to_move {
+        true => "white",
+        false => "black",
+    };
+    writeln!(
+        output,
+        "Final score from the side to move's point of view ({}): {}",
+        side_to_move,
+        evaluator.evaluate(&game)
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod run {
+        use crate::{
+            cli::evaluate::{run, EvaluateCommandError},
+            evaluation_engine::params::DEFAULT_EVAL_PARAMS,
+        };
+
+        #[test]
+        fn prints_trace_when_fen_is_valid() {
+            // arrange
+            let mut output = Vec::new();
+            // act
+            let result = run("4k3/8/8/8/8/2N5/8/4K3 b - - 0 1", &DEFAULT_EVAL_PARAMS, &mut output);
+            // assert
+            assert!(result.is_ok());
+            let output = String::from_utf8(output).expect("utf-8 output");
+            assert!(output.contains("Material"));
+            let white_score = output
+                .lines()
+                .find_map(|line| line.strip_prefix("Final score from white's point of view: "))
+                .expect("white's score");
+            let side_to_move_score = output
+                .lines()
+                .find_map(|line| line.strip_prefix("Final score from the side to move's point of view (black): "))
+                .expect("side to move's score");
+            assert!(white_score.parse::<i32>().expect("a number") > 250);
+            assert_eq!(side_to_move_score, format!("-{}", white_score))
+        }
+
+        #[test]
+        fn returns_error_when_fen_is_invalid() {
+            // arrange
+            let mut output = Vec::new();
+            // act
+            let result = run("not a fen", &DEFAULT_EVAL_PARAMS, &mut output);
+            // assert
+            assert!(matches!(result, Err(EvaluateCommandError::FenError(_))));
+            assert!(output.is_empty())
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod evaluate;
+pub(crate) mod position_editor;
+pub(crate) mod search;
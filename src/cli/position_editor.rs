@@ -0,0 +1,380 @@
+use std::io::{self, BufRead, Write};
+
+use thiserror::Error;
+
+use crate::chess_state::{
+    board_display::{BoardOrientation, PieceStyle},
+    castling::CastlingNotation,
+    chess960::Chess960Error,
+    chess_pieces::PieceEnum,
+    coordinate_point::CoordinatePosition,
+    coordinates::CoordinateError,
+    fen::FenError,
+    game_state::GameState,
+    moves::shared::CastleType,
+    position_editor::{PositionEditor, PositionEditorError},
+};
+
+const HELP: &str = "\
+commands:
+  place <square> <piece>    put a piece (e.g. `place e4 N`) on an empty square
+  replace <square> <piece>  put a piece on a square, replacing anything already there
+  move <from> <to>          move a piece onto an empty square
+  capture <from> <to>       move a piece, replacing anything already on the destination
+  clear <square>            remove the piece on a square
+  clearboard                remove every piece
+  side <w|b>                set the side to move
+  castle <K|Q|k|q>          toggle a castling right
+  ep <square|->             set or remove the en passant square
+  fen <fen>                 start again from a FEN string
+  startpos                  start again from the classical starting position
+  chess960 <index>          start again from one of the 960 Chess960 starting positions (0-959)
+  show [unicode] [flip]     draw the board, optionally with figurines or with black at the bottom
+  done [shredder]           check the position and print its FEN, optionally with Shredder-FEN castling
+  quit                      leave without a position
+";
+
+#[derive(Debug, Error)]
+pub(crate) enum EditorCommandError {
+    #[error("Unknown command {0}, type `help` for a list of commands")]
+    UnknownCommand(String),
+
+    #[error("Command {0} is missing an argument")]
+    MissingArgument(String),
+
+    #[error("{0} is not a valid piece, expects one of PNBRQK or pnbrqk")]
+    InvalidPiece(String),
+
+    #[error("{0} is not a valid side, expects w or b")]
+    InvalidSide(String),
+
+    #[error("{0} is not a valid castling right, expects one of KQkq")]
+    InvalidCastlingRight(String),
+
+    #[error("{0} is not a valid Chess960 start position, expects a number from 0 to 959")]
+    InvalidChess960Index(String),
+
+    #[error("{0} is not a valid option for command {1}")]
+    InvalidOption(String, String),
+
+    #[error("Coordinate error: {0}")]
+    CoordinateError(#[from] CoordinateError),
+
+    #[error("FEN error: {0}")]
+    FenError(#[from] FenError),
+
+    #[error("Chess960 error: {0}")]
+    Chess960Error(#[from] Chess960Error),
+
+    #[error("{0}")]
+    PositionEditorError(#[from] PositionEditorError),
+}
+
+/// One line of input to the position editor
+#[derive(Debug, PartialEq, Eq)]
+enum EditorCommand {
+    Place(CoordinatePosition, PieceEnum, bool),
+    Move(CoordinatePosition, CoordinatePosition, bool),
+    Clear(CoordinatePosition),
+    ClearBoard,
+    SideToMove(bool),
+    ToggleCastling(bool, CastleType),
+    EnPassant(Option<CoordinatePosition>),
+    LoadFen(String),
+    StartPosition,
+    Chess960(u16),
+    Show(PieceStyle, BoardOrientation),
+    Help,
+    Done(CastlingNotation),
+    Quit,
+}
+
+impl EditorCommand {
+    fn parse(line: &str) -> Result<Self, EditorCommandError> {
+        use EditorCommandError::*;
+
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let mut argument = || words.next().ok_or(MissingArgument(command.to_string()));
+
+        Ok(match command {
+            "place" | "replace" => {
+                let position = CoordinatePosition::from_str(argument()?)?;
+                let piece = argument()?;
+                let piece = match piece.chars().collect::<Vec<char>>()[..] {
+                    [ch] => PieceEnum::from_char(ch),
+                    _ => None,
+                }
+                .ok_or(InvalidPiece(piece.to_string()))?;
+                Self::Place(position, piece, command == "replace")
+            }
+            "move" | "capture" => {
+                let from = CoordinatePosition::from_str(argument()?)?;
+                let to = CoordinatePosition::from_str(argument()?)?;
+                Self::Move(from, to, command == "capture")
+            }
+            "clear" => Self::Clear(CoordinatePosition::from_str(argument()?)?),
+            "clearboard" => Self::ClearBoard,
+            "side" => match argument()? {
+                "w" => Self::SideToMove(true),
+                "b" => Self::SideToMove(false),
+                other => return Err(InvalidSide(other.to_string())),
+            },
+            "castle" => match argument()? {
+                "K" => Self::ToggleCastling(true, CastleType::ShortCastle),
+                "Q" => Self::ToggleCastling(true, CastleType::LongCastle),
+                "k" => Self::ToggleCastling(false, CastleType::ShortCastle),
+                "q" => Self::ToggleCastling(false, CastleType::LongCastle),
+                other => return Err(InvalidCastlingRight(other.to_string())),
+            },
+            "ep" => match argument()? {
+                "-" => Self::EnPassant(None),
+                square => Self::EnPassant(Some(CoordinatePosition::from_str(square)?)),
+            },
+            "fen" => Self::LoadFen(line.trim_start().trim_start_matches("fen").trim().to_string()),
+            "startpos" => Self::StartPosition,
+            "chess960" => {
+                let index = argument()?;
+                Self::Chess960(index.parse().map_err(|_| InvalidChess960Index(index.to_string()))?)
+            }
+            "show" => {
+                let (mut style, mut orientation) = (PieceStyle::Ascii, BoardOrientation::WhiteAtBottom);
+                for option in words {
+                    match option {
+                        "unicode" => style = PieceStyle::Unicode,
+                        "flip" => orientation = BoardOrientation::BlackAtBottom,
+                        other => return Err(InvalidOption(other.to_string(), command.to_string())),
+                    }
+                }
+                Self::Show(style, orientation)
+            }
+            "help" => Self::Help,
+            "done" => match words.next() {
+                None => Self::Done(CastlingNotation::XFen),
+                Some("shredder") => Self::Done(CastlingNotation::ShredderFen),
+                Some(other) => return Err(InvalidOption(other.to_string(), command.to_string())),
+            },
+            "quit" => Self::Quit,
+            other => return Err(UnknownCommand(other.to_string())),
+        })
+    }
+}
+
+/// Runs the position editor, reading one command per line from `input` and writing replies to `output`.
+///
+/// Returns the finished position once `done` is given and the position passes `PositionEditor::finalize`, or
+/// `None` if the editor is left with `quit` or the input runs out. An illegal position is reported and the
+/// editor carries on, so the mistake can be fixed.
+pub(crate) fn run(
+    input: impl BufRead,
+    output: &mut impl Write,
+) -> io::Result<Option<GameState>> {
+    let mut editor = PositionEditor::new();
+    writeln!(output, "position editor, type `help` for a list of commands")?;
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match apply(&mut editor, &line) {
+            Ok(Some(EditorCommand::Show(style, orientation))) => {
+                let game = editor.to_game_state_unchecked();
+                write!(output, "{}", game.board.display(style, orientation))?;
+                writeln!(output, "{}", game.to_fen(CastlingNotation::XFen))?;
+                if let Some(index) = game.board.to_chess960_index() {
+                    writeln!(output, "Chess960 start position {}", index)?;
+                }
+            }
+            Ok(Some(EditorCommand::Help)) => write!(output, "{}", HELP)?,
+            Ok(Some(EditorCommand::Done(notation))) => match editor.finalize() {
+                Ok(game) => {
+                    writeln!(output, "{}", game.to_fen(notation))?;
+                    return Ok(Some(game));
+                }
+                Err(error) => writeln!(output, "error: {}", error)?,
+            },
+            Ok(Some(EditorCommand::Quit)) => return Ok(None),
+            Ok(_) => {}
+            Err(error) => writeln!(output, "error: {}", error)?,
+        }
+    }
+    Ok(None)
+}
+
+/// Applies one line of input to the editor, handing back any command that needs the caller to act on it
+fn apply(editor: &mut PositionEditor, line: &str) -> Result<Option<EditorCommand>, EditorCommandError> {
+    match EditorCommand::parse(line)? {
+        EditorCommand::Place(position, piece, replace) => editor.place_piece(position, piece, replace)?,
+        EditorCommand::Move(from, to, replace) => {
+            editor.move_piece(from, to, replace)?;
+        }
+        EditorCommand::Clear(position) => {
+            editor.clear_square(position);
+        }
+        EditorCommand::ClearBoard => editor.clear_board(),
+        EditorCommand::SideToMove(white) => editor.set_side_to_move(white),
+        EditorCommand::ToggleCastling(white, castle_type) => {
+            editor.toggle_castling_right(white, castle_type)?
+        }
+        EditorCommand::EnPassant(square) => editor.set_en_passant(square),
+        EditorCommand::LoadFen(fen) => {
+            *editor = PositionEditor::from_game_state(GameState::from_fen(&fen)?)?;
+        }
+        EditorCommand::StartPosition => *editor = PositionEditor::from_game_state(GameState::default())?,
+        EditorCommand::Chess960(index) => {
+            *editor = PositionEditor::from_game_state(GameState::from_chess960_index(index)?)?;
+        }
+        command => return Ok(Some(command)),
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    mod parse {
+        use crate::{
+            chess_state::{
+                board_display::{BoardOrientation, PieceStyle}, castling::CastlingNotation,
+                chess_pieces::PieceEnum, coordinate_point::CoordinatePosition, moves::shared::CastleType,
+            },
+            cli::position_editor::{EditorCommand, EditorCommandError},
+        };
+
+        #[test]
+        fn reads_arguments_when_given_valid_commands() {
+            // arrange
+            let e4 = CoordinatePosition::from_str("e4").expect("valid square");
+            let d5 = CoordinatePosition::from_str("d5").expect("valid square");
+            // act + assert
+            assert_eq!(
+                EditorCommand::parse("place e4 N").expect("valid command"),
+                EditorCommand::Place(e4, PieceEnum::WhiteKnight, false)
+            );
+            assert_eq!(
+                EditorCommand::parse("replace e4 q").expect("valid command"),
+                EditorCommand::Place(e4, PieceEnum::BlackQueen, true)
+            );
+            assert_eq!(
+                EditorCommand::parse("capture e4 d5").expect("valid command"),
+                EditorCommand::Move(e4, d5, true)
+            );
+            assert_eq!(
+                EditorCommand::parse("chess960 518").expect("valid command"),
+                EditorCommand::Chess960(518)
+            );
+            assert_eq!(
+                EditorCommand::parse("show flip unicode").expect("valid command"),
+                EditorCommand::Show(PieceStyle::Unicode, BoardOrientation::BlackAtBottom)
+            );
+            assert_eq!(
+                EditorCommand::parse("done shredder").expect("valid command"),
+                EditorCommand::Done(CastlingNotation::ShredderFen)
+            );
+            assert_eq!(
+                EditorCommand::parse("castle q").expect("valid command"),
+                EditorCommand::ToggleCastling(false, CastleType::LongCastle)
+            );
+            assert_eq!(
+                EditorCommand::parse("ep -").expect("valid command"),
+                EditorCommand::EnPassant(None)
+            );
+            assert_eq!(
+                EditorCommand::parse("fen 8/8/8/8/8/8/8/8 w - -").expect("valid command"),
+                EditorCommand::LoadFen("8/8/8/8/8/8/8/8 w - -".to_string())
+            )
+        }
+
+        #[test]
+        fn returns_correct_errors_when_given_invalid_commands() {
+            // act + assert
+            assert!(matches!(
+                EditorCommand::parse("undo"),
+                Err(EditorCommandError::UnknownCommand(_))
+            ));
+            assert!(matches!(
+                EditorCommand::parse("place e4"),
+                Err(EditorCommandError::MissingArgument(_))
+            ));
+            assert!(matches!(
+                EditorCommand::parse("place e4 X"),
+                Err(EditorCommandError::InvalidPiece(_))
+            ));
+            assert!(matches!(
+                EditorCommand::parse("side white"),
+                Err(EditorCommandError::InvalidSide(_))
+            ));
+            assert!(matches!(
+                EditorCommand::parse("chess960 first"),
+                Err(EditorCommandError::InvalidChess960Index(_))
+            ));
+            assert!(matches!(
+                EditorCommand::parse("show sideways"),
+                Err(EditorCommandError::InvalidOption(..))
+            ))
+        }
+    }
+
+    mod run {
+        use std::io::Cursor;
+
+        use crate::{chess_state::castling::CastlingNotation, cli::position_editor::run};
+
+        #[test]
+        fn refuses_to_finish_until_position_is_legal() {
+            // arrange
+            let input = "place e1 K\nplace e8 k\nplace e7 R\ndone\nclear e7\ndone\n";
+            let mut output = Vec::new();
+            // act
+            let game = run(Cursor::new(input), &mut output).expect("no io errors");
+            let output = String::from_utf8(output).expect("valid utf8");
+            // assert
+            assert!(output.contains("error: Black is in check, but it is not their move"));
+            assert_eq!(
+                game.expect("legal position").to_fen(CastlingNotation::XFen),
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 1"
+            )
+        }
+
+        #[test]
+        fn writes_shredder_fen_when_finishing_chess960_position() {
+            // arrange
+            let input = "chess960 0\nshow\ndone shredder\n";
+            let mut output = Vec::new();
+            // act
+            let game = run(Cursor::new(input), &mut output).expect("no io errors");
+            let output = String::from_utf8(output).expect("valid utf8");
+            // assert
+            assert!(output.contains("Chess960 start position 0"));
+            assert!(output.contains("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"));
+            assert!(game.is_some())
+        }
+
+        #[test]
+        fn refuses_to_start_chess960_position_when_index_is_out_of_range() {
+            // arrange
+            let input = "chess960 960\nstartpos\ndone\n";
+            let mut output = Vec::new();
+            // act
+            let game = run(Cursor::new(input), &mut output).expect("no io errors");
+            let output = String::from_utf8(output).expect("valid utf8");
+            // assert
+            assert!(output.contains("error: Chess960 error: Chess960 start position index 960 is out of range 0-959"));
+            assert_eq!(
+                game.expect("legal position").to_fen(CastlingNotation::XFen),
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+            )
+        }
+
+        #[test]
+        fn returns_no_position_when_quit() {
+            // arrange
+            let input = "fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\ncastle K\nquit\n";
+            let mut output = Vec::new();
+            // act
+            let game = run(Cursor::new(input), &mut output).expect("no io errors");
+            // assert
+            assert_eq!(game, None)
+        }
+    }
+}
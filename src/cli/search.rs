@@ -0,0 +1,511 @@
+use std::{
+    io::{self, BufRead, Write},
+    thread,
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::{
+    chess_state::{fen::FenError, game_state::GameState},
+    evaluation_engine::params::EvalParams,
+    search_engine::{
+        limits::SearchLimits, time_manager::Clock, transposition_table::DEFAULT_TRANSPOSITION_TABLE_MEGABYTES,
+        SearchResult, Searcher,
+    },
+};
+
+#[derive(Debug, Error)]
+pub(crate) enum SearchCommandError {
+    #[error(
+        "usage: chess-engine search <depth> <fen> [--multipv <n>] [--threads <n>] [--nodes <n>] [--movetime <ms>] \
+         [--mate <moves>] [--searchmoves <move>,<move>...] [--wtime <ms>] [--btime <ms>] [--winc <ms>] \
+         [--binc <ms>] [--movestogo <n>] [--infinite] [--hash <megabytes>]"
+    )]
+    Usage,
+
+    #[error("{0} is not a valid search depth")]
+    InvalidDepth(String),
+
+    #[error("{value} is not a valid value for {option}")]
+    InvalidOption { option: String, value: String },
+
+    #[error("Unknown option {0}")]
+    UnknownOption(String),
+
+    #[error("{0} is not a legal move in the position")]
+    IllegalMove(String),
+
+    #[error("FEN error: {0}")]
+    FenError(#[from] FenError),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// The settings for a search, read from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SearchArguments {
+    pub depth: u32,
+    pub fen: String,
+    /// How many of the best moves to find a line for
+    pub multi_pv: usize,
+    pub threads: usize,
+    pub nodes: Option<u64>,
+    pub move_time: Option<Duration>,
+    /// Stops once a mate in this many moves is found
+    pub mate: Option<u32>,
+    /// The moves to choose between, in UCI notation, or empty for every legal move
+    pub search_moves: Vec<String>,
+    /// The time left on the clocks, if any of it was given
+    pub clock: Option<Clock>,
+    /// Searches until a line is read from the input, ignoring the depth and every other limit
+    pub infinite: bool,
+    /// How many megabytes the transposition table takes up
+    pub hash: usize,
+}
+
+impl SearchArguments {
+    /// Parses the arguments after `search`: the depth, then the FEN, then any options
+    pub(crate) fn parse(args: &[String]) -> Result<Self, SearchCommandError> {
+        let [depth, rest @ ..] = args else {
+            return Err(SearchCommandError::Usage);
+        };
+        let fen_length = rest
+            .iter()
+            .position(|arg| arg.starts_with("--"))
+            .unwrap_or(rest.len());
+        let (fen, options) = rest.split_at(fen_length);
+        if fen.is_empty() {
+            return Err(SearchCommandError::Usage);
+        }
+        let mut arguments = Self {
+            depth: depth
+                .parse()
+                .ok()
+                .filter(|&depth| depth > 0)
+                .ok_or_else(|| SearchCommandError::InvalidDepth(depth.clone()))?,
+            fen: fen.join(" "),
+            multi_pv: 1,
+            threads: 1,
+            nodes: None,
+            move_time: None,
+            mate: None,
+            search_moves: Vec::new(),
+            clock: None,
+            infinite: false,
+            hash: DEFAULT_TRANSPOSITION_TABLE_MEGABYTES,
+        };
+
+        let mut options = options.iter();
+        while let Some(option) = options.next() {
+            // the only option without a value
+            if option == "--infinite" {
+                arguments.infinite = true;
+                continue;
+            }
+            let value = options.next().ok_or(SearchCommandError::Usage)?;
+            let invalid = || SearchCommandError::InvalidOption {
+                option: option.clone(),
+                value: value.clone(),
+            };
+            let millis = || value.parse().map(Duration::from_millis).map_err(|_| invalid());
+            match option.as_str() {
+                "--multipv" => {
+                    arguments.multi_pv = value
+                        .parse()
+                        .ok()
+                        .filter(|&lines| lines > 0)
+                        .ok_or_else(invalid)?
+                }
+                "--threads" => {
+                    arguments.threads = value
+                        .parse()
+                        .ok()
+                        .filter(|&threads| threads > 0)
+                        .ok_or_else(invalid)?
+                }
+                "--hash" => {
+                    arguments.hash = value
+                        .parse()
+                        .ok()
+                        .filter(|&megabytes| megabytes > 0)
+                        .ok_or_else(invalid)?
+                }
+                "--nodes" => arguments.nodes = Some(value.parse().map_err(|_| invalid())?),
+                "--movetime" => {
+                    arguments.move_time = Some(Duration::from_millis(value.parse().map_err(|_| invalid())?))
+                }
+                "--mate" => {
+                    arguments.mate = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&moves| moves > 0)
+                            .ok_or_else(invalid)?,
+                    )
+                }
+                "--searchmoves" => arguments.search_moves = value.split(',').map(str::to_string).collect(),
+                "--wtime" => arguments.clock.get_or_insert_with(Clock::default).white_time = millis()?,
+                "--btime" => arguments.clock.get_or_insert_with(Clock::default).black_time = millis()?,
+                "--winc" => arguments.clock.get_or_insert_with(Clock::default).white_increment = millis()?,
+                "--binc" => arguments.clock.get_or_insert_with(Clock::default).black_increment = millis()?,
+                "--movestogo" => {
+                    arguments.clock.get_or_insert_with(Clock::default).moves_to_go = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&moves| moves > 0)
+                            .ok_or_else(invalid)?,
+                    )
+                }
+                _ => return Err(SearchCommandError::UnknownOption(option.clone())),
+            }
+        }
+        Ok(arguments)
+    }
+}
+
+/// Writes one line of a completed depth as a UCI style info line
+fn write_depth(game: &GameState, result: &SearchResult, output: &mut impl Write) -> io::Result<()> {
+    let principal_variation: Vec<String> = result
+        .principal_variation
+        .iter()
+        .scan(*game, |position, chess_move| {
+            let uci = position.move_to_uci(chess_move);
+            *position = position.make_move(chess_move);
+            Some(uci)
+        })
+        .collect();
+    writeln!(
+        output,
+        "info depth {} multipv {} score {} nodes {} pv {}",
+        result.depth,
+        result.multi_pv,
+        result.score,
+        result.nodes,
+        principal_variation.join(" ")
+    )
+}
+
+/// Searches a FEN position with `params`, printing every line of every depth and then the best move. The
+/// arguments are those after `search`. An infinite search is stopped by reading a line from `input`, or by
+/// `input` running out.
+pub(crate) fn run(
+    args: &[String],
+    params: &EvalParams,
+    mut input: impl BufRead + Send,
+    output: &mut impl Write,
+) -> Result<(), SearchCommandError> {
+    let arguments = SearchArguments::parse(args)?;
+    let game = GameState::from_fen(&arguments.fen)?;
+    let search_moves = arguments
+        .search_moves
+        .iter()
+        .map(|uci| game.find_uci_move(uci).ok_or_else(|| SearchCommandError::IllegalMove(uci.clone())))
+        .collect::<Result<_, _>>()?;
+    let limits = SearchLimits {
+        depth: Some(arguments.depth),
+        nodes: arguments.nodes,
+        move_time: arguments.move_time,
+        clock: arguments.clock,
+        infinite: arguments.infinite,
+        mate: arguments.mate,
+        search_moves,
+    };
+
+    let mut searcher = Searcher::with_params(params.clone());
+    searcher.set_multi_pv(arguments.multi_pv);
+    searcher.set_threads(arguments.threads);
+    searcher.set_hash_size(arguments.hash);
+    let stop_handle = searcher.stop_handle();
+    let mut write_result = Ok(());
+    let (result, read_result) = thread::scope(|scope| {
+        let reader = arguments.infinite.then(|| {
+            scope.spawn(move || {
+                let read_result = input.read_line(&mut String::new());
+                stop_handle.stop();
+                read_result
+            })
+        });
+        let result = searcher.search(&game, &limits, |result| {
+            if write_result.is_ok() {
+                write_result = write_depth(&game, result, output);
+            }
+        });
+        let read_result = reader.map(|reader| reader.join().expect("the input thread does not panic"));
+        (result, read_result)
+    });
+    write_result?;
+    read_result.transpose()?;
+
+    match result.and_then(|result| result.best_move) {
+        Some(best_move) => writeln!(output, "bestmove {}", game.move_to_uci(&best_move))?,
+        None => writeln!(output, "bestmove (none)")?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod run {
+        use std::{
+            io::{self, BufReader, Read},
+            thread,
+            time::Duration,
+        };
+
+        use crate::{
+            cli::search::{run, SearchCommandError},
+            evaluation_engine::params::DEFAULT_EVAL_PARAMS,
+        };
+
+        fn args(args: &[&str]) -> Vec<String> {
+            args.iter().map(|arg| arg.to_string()).collect()
+        }
+
+        #[test]
+        fn prints_every_depth_then_best_move() {
+            // arrange
+            let mut output = Vec::new();
+            // act
+            let result = run(
+                &args(&["2", "6k1/5ppp/8/8/8/8/5PPP/R5K1", "w", "-", "-", "0", "1"]),
+                &DEFAULT_EVAL_PARAMS,
+                io::empty(),
+                &mut output,
+            );
+            // assert
+            assert!(result.is_ok());
+            let output = String::from_utf8(output).expect("utf-8 output");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines.len(), 3);
+            for (line, depth) in lines[..2].iter().zip(1..) {
+                assert!(line.starts_with(&format!("info depth {} multipv 1 score mate 1 nodes ", depth)));
+                assert!(line.ends_with(" pv a1a8"));
+            }
+            assert_eq!(lines[2], "bestmove a1a8")
+        }
+
+        #[test]
+        fn prints_every_line_of_every_depth_when_asked_for_several() {
+            // arrange
+            let mut output = Vec::new();
+            // act
+            let result = run(
+                &args(&["2", "6k1/5ppp/8/8/8/8/5PPP/R5K1", "w", "-", "-", "0", "1", "--multipv", "3"]),
+                &DEFAULT_EVAL_PARAMS,
+                io::empty(),
+                &mut output,
+            );
+            // assert
+            assert!(result.is_ok());
+            let output = String::from_utf8(output).expect("utf-8 output");
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines.len(), 7);
+            for (line, (depth, multi_pv)) in lines.iter().zip([(1, 1), (1, 2), (1, 3), (2, 1), (2, 2), (2, 3)]) {
+                assert!(line.starts_with(&format!("info depth {} multipv {} score ", depth, multi_pv)));
+            }
+            assert!(lines[3].ends_with(" pv a1a8"));
+            assert_eq!(lines[6], "bestmove a1a8")
+        }
+
+        #[test]
+        fn searches_only_the_moves_asked_for() {
+            // arrange
+            let mut output = Vec::new();
+            // act
+            let result = run(
+                &args(&["2", "6k1/5ppp/8/8/8/8/5PPP/R5K1", "w", "-", "-", "0", "1", "--searchmoves", "g2g3,h2h3"]),
+                &DEFAULT_EVAL_PARAMS,
+                io::empty(),
+                &mut output,
+            );
+            let illegal = run(
+                &args(&["2", "6k1/5ppp/8/8/8/8/5PPP/R5K1", "w", "-", "-", "0", "1", "--searchmoves", "a1a9"]),
+                &DEFAULT_EVAL_PARAMS,
+                io::empty(),
+                &mut Vec::new(),
+            );
+            // assert
+            assert!(result.is_ok());
+            let output = String::from_utf8(output).expect("utf-8 output");
+            let best_move = output.lines().last().expect("bestmove line");
+            assert!(best_move == "bestmove g2g3" || best_move == "bestmove h2h3");
+            assert!(matches!(illegal, Err(SearchCommandError::IllegalMove(_))))
+        }
+
+        #[test]
+        fn searches_past_the_depth_until_a_line_is_read_when_infinite() {
+            // arrange
+            // sends its line only once the search has had time to go deeper than asked
+            struct SlowInput;
+            impl Read for SlowInput {
+                fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+                    thread::sleep(Duration::from_millis(100));
+                    buffer[0] = b'\n';
+                    Ok(1)
+                }
+            }
+            let mut output = Vec::new();
+            // act
+            let result = run(
+                &args(&["1", "7k/8/8/8/8/8/8/K7", "w", "-", "-", "0", "1", "--infinite"]),
+                &DEFAULT_EVAL_PARAMS,
+                BufReader::new(SlowInput),
+                &mut output,
+            );
+            // assert
+            assert!(result.is_ok());
+            let output = String::from_utf8(output).expect("utf-8 output");
+            assert!(output.lines().any(|line| line.starts_with("info depth 2 ")));
+            assert!(output.lines().last().expect("bestmove line").starts_with("bestmove "))
+        }
+
+        #[test]
+        fn returns_error_when_depth_or_fen_is_missing_or_invalid() {
+            // arrange
+            let mut output = Vec::new();
+            // act + assert
+            assert!(matches!(
+                run(&args(&["3"]), &DEFAULT_EVAL_PARAMS, io::empty(), &mut output),
+                Err(SearchCommandError::Usage)
+            ));
+            assert!(matches!(
+                run(&args(&["deep", "8/8/8/8/8/8/8/8 w - - 0 1"]), &DEFAULT_EVAL_PARAMS, io::empty(), &mut output),
+                Err(SearchCommandError::InvalidDepth(_))
+            ));
+            assert!(matches!(
+                run(&args(&["3", "not", "a", "fen"]), &DEFAULT_EVAL_PARAMS, io::empty(), &mut output),
+                Err(SearchCommandError::FenError(_))
+            ));
+            assert!(output.is_empty())
+        }
+    }
+
+    mod search_arguments {
+        use std::time::Duration;
+
+        use crate::{
+            cli::search::{SearchArguments, SearchCommandError},
+            search_engine::time_manager::Clock,
+        };
+
+        fn args(args: &[&str]) -> Vec<String> {
+            args.iter().map(|arg| arg.to_string()).collect()
+        }
+
+        #[test]
+        fn reads_depth_fen_and_options() {
+            // act
+            let output = SearchArguments::parse(&args(&[
+                "4",
+                "8/8/8/8/8/8/8/K1k5",
+                "w",
+                "-",
+                "-",
+                "--multipv",
+                "2",
+                "--infinite",
+                "--threads",
+                "3",
+                "--hash",
+                "64",
+            ]));
+            // assert
+            assert_eq!(
+                output.expect("valid arguments"),
+                SearchArguments {
+                    depth: 4,
+                    fen: "8/8/8/8/8/8/8/K1k5 w - -".to_string(),
+                    multi_pv: 2,
+                    threads: 3,
+                    nodes: None,
+                    move_time: None,
+                    mate: None,
+                    search_moves: Vec::new(),
+                    clock: None,
+                    infinite: true,
+                    hash: 64,
+                }
+            )
+        }
+
+        #[test]
+        fn returns_error_when_option_is_unknown_or_invalid() {
+            // act + assert
+            assert!(matches!(
+                SearchArguments::parse(&args(&["4", "8/8/8/8/8/8/8/K1k5", "w", "--multipv", "0"])),
+                Err(SearchCommandError::InvalidOption { .. })
+            ));
+            assert!(matches!(
+                SearchArguments::parse(&args(&["4", "8/8/8/8/8/8/8/K1k5", "w", "--ponder", "1"])),
+                Err(SearchCommandError::UnknownOption(_))
+            ));
+            assert!(matches!(
+                SearchArguments::parse(&args(&["4", "--multipv", "2"])),
+                Err(SearchCommandError::Usage)
+            ))
+        }
+
+        #[test]
+        fn reads_search_limits() {
+            // act
+            let output = SearchArguments::parse(&args(&[
+                "9",
+                "8/8/8/8/8/8/8/K1k5",
+                "w",
+                "--nodes",
+                "5000",
+                "--movetime",
+                "250",
+                "--mate",
+                "3",
+                "--searchmoves",
+                "a1a2,a1b1",
+            ]))
+            .expect("valid arguments");
+            // assert
+            assert_eq!(
+                (output.nodes, output.move_time, output.mate, output.search_moves),
+                (
+                    Some(5000),
+                    Some(Duration::from_millis(250)),
+                    Some(3),
+                    vec!["a1a2".to_string(), "a1b1".to_string()]
+                )
+            )
+        }
+
+        #[test]
+        fn reads_clock() {
+            // act
+            let output = SearchArguments::parse(&args(&[
+                "20",
+                "8/8/8/8/8/8/8/K1k5",
+                "w",
+                "--wtime",
+                "60000",
+                "--btime",
+                "45000",
+                "--winc",
+                "1000",
+                "--movestogo",
+                "12",
+            ]));
+            let invalid = SearchArguments::parse(&args(&["20", "8/8/8/8/8/8/8/K1k5", "w", "--btime", "-5"]));
+            // assert
+            assert_eq!(
+                output.expect("valid arguments").clock,
+                Some(Clock {
+                    white_time: Duration::from_secs(60),
+                    black_time: Duration::from_secs(45),
+                    white_increment: Duration::from_secs(1),
+                    black_increment: Duration::ZERO,
+                    moves_to_go: Some(12),
+                })
+            );
+            assert!(matches!(invalid, Err(SearchCommandError::InvalidOption { .. })))
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Piece values, piece-square tables, and game phase weights for the evaluator, all in centipawns. Values come
+//! in middlegame/endgame pairs, which are blended by the game phase.
+//!
+//! The tables are written out as the board is drawn from white's side, with a8 first and h1 last, and are
+//! then reordered into bitmask order (bit `i` at index `i`) by `to_bit_order`. Black uses the same tables
+//! by mirroring its pieces onto white's side of the board first.
+//!
+//! Every value here but `MAX_PHASE` is a default for `params::EvalParams`, which the evaluator reads instead, so
+//! it can be replaced from a parameter file.
+
+use crate::evaluation_engine::score::Score;
+
+pub(crate) const PAWN_VALUE: Score = Score::new(100, 120);
+pub(crate) const KNIGHT_VALUE: Score = Score::new(320, 300);
+pub(crate) const BISHOP_VALUE: Score = Score::new(330, 320);
+pub(crate) const ROOK_VALUE: Score = Score::new(500, 530);
+pub(crate) const QUEEN_VALUE: Score = Score::new(900, 950);
+
+/// How much each piece counts towards the game phase
+pub(crate) const KNIGHT_PHASE: i32 = 1;
+pub(crate) const BISHOP_PHASE: i32 = 1;
+pub(crate) const ROOK_PHASE: i32 = 2;
+pub(crate) const QUEEN_PHASE: i32 = 4;
+/// The game phase of a full set of pieces. The phase is always scaled to run up to this, whatever the weights,
+/// so it is the same as a full set adds up to with the default weights.
+pub(crate) const MAX_PHASE: i32 = 24;
+
+/// Applied once for every pawn beyond the first on a file
+pub(crate) const DOUBLED_PAWN_PENALTY: Score = Score::new(-10, -25);
+/// Applied to a pawn with no friendly pawns on either neighbouring file
+pub(crate) const ISOLATED_PAWN_PENALTY: Score = Score::new(-10, -15);
+/// Applied to a pawn that has been left behind by its neighbours and cannot safely step forward
+pub(crate) const BACKWARD_PAWN_PENALTY: Score = Score::new(-8, -10);
+/// Applied to a pawn defended by another pawn or standing beside one
+pub(crate) const CONNECTED_PAWN_BONUS: Score = Score::new(8, 6);
+
+/// Passed pawn bonus by rank, from the side's own point of view, so index `6` is a pawn one step from promoting
+pub(crate) const PASSED_PAWN_BONUS: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(5, 10),
+    Score::new(5, 15),
+    Score::new(10, 25),
+    Score::new(20, 45),
+    Score::new(35, 75),
+    Score::new(60, 120),
+    Score::new(0, 0),
+];
+
+/// Candidate passed pawn bonus by rank, in the same order as `PASSED_PAWN_BONUS`
+pub(crate) const CANDIDATE_PASSER_BONUS: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(3, 5),
+    Score::new(3, 8),
+    Score::new(6, 12),
+    Score::new(10, 20),
+    Score::new(15, 30),
+    Score::new(0, 0),
+    Score::new(0, 0),
+];
+
+/// Bonus for each friendly pawn sheltering the king, on the rank directly in front of it (index `0`) or the rank
+/// after that (index `1`), on the king's file or a neighbouring one
+pub(crate) const PAWN_SHIELD_BONUS: [Score; 2] = [Score::new(15, 0), Score::new(8, 0)];
+/// Penalty for each enemy pawn storming the king, by how many ranks in front of the king it stands, minus one
+pub(crate) const PAWN_STORM_PENALTY: [Score; 3] = [
+    Score::new(-5, 0),
+    Score::new(-20, -5),
+    Score::new(-10, 0),
+];
+/// Penalty for each file on or beside the king with no friendly pawns, but some enemy ones
+pub(crate) const KING_SEMI_OPEN_FILE_PENALTY: Score = Score::new(-15, 0);
+/// Penalty for each file on or beside the king with no pawns at all
+pub(crate) const KING_OPEN_FILE_PENALTY: Score = Score::new(-25, -5);
+
+/// How much each enemy piece attacking the king zone adds to the attack weight
+pub(crate) const KNIGHT_ATTACK_WEIGHT: i32 = 2;
+pub(crate) const BISHOP_ATTACK_WEIGHT: i32 = 2;
+pub(crate) const ROOK_ATTACK_WEIGHT: i32 = 3;
+pub(crate) const QUEEN_ATTACK_WEIGHT: i32 = 5;
+/// Penalty by total attack weight on the king zone. It grows faster than the weight, since a lone attacker is
+/// rarely dangerous while several together often are. Weights past the end use the last entry.
+pub(crate) const KING_ZONE_ATTACK_PENALTY: [Score; 16] = [
+    Score::new(0, 0),
+    Score::new(0, 0),
+    Score::new(-5, 0),
+    Score::new(-10, -2),
+    Score::new(-18, -4),
+    Score::new(-28, -6),
+    Score::new(-40, -10),
+    Score::new(-55, -14),
+    Score::new(-72, -18),
+    Score::new(-92, -23),
+    Score::new(-115, -28),
+    Score::new(-140, -35),
+    Score::new(-168, -42),
+    Score::new(-200, -50),
+    Score::new(-235, -58),
+    Score::new(-270, -68),
+];
+
+/// Mobility bonus by the number of safe squares a knight can move to
+pub(crate) const KNIGHT_MOBILITY: [Score; 9] = [
+    Score::new(-25, -30),
+    Score::new(-12, -15),
+    Score::new(-4, -6),
+    Score::new(0, 0),
+    Score::new(4, 5),
+    Score::new(8, 10),
+    Score::new(12, 14),
+    Score::new(15, 17),
+    Score::new(17, 19),
+];
+/// Mobility bonus by the number of safe squares a bishop can move to
+pub(crate) const BISHOP_MOBILITY: [Score; 14] = [
+    Score::new(-20, -25),
+    Score::new(-10, -12),
+    Score::new(-2, -4),
+    Score::new(4, 3),
+    Score::new(9, 9),
+    Score::new(14, 14),
+    Score::new(18, 19),
+    Score::new(21, 23),
+    Score::new(24, 26),
+    Score::new(26, 29),
+    Score::new(28, 31),
+    Score::new(30, 33),
+    Score::new(31, 34),
+    Score::new(32, 35),
+];
+/// Mobility bonus by the number of safe squares a rook can move to
+pub(crate) const ROOK_MOBILITY: [Score; 15] = [
+    Score::new(-15, -30),
+    Score::new(-8, -15),
+    Score::new(-3, -5),
+    Score::new(0, 2),
+    Score::new(3, 8),
+    Score::new(5, 14),
+    Score::new(8, 20),
+    Score::new(11, 25),
+    Score::new(13, 29),
+    Score::new(15, 33),
+    Score::new(17, 36),
+    Score::new(18, 39),
+    Score::new(19, 41),
+    Score::new(20, 43),
+    Score::new(21, 45),
+];
+/// Mobility bonus by the number of safe squares a queen can move to
+pub(crate) const QUEEN_MOBILITY: [Score; 28] = [
+    Score::new(-10, -20),
+    Score::new(-6, -12),
+    Score::new(-3, -6),
+    Score::new(-1, -2),
+    Score::new(1, 2),
+    Score::new(3, 6),
+    Score::new(5, 10),
+    Score::new(6, 13),
+    Score::new(7, 16),
+    Score::new(8, 19),
+    Score::new(9, 21),
+    Score::new(10, 23),
+    Score::new(11, 25),
+    Score::new(12, 27),
+    Score::new(13, 29),
+    Score::new(13, 30),
+    Score::new(14, 31),
+    Score::new(14, 32),
+    Score::new(15, 33),
+    Score::new(15, 34),
+    Score::new(16, 35),
+    Score::new(16, 36),
+    Score::new(17, 37),
+    Score::new(17, 38),
+    Score::new(18, 38),
+    Score::new(18, 39),
+    Score::new(19, 39),
+    Score::new(19, 40),
+];
+
+/// Reorders a table written with a8 first and h1 last into bitmask order. a8 is the highest bit and h1 the
+/// lowest, so this is a straight reversal.
+const fn to_bit_order(table: [i32; 64]) -> [i32; 64] {
+    let mut output = [0; 64];
+    let mut index = 0;
+    while index < 64 {
+        output[index] = table[63 - index];
+        index += 1;
+    }
+    output
+}
+
+#[rustfmt::skip]
+pub(crate) const PAWN_MG_TABLE: [i32; 64] = to_bit_order([
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+]);
+
+#[rustfmt::skip]
+pub(crate) const KNIGHT_MG_TABLE: [i32; 64] = to_bit_order([
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+]);
+
+#[rustfmt::skip]
+pub(crate) const BISHOP_MG_TABLE: [i32; 64] = to_bit_order([
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+]);
+
+#[rustfmt::skip]
+pub(crate) const ROOK_MG_TABLE: [i32; 64] = to_bit_order([
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+]);
+
+#[rustfmt::skip]
+pub(crate) const QUEEN_MG_TABLE: [i32; 64] = to_bit_order([
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+]);
+
+#[rustfmt::skip]
+pub(crate) const KING_MG_TABLE: [i32; 64] = to_bit_order([
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+]);
+
+#[rustfmt::skip]
+pub(crate) const PAWN_EG_TABLE: [i32; 64] = to_bit_order([
+      0,   0,   0,   0,   0,   0,   0,   0,
+     80,  80,  80,  80,  80,  80,  80,  80,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     30,  30,  30,  30,  30,  30,  30,  30,
+     15,  15,  15,  15,  15,  15,  15,  15,
+      5,   5,   5,   5,   5,   5,   5,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+]);
+
+#[rustfmt::skip]
+pub(crate) const KNIGHT_EG_TABLE: [i32; 64] = to_bit_order([
+    -40, -30, -20, -20, -20, -20, -30, -40,
+    -30, -15,  -5,   0,   0,  -5, -15, -30,
+    -20,  -5,  10,  15,  15,  10,  -5, -20,
+    -20,   0,  15,  20,  20,  15,   0, -20,
+    -20,   0,  15,  20,  20,  15,   0, -20,
+    -20,  -5,  10,  15,  15,  10,  -5, -20,
+    -30, -15,  -5,   0,   0,  -5, -15, -30,
+    -40, -30, -20, -20, -20, -20, -30, -40,
+]);
+
+#[rustfmt::skip]
+pub(crate) const BISHOP_EG_TABLE: [i32; 64] = to_bit_order([
+    -15, -10, -10, -10, -10, -10, -10, -15,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -15, -10, -10, -10, -10, -10, -10, -15,
+]);
+
+#[rustfmt::skip]
+pub(crate) const ROOK_EG_TABLE: [i32; 64] = to_bit_order([
+     10,  10,  10,  10,  10,  10,  10,  10,
+     15,  15,  15,  15,  15,  15,  15,  15,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+]);
+
+#[rustfmt::skip]
+pub(crate) const QUEEN_EG_TABLE: [i32; 64] = to_bit_order([
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -10,   5,  10,  10,  10,  10,   5, -10,
+     -5,   5,  10,  15,  15,  10,   5,  -5,
+     -5,   5,  10,  15,  15,  10,   5,  -5,
+    -10,   5,  10,  10,  10,  10,   5, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+]);
+
+/// Unlike in the middlegame, the king should head for the centre once the queens and most pieces are gone
+#[rustfmt::skip]
+pub(crate) const KING_EG_TABLE: [i32; 64] = to_bit_order([
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+]);
+
+#[cfg(test)]
+mod tests {
+    mod to_bit_order {
+        use crate::{
+            chess_state::coordinate_point::CoordinatePosition,
+            evaluation_engine::consts::{KING_MG_TABLE, PAWN_EG_TABLE, PAWN_MG_TABLE},
+        };
+
+        fn bit_index(position: &str) -> usize {
+            CoordinatePosition::from_str(position)
+                .expect("valid square")
+                .to_bitmask()
+                .trailing_zeros() as usize
+        }
+
+        #[test]
+        fn finds_values_on_the_squares_they_were_written_on() {
+            // act + assert
+            assert_eq!(KING_MG_TABLE[bit_index("g1")], 30);
+            assert_eq!(KING_MG_TABLE[bit_index("c1")], 10);
+            assert_eq!(PAWN_MG_TABLE[bit_index("d2")], -20);
+            assert_eq!(PAWN_EG_TABLE[bit_index("a7")], 80);
+            assert_eq!(PAWN_MG_TABLE[bit_index("e4")], 20)
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    chess_state::{
+        board_bitmask::BoardBitmasks,
+        coordinates::XCoordinate,
+        moves::{
+            attack_maps::{BishopAttackMaps, KingAttackMaps, KnightAttackMaps, QueenAttackMaps, RookAttackMaps},
+            chess_flip::ChessFlip,
+            chess_move::{ChessDirection, ChessShiftMove},
+        },
+    },
+    evaluation_engine::{
+        params::EvalParams,
+        pawn_structure::file_fill,
+        score::Score,
+        trace::CoefficientSink,
+    },
+};
+
+/// Scores the safety of the white king (`white = true`) or black king (`white = false`), so a higher score is
+/// safer for that side. Made up of the pawn shelter in front of the king and the enemy pieces attacking around it.
+pub(crate) fn king_safety(
+    board: &BoardBitmasks,
+    white: bool,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    let (king, own_pawns, enemy_pawns) = match white {
+        true => (board.white_kings.mask, board.white_pawns.mask, board.black_pawns.mask),
+        false => (board.black_kings.mask, board.black_pawns.mask, board.white_pawns.mask),
+    };
+    if king == 0 {
+        return Score::ZERO;
+    }
+
+    let shelter = match white {
+        true => king_shelter(king, own_pawns, enemy_pawns, white, params, coefficients),
+        // mirrored onto white's side of the board, so "up" is always away from the king's own back rank
+        false => king_shelter(
+            king.flip_vertical(),
+            own_pawns.flip_vertical(),
+            enemy_pawns.flip_vertical(),
+            white,
+            params,
+            coefficients,
+        ),
+    };
+    shelter + king_zone_attacks(board, white, params, coefficients)
+}
+
+/// Scores the pawns around a king as if it were white: friendly pawns shielding it, enemy pawns storming it, and
+/// files beside it with no friendly pawns to close them. `white` only says which side the parameters used are
+/// recorded for.
+fn king_shelter(
+    king: u64,
+    own_pawns: u64,
+    enemy_pawns: u64,
+    white: bool,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    let mut score = Score::ZERO;
+
+    // the king's file and its neighbours, one rank at a time going up the board
+    let first_row = {
+        let front = king.shift_move(ChessDirection::Up);
+        front | front.shift_move(ChessDirection::Left) | front.shift_move(ChessDirection::Right)
+    };
+    let rows = [
+        first_row,
+        first_row.shift_move(ChessDirection::Up),
+        first_row.shift_move(ChessDirection::Up).shift_move(ChessDirection::Up),
+    ];
+
+    for (rank, (row, bonus)) in rows.iter().zip(params.pawn_shield_bonus).enumerate() {
+        let shields = (own_pawns & row).count_ones() as i32;
+        score += bonus * shields;
+        coefficients.add(white, "pawn_shield_bonus", rank, shields);
+    }
+    for (rank, (row, penalty)) in rows.iter().zip(params.pawn_storm_penalty).enumerate() {
+        let stormers = (enemy_pawns & row).count_ones() as i32;
+        score += penalty * stormers;
+        coefficients.add(white, "pawn_storm_penalty", rank, stormers);
+    }
+
+    let king_files = file_fill(first_row | king);
+    for file in XCoordinate::ALL {
+        let file = file as u64 & king_files;
+        if file == 0 || own_pawns & file != 0 {
+            continue;
+        }
+        let (name, penalty) = match enemy_pawns & file {
+            0 => ("king_open_file_penalty", params.king_open_file_penalty),
+            _ => ("king_semi_open_file_penalty", params.king_semi_open_file_penalty),
+        };
+        score += penalty;
+        coefficients.add(white, name, 0, 1);
+    }
+
+    score
+}
+
+/// Scores the enemy pieces attacking the squares around the king, weighted by piece type with the attack
+/// weights in `params`
+fn king_zone_attacks(
+    board: &BoardBitmasks,
+    white: bool,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    match king_attack_index(king_zone_attackers(board, white), params) {
+        Some(index) => {
+            coefficients.add(white, "king_zone_attack_penalty", index, 1);
+            params.king_zone_attack_penalty[index]
+        }
+        None => Score::ZERO,
+    }
+}
+
+/// Looks up which `king_zone_attack_penalty` a king takes from the knights, bishops, rooks and queens attacking
+/// its zone, as given by `king_zone_attackers`. A single attacker is not counted, as it cannot mount an attack
+/// alone, so it gives `None`.
+pub(crate) fn king_attack_index(attackers: [i32; 4], params: &EvalParams) -> Option<usize> {
+    if attackers.iter().sum::<i32>() < 2 {
+        return None;
+    }
+    let weight: i32 = attackers
+        .iter()
+        .zip(params.king_attack_weights)
+        .map(|(count, weight)| count * weight)
+        .sum();
+    Some((weight as usize).min(params.king_zone_attack_penalty.len() - 1))
+}
+
+/// Counts the enemy knights, bishops, rooks and queens attacking the squares around the white king
+/// (`white = true`) or black king (`white = false`), or none if that king is missing
+pub(crate) fn king_zone_attackers(board: &BoardBitmasks, white: bool) -> [i32; 4] {
+    let (king, knights, bishops, rooks, queens) = match white {
+        true => (
+            board.white_kings.mask,
+            board.black_knights.mask,
+            board.black_bishops.mask,
+            board.black_rooks.mask,
+            board.black_queens.mask,
+        ),
+        false => (
+            board.black_kings.mask,
+            board.white_knights.mask,
+            board.white_bishops.mask,
+            board.white_rooks.mask,
+            board.white_queens.mask,
+        ),
+    };
+    if king == 0 {
+        return [0; 4];
+    }
+    let zone = king | king.calculate_unconstrained_king_attack_maps();
+    let occupied = board.all_pieces.mask;
+
+    let count_attackers = |pieces: u64, attack_map: &dyn Fn(u64) -> u64| {
+        let mut pieces = pieces;
+        let mut attackers = 0;
+        while pieces != 0 {
+            let piece = 1u64 << pieces.trailing_zeros();
+            pieces &= pieces - 1;
+            if attack_map(piece) & zone != 0 {
+                attackers += 1;
+            }
+        }
+        attackers
+    };
+    [
+        count_attackers(knights, &|piece| piece.calculate_unconstrained_knight_maps()),
+        count_attackers(bishops, &|piece| piece.calculate_bishop_attack_maps(occupied)),
+        count_attackers(rooks, &|piece| piece.calculate_rook_attack_maps(occupied)),
+        count_attackers(queens, &|piece| piece.calculate_queen_attack_maps(occupied)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    mod king_shelter {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{
+                consts::{KING_OPEN_FILE_PENALTY, KING_SEMI_OPEN_FILE_PENALTY, PAWN_SHIELD_BONUS, PAWN_STORM_PENALTY},
+                king_safety::king_shelter,
+                params::DEFAULT_EVAL_PARAMS,
+                score::Score,
+            },
+        };
+
+        fn shelter_from_fen(fen: &str) -> Score {
+            let board = GameState::from_fen(fen).expect("valid fen").board;
+            king_shelter(
+                board.white_kings.mask,
+                board.white_pawns.mask,
+                board.black_pawns.mask,
+                true,
+                &DEFAULT_EVAL_PARAMS,
+                &mut (),
+            )
+        }
+
+        #[test]
+        fn rewards_full_shield_when_king_has_castled() {
+            // act
+            let output = shelter_from_fen("6k1/8/8/8/8/8/5PPP/6K1 w - - 0 1");
+            // assert
+            assert_eq!(output, PAWN_SHIELD_BONUS[0] * 3)
+        }
+
+        #[test]
+        fn penalises_storming_pawns_and_open_files() {
+            // act
+            // the g pawn has gone, the h pawn has been pushed, and a black pawn has arrived on g4
+            let output = shelter_from_fen("6k1/8/8/8/6p1/7P/5P2/6K1 w - - 0 1");
+            // assert
+            assert_eq!(
+                output,
+                PAWN_SHIELD_BONUS[0] + PAWN_SHIELD_BONUS[1] + PAWN_STORM_PENALTY[2] + KING_SEMI_OPEN_FILE_PENALTY
+            );
+            let open = shelter_from_fen("6k1/8/8/8/8/8/5P1P/6K1 w - - 0 1");
+            assert_eq!(open, PAWN_SHIELD_BONUS[0] * 2 + KING_OPEN_FILE_PENALTY)
+        }
+    }
+
+    mod king_safety {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{
+                king_safety::king_safety,
+                params::{EvalParams, DEFAULT_EVAL_PARAMS},
+                score::Score,
+            },
+        };
+
+        #[test]
+        fn scores_both_kings_equally_when_in_starting_position() {
+            // arrange
+            let board = GameState::default().board;
+            // act + assert
+            assert_eq!(
+                king_safety(&board, true, &DEFAULT_EVAL_PARAMS, &mut ()),
+                king_safety(&board, false, &DEFAULT_EVAL_PARAMS, &mut ())
+            )
+        }
+
+        #[test]
+        fn penalises_king_when_several_pieces_attack_its_zone() {
+            // arrange
+            // the black queen on h4 and knight on g4 both bear down on the white king's zone
+            let attacked = GameState::from_fen("6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1")
+                .expect("valid fen")
+                .board;
+            // only the queen is left, which is not enough for an attack on its own
+            let lone_attacker = GameState::from_fen("6k1/5ppp/8/8/7q/8/5PPP/6K1 w - - 0 1")
+                .expect("valid fen")
+                .board;
+            // act
+            let attacked_score = king_safety(&attacked, true, &DEFAULT_EVAL_PARAMS, &mut ());
+            let lone_attacker_score = king_safety(&lone_attacker, true, &DEFAULT_EVAL_PARAMS, &mut ());
+            // assert
+            assert!(attacked_score.mg < lone_attacker_score.mg);
+            assert_eq!(lone_attacker_score, king_safety(&lone_attacker, false, &DEFAULT_EVAL_PARAMS, &mut ()));
+            assert_ne!(attacked_score, Score::ZERO)
+        }
+
+        #[test]
+        fn weighs_attackers_with_the_attack_weights_in_params() {
+            // arrange
+            let board = GameState::from_fen("6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1")
+                .expect("valid fen")
+                .board;
+            let params = EvalParams {
+                king_attack_weights: [0, 0, 0, 0],
+                ..EvalParams::default()
+            };
+            // act
+            let output = king_safety(&board, true, &params, &mut ());
+            // assert
+            let attack_penalty = DEFAULT_EVAL_PARAMS.king_zone_attack_penalty[7];
+            assert_eq!(output, king_safety(&board, true, &DEFAULT_EVAL_PARAMS, &mut ()) - attack_penalty)
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    chess_state::{board_bitmask::BoardBitmasks, chess_pieces::PieceEnum},
+    evaluation_engine::{params::EvalParams, score::Score, trace::CoefficientSink},
+};
+
+/// Scores the material of the white pieces (`white = true`) or black pieces (`white = false`), counting each
+/// piece type with a popcount of its bitmask. Kings are left out, since each side always has exactly one.
+pub(crate) fn material_score(
+    board: &BoardBitmasks,
+    white: bool,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    let (pawns, knights, bishops, rooks, queens) = match white {
+        true => (
+            board.white_pawns.count(),
+            board.white_knights.count(),
+            board.white_bishops.count(),
+            board.white_rooks.count(),
+            board.white_queens.count(),
+        ),
+        false => (
+            board.black_pawns.count(),
+            board.black_knights.count(),
+            board.black_bishops.count(),
+            board.black_rooks.count(),
+            board.black_queens.count(),
+        ),
+    };
+    let counts = [
+        ("pawn_value", pawns),
+        ("knight_value", knights),
+        ("bishop_value", bishops),
+        ("rook_value", rooks),
+        ("queen_value", queens),
+    ];
+    for (name, count) in counts {
+        coefficients.add(white, name, 0, count as i32);
+    }
+
+    params.pawn_value * pawns as i32
+        + params.knight_value * knights as i32
+        + params.bishop_value * bishops as i32
+        + params.rook_value * rooks as i32
+        + params.queen_value * queens as i32
+}
+
+/// Returns the material value of a single piece of either colour. Kings are worth nothing, as in `material_score`.
+pub(crate) fn piece_value(piece: PieceEnum, params: &EvalParams) -> Score {
+    use PieceEnum::*;
+    match piece {
+        WhitePawn | BlackPawn => params.pawn_value,
+        WhiteKnight | BlackKnight => params.knight_value,
+        WhiteBishop | BlackBishop => params.bishop_value,
+        WhiteRook | BlackRook => params.rook_value,
+        WhiteQueen | BlackQueen => params.queen_value,
+        WhiteKing | BlackKing => Score::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod material_score {
+        use crate::{
+            chess_state::{board_bitmask::BoardBitmasks, game_state::GameState},
+            evaluation_engine::{
+                consts::{PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE},
+                material::material_score,
+                params::DEFAULT_EVAL_PARAMS,
+                score::Score,
+            },
+        };
+
+        #[test]
+        fn scores_both_sides_equally_when_material_is_level() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act
+            let white = material_score(&board, true, &DEFAULT_EVAL_PARAMS, &mut ());
+            let black = material_score(&board, false, &DEFAULT_EVAL_PARAMS, &mut ());
+            // assert
+            assert_eq!(white, black);
+            assert_ne!(white, Score::ZERO)
+        }
+
+        #[test]
+        fn counts_every_piece_when_material_is_uneven() {
+            // arrange
+            // white has a queen and two pawns against black's two rooks
+            let game = GameState::from_fen("r3k2r/8/8/8/8/8/PP6/3QK3 w - - 0 1").expect("valid fen");
+            // act
+            let white = material_score(&game.board, true, &DEFAULT_EVAL_PARAMS, &mut ());
+            let black = material_score(&game.board, false, &DEFAULT_EVAL_PARAMS, &mut ());
+            // assert
+            assert_eq!(white, QUEEN_VALUE + PAWN_VALUE * 2);
+            assert_eq!(black, ROOK_VALUE * 2)
+        }
+    }
+
+    mod piece_value {
+        use crate::{
+            chess_state::chess_pieces::PieceEnum,
+            evaluation_engine::{
+                consts::{KNIGHT_VALUE, ROOK_VALUE},
+                material::piece_value,
+                params::DEFAULT_EVAL_PARAMS,
+                score::Score,
+            },
+        };
+
+        #[test]
+        fn values_pieces_alike_for_both_colours_and_kings_at_zero() {
+            // act + assert
+            assert_eq!(piece_value(PieceEnum::WhiteKnight, &DEFAULT_EVAL_PARAMS), KNIGHT_VALUE);
+            assert_eq!(piece_value(PieceEnum::BlackRook, &DEFAULT_EVAL_PARAMS), ROOK_VALUE);
+            assert_eq!(piece_value(PieceEnum::BlackKing, &DEFAULT_EVAL_PARAMS), Score::ZERO)
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    chess_state::{
+        board_bitmask::BoardBitmasks,
+        moves::attack_maps::{
+            BishopAttackMaps, BlackPawnAttackMaps, KnightAttackMaps, QueenAttackMaps, RookAttackMaps,
+            WhitePawnAttackMaps,
+        },
+    },
+    evaluation_engine::{params::EvalParams, score::Score, trace::CoefficientSink},
+};
+
+/// Sums the mobility bonus of every piece in `pieces`, looking each piece's safe square count up in the table
+/// called `name`
+fn pieces_mobility(
+    pieces: u64,
+    safe_squares: u64,
+    attack_map: impl Fn(u64) -> u64,
+    (name, table): (&'static str, &[Score]),
+    white: bool,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    let mut score = Score::ZERO;
+    let mut pieces = pieces;
+    while pieces != 0 {
+        let piece = 1u64 << pieces.trailing_zeros();
+        pieces &= pieces - 1;
+        let moves = (attack_map(piece) & safe_squares).count_ones() as usize;
+        let index = moves.min(table.len() - 1);
+        score += table[index];
+        coefficients.add(white, name, index, 1);
+    }
+    score
+}
+
+/// Scores how freely the white pieces (`white = true`) or black pieces (`white = false`) can move. Each knight,
+/// bishop, rook, and queen counts the squares it attacks, stopping at blockers, leaving out squares holding a
+/// friendly piece or attacked by an enemy pawn, and scores that count through its own non-linear table.
+pub(crate) fn mobility(
+    board: &BoardBitmasks,
+    white: bool,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    let occupied = board.all_pieces.mask;
+    let (own_pieces, knights, bishops, rooks, queens, enemy_pawn_attacks) = match white {
+        true => (
+            board.white_pieces.mask,
+            board.white_knights.mask,
+            board.white_bishops.mask,
+            board.white_rooks.mask,
+            board.white_queens.mask,
+            board.black_pawns.mask.calculate_unconstrained_black_pawn_attack_maps(),
+        ),
+        false => (
+            board.black_pieces.mask,
+            board.black_knights.mask,
+            board.black_bishops.mask,
+            board.black_rooks.mask,
+            board.black_queens.mask,
+            board.white_pawns.mask.calculate_unconstrained_white_pawn_attack_maps(),
+        ),
+    };
+    let safe_squares = !own_pieces & !enemy_pawn_attacks;
+
+    pieces_mobility(
+        knights,
+        safe_squares,
+        |piece| piece.calculate_unconstrained_knight_maps(),
+        ("knight_mobility", &params.knight_mobility),
+        white,
+        coefficients,
+    ) + pieces_mobility(
+        bishops,
+        safe_squares,
+        |piece| piece.calculate_bishop_attack_maps(occupied),
+        ("bishop_mobility", &params.bishop_mobility),
+        white,
+        coefficients,
+    ) + pieces_mobility(
+        rooks,
+        safe_squares,
+        |piece| piece.calculate_rook_attack_maps(occupied),
+        ("rook_mobility", &params.rook_mobility),
+        white,
+        coefficients,
+    ) + pieces_mobility(
+        queens,
+        safe_squares,
+        |piece| piece.calculate_queen_attack_maps(occupied),
+        ("queen_mobility", &params.queen_mobility),
+        white,
+        coefficients,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    mod mobility {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{
+                consts::{BISHOP_MOBILITY, KNIGHT_MOBILITY, QUEEN_MOBILITY, ROOK_MOBILITY},
+                mobility::mobility,
+                params::DEFAULT_EVAL_PARAMS,
+            },
+        };
+
+        #[test]
+        fn scores_both_sides_equally_when_in_starting_position() {
+            // arrange
+            let board = GameState::default().board;
+            // act
+            let white = mobility(&board, true, &DEFAULT_EVAL_PARAMS, &mut ());
+            // assert
+            // two squares for each knight, and no moves for anything else
+            assert_eq!(
+                white,
+                KNIGHT_MOBILITY[2] * 2
+                    + BISHOP_MOBILITY[0] * 2
+                    + ROOK_MOBILITY[0] * 2
+                    + QUEEN_MOBILITY[0]
+            );
+            assert_eq!(white, mobility(&board, false, &DEFAULT_EVAL_PARAMS, &mut ()))
+        }
+
+        #[test]
+        fn stops_sliders_at_blockers_and_leaves_out_squares_covered_by_enemy_pawns() {
+            // arrange
+            // the rook on a1 is hemmed in by its own pawn on a3 and knight on d1
+            let board = GameState::from_fen("4k3/8/8/8/8/P7/8/R2NK3 w - - 0 1")
+                .expect("valid fen")
+                .board;
+            // the black pawn on d4 covers c3 and e3
+            let covered = GameState::from_fen("4k3/8/8/8/3p4/8/8/3NK3 w - - 0 1")
+                .expect("valid fen")
+                .board;
+            // act + assert
+            // a2, b1, c1 for the rook; b2, c3, e3, f2 for the knight
+            assert_eq!(
+                mobility(&board, true, &DEFAULT_EVAL_PARAMS, &mut ()),
+                ROOK_MOBILITY[3] + KNIGHT_MOBILITY[4]
+            );
+            // only b2 and f2 are left for the knight
+            assert_eq!(mobility(&covered, true, &DEFAULT_EVAL_PARAMS, &mut ()), KNIGHT_MOBILITY[2])
+        }
+    }
+}
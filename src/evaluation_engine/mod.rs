@@ -0,0 +1,273 @@
+pub(crate) mod consts;
+pub(crate) mod king_safety;
+pub(crate) mod material;
+pub(crate) mod mobility;
+pub(crate) mod params;
+pub(crate) mod pawn_hash;
+pub(crate) mod pawn_structure;
+pub(crate) mod phase;
+pub(crate) mod piece_square_tables;
+pub(crate) mod score;
+#[cfg(test)]
+mod symmetry;
+pub(crate) mod trace;
+
+use crate::{
+    chess_state::{board_bitmask::BoardBitmasks, game_state::GameState},
+    evaluation_engine::{
+        king_safety::king_safety,
+        material::material_score,
+        mobility::mobility,
+        params::EvalParams,
+        pawn_hash::{PawnHashTable, DEFAULT_PAWN_HASH_ENTRIES},
+        pawn_structure::PawnEvaluation,
+        phase::game_phase,
+        piece_square_tables::piece_square_score,
+        score::Score,
+        trace::EvaluationTrace,
+    },
+};
+
+/// The parts of an evaluation before they are blended into one number, kept so they can be inspected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TaperedEvaluation {
+    /// The game phase, from `0` (pure endgame) up to `consts::MAX_PHASE` (pure middlegame)
+    pub phase: i32,
+    /// The middlegame and endgame scores from white's point of view
+    pub score: Score,
+}
+
+impl TaperedEvaluation {
+    /// Blends the middlegame and endgame scores by the phase, giving a score from white's point of view
+    pub(crate) fn tapered_score(&self) -> i32 {
+        self.score.taper(self.phase)
+    }
+}
+
+/// Sums every term from white's point of view, taking the pawn structure from an already computed evaluation.
+/// This is the fast path the search evaluates with, so unlike `EvaluationTrace` it keeps nothing but the total.
+fn sum_terms(board: &BoardBitmasks, pawns: &PawnEvaluation, params: &EvalParams) -> TaperedEvaluation {
+    let score = material_score(board, true, params, &mut ()) - material_score(board, false, params, &mut ())
+        + piece_square_score(board, true, params, &mut ())
+        - piece_square_score(board, false, params, &mut ())
+        + pawns.score()
+        + king_safety(board, true, params, &mut ())
+        - king_safety(board, false, params, &mut ())
+        + mobility(board, true, params, &mut ())
+        - mobility(board, false, params, &mut ());
+    TaperedEvaluation {
+        phase: game_phase(board, params),
+        score,
+    }
+}
+
+/// Works out the game phase and the middlegame and endgame scores of every term with the default parameters,
+/// without blending them or caching anything, for tests to check the `Evaluator` against
+#[cfg(test)]
+pub(crate) fn evaluate_tapered(board: &BoardBitmasks) -> TaperedEvaluation {
+    let pawns =
+        pawn_structure::evaluate_pawns(board.white_pawns.mask, board.black_pawns.mask, &EvalParams::DEFAULT, &mut ());
+    sum_terms(board, &pawns, &EvalParams::DEFAULT)
+}
+
+/// Works out every term of the evaluation with the default parameters, split by colour, without caching
+/// anything, for tests to check the `Evaluator` against
+#[cfg(test)]
+pub(crate) fn evaluate_with_trace(board: &BoardBitmasks) -> EvaluationTrace {
+    let pawns =
+        pawn_structure::evaluate_pawns(board.white_pawns.mask, board.black_pawns.mask, &EvalParams::DEFAULT, &mut ());
+    EvaluationTrace::new(board, &pawns, &EvalParams::DEFAULT, &mut ())
+}
+
+/// Turns a score from white's point of view into one from the side to move's point of view
+fn side_relative(white_score: i32, white_to_move: bool) -> i32 {
+    match white_to_move {
+        true => white_score,
+        false => -white_score,
+    }
+}
+
+/// Scores the position in centipawns from the point of view of the side to move with the default parameters, so
+/// a positive score is good for whoever is about to play. Scores are built up from white's point of view and
+/// flipped for black. Only tests use this, the engine evaluates through an `Evaluator`.
+#[cfg(test)]
+pub(crate) fn evaluate(game: &GameState) -> i32 {
+    side_relative(evaluate_tapered(&game.board).tapered_score(), game.white_to_move)
+}
+
+/// Evaluates positions with a set of parameters, keeping caches between calls, so a search should hold on to one
+/// evaluator for as long as it runs
+#[derive(Debug, Clone)]
+pub(crate) struct Evaluator {
+    params: EvalParams,
+    pawn_hash_table: PawnHashTable,
+}
+
+impl Evaluator {
+    pub(crate) fn new() -> Self {
+        Self::with_pawn_hash_entries(DEFAULT_PAWN_HASH_ENTRIES)
+    }
+
+    /// Creates an evaluator whose pawn hash table holds `entry_count` entries, rounded up to a power of two
+    pub(crate) fn with_pawn_hash_entries(entry_count: usize) -> Self {
+        Self {
+            params: EvalParams::default(),
+            pawn_hash_table: PawnHashTable::new(entry_count),
+        }
+    }
+
+    /// Creates an evaluator scoring positions with `params` instead of the defaults
+    pub(crate) fn with_params(params: EvalParams) -> Self {
+        Self {
+            params,
+            ..Self::new()
+        }
+    }
+
+    pub(crate) fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    /// Swaps in new parameters, emptying the caches since their entries were worked out with the old ones
+    #[cfg(test)]
+    pub(crate) fn set_params(&mut self, params: EvalParams) {
+        self.params = params;
+        self.clear()
+    }
+
+    /// Works out the game phase and the middlegame and endgame scores, looking the pawn structure up in the
+    /// pawn hash table
+    pub(crate) fn evaluate_tapered(&mut self, board: &BoardBitmasks) -> TaperedEvaluation {
+        let pawns = self
+            .pawn_hash_table
+            .get_or_evaluate(board.white_pawns.mask, board.black_pawns.mask, &self.params);
+        sum_terms(board, &pawns, &self.params)
+    }
+
+    /// Works out every term of the evaluation split by colour, looking the pawn structure up in the pawn hash
+    /// table
+    pub(crate) fn evaluate_with_trace(&mut self, board: &BoardBitmasks) -> EvaluationTrace {
+        let pawns = self
+            .pawn_hash_table
+            .get_or_evaluate(board.white_pawns.mask, board.black_pawns.mask, &self.params);
+        EvaluationTrace::new(board, &pawns, &self.params, &mut ())
+    }
+
+    /// Scores the position in centipawns from the point of view of the side to move
+    pub(crate) fn evaluate(&mut self, game: &GameState) -> i32 {
+        side_relative(self.evaluate_tapered(&game.board).tapered_score(), game.white_to_move)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn pawn_hash_table(&self) -> &PawnHashTable {
+        &self.pawn_hash_table
+    }
+
+    /// Empties every cache, e.g. before starting a new game
+    #[cfg(test)]
+    pub(crate) fn clear(&mut self) {
+        self.pawn_hash_table.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod evaluate {
+        use crate::{chess_state::game_state::GameState, evaluation_engine::evaluate};
+
+        #[test]
+        fn scores_zero_when_in_starting_position() {
+            // act + assert
+            assert_eq!(evaluate(&GameState::default()), 0)
+        }
+
+        #[test]
+        fn flips_sign_when_side_to_move_changes() {
+            // arrange
+            // white is a knight up
+            let white_to_move = GameState::from_fen("4k3/8/8/8/8/2N5/8/4K3 w - - 0 1")
+                .expect("valid fen");
+            let black_to_move = GameState::from_fen("4k3/8/8/8/8/2N5/8/4K3 b - - 0 1")
+                .expect("valid fen");
+            // act
+            let white_score = evaluate(&white_to_move);
+            let black_score = evaluate(&black_to_move);
+            // assert
+            assert!(white_score > 250);
+            assert_eq!(black_score, -white_score)
+        }
+    }
+
+    mod evaluator {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{evaluate, params::EvalParams, score::Score, Evaluator},
+        };
+
+        #[test]
+        fn matches_uncached_evaluation_and_hits_pawn_cache_when_pawns_are_unchanged() {
+            // arrange
+            let mut evaluator = Evaluator::with_pawn_hash_entries(256);
+            // the same pawns, with the knights and king moved around them
+            let fens = [
+                "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+                "r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 4 4",
+                "r1bqk2r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQK2R b KQkq - 4 4",
+            ];
+            for fen in fens {
+                let game = GameState::from_fen(fen).expect("valid fen");
+                // act + assert
+                assert_eq!(evaluator.evaluate(&game), evaluate(&game));
+            }
+            // assert
+            assert_eq!(evaluator.pawn_hash_table().misses(), 1);
+            assert_eq!(evaluator.pawn_hash_table().hits(), 2)
+        }
+
+        #[test]
+        fn uses_new_params_and_empties_caches_when_params_are_set() {
+            // arrange
+            let game = GameState::from_fen("4k3/8/8/8/8/2N5/8/4K3 w - - 0 1").expect("valid fen");
+            let mut evaluator = Evaluator::new();
+            let before = evaluator.evaluate(&game);
+            let mut params = EvalParams::default();
+            params.knight_value += Score::new(100, 100);
+            // act
+            evaluator.set_params(params);
+            let after = evaluator.evaluate(&game);
+            // assert
+            assert_eq!(after, before + 100);
+            assert_eq!(evaluator.pawn_hash_table().misses(), 1)
+        }
+    }
+
+    mod evaluate_tapered {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{consts::MAX_PHASE, evaluate_tapered},
+        };
+
+        #[test]
+        fn uses_endgame_score_when_only_kings_and_pawns_remain() {
+            // arrange
+            let game = GameState::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").expect("valid fen");
+            // act
+            let output = evaluate_tapered(&game.board);
+            // assert
+            assert_eq!(output.phase, 0);
+            assert_eq!(output.tapered_score(), output.score.eg)
+        }
+
+        #[test]
+        fn uses_middlegame_score_when_all_pieces_remain() {
+            // arrange
+            let game = GameState::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+                .expect("valid fen");
+            // act
+            let output = evaluate_tapered(&game.board);
+            // assert
+            assert_eq!(output.phase, MAX_PHASE);
+            assert_eq!(output.tapered_score(), output.score.mg)
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! Every tunable evaluation weight, gathered into one struct so it can be swapped at run time. The defaults come
+//! from `consts`, and a parameter file can override them without recompiling.
+//!
+//! A parameter file has one `name = values` entry per parameter, where the values are separated by commas.
+//! Most parameters are middlegame/endgame pairs (`pawn_value = 100 120`), but the phase and attack weights are
+//! plain integers the phase does not blend (`phase_weights = 1, 1, 2, 4`). An entry may run on over several
+//! lines, and anything after a `#` is a comment. Every parameter must be given exactly once, with exactly the
+//! expected number of values.
+
+use std::{fmt, fs, io, path::Path};
+
+use thiserror::Error;
+
+use crate::evaluation_engine::{consts::*, score::Score};
+
+#[derive(Debug, Error)]
+pub(crate) enum EvalParamsError {
+    #[error("Line {line}: unknown parameter {name}")]
+    UnknownParameter { line: usize, name: String },
+
+    #[error("Line {line}: parameter {name} is given more than once")]
+    DuplicateParameter { line: usize, name: String },
+
+    #[error("Parameter {0} is missing")]
+    MissingParameter(&'static str),
+
+    #[error("Parameter {name} expects {expected} values, but {found} were given")]
+    WrongLength {
+        name: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Line {line}: {value} is not a valid middlegame/endgame pair")]
+    InvalidValue { line: usize, value: String },
+
+    #[error("Line {line}: {value} is not a valid weight")]
+    InvalidWeight { line: usize, value: String },
+
+    #[error("Parameter {0} may not have negative weights")]
+    NegativeWeight(&'static str),
+
+    #[error("Phase weights must give a full set of pieces a phase above zero")]
+    ZeroPhase,
+
+    #[error("Line {0}: values given before any parameter name")]
+    ValuesWithoutName(usize),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// The values of one parameter: middlegame/endgame pairs, or plain weights that do not change with the phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ParamValues<'a> {
+    Scores(&'a [Score]),
+    Weights(&'a [i32]),
+}
+
+/// The values of one parameter, for changing them in place
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ParamValuesMut<'a> {
+    Scores(&'a mut [Score]),
+    Weights(&'a mut [i32]),
+}
+
+impl ParamValuesMut<'_> {
+    fn len(&self) -> usize {
+        match self {
+            ParamValuesMut::Scores(scores) => scores.len(),
+            ParamValuesMut::Weights(weights) => weights.len(),
+        }
+    }
+}
+
+/// A parameter as written in a file: its name and the line it is on, and each value with the line it is on
+type Entry<'a> = (&'a str, usize, Vec<(usize, &'a str)>);
+
+/// Zips separate middlegame and endgame piece-square tables into one table of scores
+const fn zip_tables(mg_table: [i32; 64], eg_table: [i32; 64]) -> [Score; 64] {
+    let mut output = [Score::ZERO; 64];
+    let mut index = 0;
+    while index < 64 {
+        output[index] = Score::new(mg_table[index], eg_table[index]);
+        index += 1;
+    }
+    output
+}
+
+/// The weights used by every evaluation term. Piece-square tables are in bitmask order, from h1 (index `0`) to a8
+/// (index `63`), and score white's pieces; black's are mirrored onto them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EvalParams {
+    pub pawn_value: Score,
+    pub knight_value: Score,
+    pub bishop_value: Score,
+    pub rook_value: Score,
+    pub queen_value: Score,
+
+    /// How much a knight, bishop, rook and queen each count towards the game phase. The phase is scaled so that
+    /// a full set of pieces comes to `consts::MAX_PHASE`, whatever the weights.
+    pub phase_weights: [i32; 4],
+
+    pub pawn_table: [Score; 64],
+    pub knight_table: [Score; 64],
+    pub bishop_table: [Score; 64],
+    pub rook_table: [Score; 64],
+    pub queen_table: [Score; 64],
+    pub king_table: [Score; 64],
+
+    pub doubled_pawn_penalty: Score,
+    pub isolated_pawn_penalty: Score,
+    pub backward_pawn_penalty: Score,
+    pub connected_pawn_bonus: Score,
+    pub passed_pawn_bonus: [Score; 8],
+    pub candidate_passer_bonus: [Score; 8],
+
+    pub pawn_shield_bonus: [Score; 2],
+    pub pawn_storm_penalty: [Score; 3],
+    pub king_semi_open_file_penalty: Score,
+    pub king_open_file_penalty: Score,
+    /// How much a knight, bishop, rook and queen attacking the king zone each add to the attack weight
+    pub king_attack_weights: [i32; 4],
+    pub king_zone_attack_penalty: [Score; 16],
+
+    pub knight_mobility: [Score; 9],
+    pub bishop_mobility: [Score; 14],
+    pub rook_mobility: [Score; 15],
+    pub queen_mobility: [Score; 28],
+}
+
+/// The parameters evaluated when none are given
+#[cfg(test)]
+pub(crate) static DEFAULT_EVAL_PARAMS: EvalParams = EvalParams::DEFAULT;
+
+impl EvalParams {
+    pub(crate) const DEFAULT: EvalParams = EvalParams {
+        pawn_value: PAWN_VALUE,
+        knight_value: KNIGHT_VALUE,
+        bishop_value: BISHOP_VALUE,
+        rook_value: ROOK_VALUE,
+        queen_value: QUEEN_VALUE,
+
+        phase_weights: [KNIGHT_PHASE, BISHOP_PHASE, ROOK_PHASE, QUEEN_PHASE],
+
+        pawn_table: zip_tables(PAWN_MG_TABLE, PAWN_EG_TABLE),
+        knight_table: zip_tables(KNIGHT_MG_TABLE, KNIGHT_EG_TABLE),
+        bishop_table: zip_tables(BISHOP_MG_TABLE, BISHOP_EG_TABLE),
+        rook_table: zip_tables(ROOK_MG_TABLE, ROOK_EG_TABLE),
+        queen_table: zip_tables(QUEEN_MG_TABLE, QUEEN_EG_TABLE),
+        king_table: zip_tables(KING_MG_TABLE, KING_EG_TABLE),
+
+        doubled_pawn_penalty: DOUBLED_PAWN_PENALTY,
+        isolated_pawn_penalty: ISOLATED_PAWN_PENALTY,
+        backward_pawn_penalty: BACKWARD_PAWN_PENALTY,
+        connected_pawn_bonus: CONNECTED_PAWN_BONUS,
+        passed_pawn_bonus: PASSED_PAWN_BONUS,
+        candidate_passer_bonus: CANDIDATE_PASSER_BONUS,
+
+        pawn_shield_bonus: PAWN_SHIELD_BONUS,
+        pawn_storm_penalty: PAWN_STORM_PENALTY,
+        king_semi_open_file_penalty: KING_SEMI_OPEN_FILE_PENALTY,
+        king_open_file_penalty: KING_OPEN_FILE_PENALTY,
+        king_attack_weights: [
+            KNIGHT_ATTACK_WEIGHT,
+            BISHOP_ATTACK_WEIGHT,
+            ROOK_ATTACK_WEIGHT,
+            QUEEN_ATTACK_WEIGHT,
+        ],
+        king_zone_attack_penalty: KING_ZONE_ATTACK_PENALTY,
+
+        knight_mobility: KNIGHT_MOBILITY,
+        bishop_mobility: BISHOP_MOBILITY,
+        rook_mobility: ROOK_MOBILITY,
+        queen_mobility: QUEEN_MOBILITY,
+    };
+
+    /// Every parameter by name, in the order they are written to a parameter file
+    pub(crate) fn fields(&self) -> [(&'static str, ParamValues<'_>); 28] {
+        use std::slice::from_ref;
+        use ParamValues::{Scores, Weights};
+        [
+            ("pawn_value", Scores(from_ref(&self.pawn_value))),
+            ("knight_value", Scores(from_ref(&self.knight_value))),
+            ("bishop_value", Scores(from_ref(&self.bishop_value))),
+            ("rook_value", Scores(from_ref(&self.rook_value))),
+            ("queen_value", Scores(from_ref(&self.queen_value))),
+            ("phase_weights", Weights(&self.phase_weights)),
+            ("pawn_table", Scores(&self.pawn_table)),
+            ("knight_table", Scores(&self.knight_table)),
+            ("bishop_table", Scores(&self.bishop_table)),
+            ("rook_table", Scores(&self.rook_table)),
+            ("queen_table", Scores(&self.queen_table)),
+            ("king_table", Scores(&self.king_table)),
+            ("doubled_pawn_penalty", Scores(from_ref(&self.doubled_pawn_penalty))),
+            ("isolated_pawn_penalty", Scores(from_ref(&self.isolated_pawn_penalty))),
+            ("backward_pawn_penalty", Scores(from_ref(&self.backward_pawn_penalty))),
+            ("connected_pawn_bonus", Scores(from_ref(&self.connected_pawn_bonus))),
+            ("passed_pawn_bonus", Scores(&self.passed_pawn_bonus)),
+            ("candidate_passer_bonus", Scores(&self.candidate_passer_bonus)),
+            ("pawn_shield_bonus", Scores(&self.pawn_shield_bonus)),
+            ("pawn_storm_penalty", Scores(&self.pawn_storm_penalty)),
+            ("king_semi_open_file_penalty", Scores(from_ref(&self.king_semi_open_file_penalty))),
+            ("king_open_file_penalty", Scores(from_ref(&self.king_open_file_penalty))),
+            ("king_attack_weights", Weights(&self.king_attack_weights)),
+            ("king_zone_attack_penalty", Scores(&self.king_zone_attack_penalty)),
+            ("knight_mobility", Scores(&self.knight_mobility)),
+            ("bishop_mobility", Scores(&self.bishop_mobility)),
+            ("rook_mobility", Scores(&self.rook_mobility)),
+            ("queen_mobility", Scores(&self.queen_mobility)),
+        ]
+    }
+
+    /// Every parameter by name, in the same order as `fields`, for changing values in place
+    pub(crate) fn fields_mut(&mut self) -> [(&'static str, ParamValuesMut<'_>); 28] {
+        use std::slice::from_mut;
+        use ParamValuesMut::{Scores, Weights};
+        [
+            ("pawn_value", Scores(from_mut(&mut self.pawn_value))),
+            ("knight_value", Scores(from_mut(&mut self.knight_value))),
+            ("bishop_value", Scores(from_mut(&mut self.bishop_value))),
+            ("rook_value", Scores(from_mut(&mut self.rook_value))),
+            ("queen_value", Scores(from_mut(&mut self.queen_value))),
+            ("phase_weights", Weights(&mut self.phase_weights)),
+            ("pawn_table", Scores(&mut self.pawn_table)),
+            ("knight_table", Scores(&mut self.knight_table)),
+            ("bishop_table", Scores(&mut self.bishop_table)),
+            ("rook_table", Scores(&mut self.rook_table)),
+            ("queen_table", Scores(&mut self.queen_table)),
+            ("king_table", Scores(&mut self.king_table)),
+            ("doubled_pawn_penalty", Scores(from_mut(&mut self.doubled_pawn_penalty))),
+            ("isolated_pawn_penalty", Scores(from_mut(&mut self.isolated_pawn_penalty))),
+            ("backward_pawn_penalty", Scores(from_mut(&mut self.backward_pawn_penalty))),
+            ("connected_pawn_bonus", Scores(from_mut(&mut self.connected_pawn_bonus))),
+            ("passed_pawn_bonus", Scores(&mut self.passed_pawn_bonus)),
+            ("candidate_passer_bonus", Scores(&mut self.candidate_passer_bonus)),
+            ("pawn_shield_bonus", Scores(&mut self.pawn_shield_bonus)),
+            ("pawn_storm_penalty", Scores(&mut self.pawn_storm_penalty)),
+            ("king_semi_open_file_penalty", Scores(from_mut(&mut self.king_semi_open_file_penalty))),
+            ("king_open_file_penalty", Scores(from_mut(&mut self.king_open_file_penalty))),
+            ("king_attack_weights", Weights(&mut self.king_attack_weights)),
+            ("king_zone_attack_penalty", Scores(&mut self.king_zone_attack_penalty)),
+            ("knight_mobility", Scores(&mut self.knight_mobility)),
+            ("bishop_mobility", Scores(&mut self.bishop_mobility)),
+            ("rook_mobility", Scores(&mut self.rook_mobility)),
+            ("queen_mobility", Scores(&mut self.queen_mobility)),
+        ]
+    }
+
+    /// The phase of a full set of pieces by the phase weights, before it is scaled to `consts::MAX_PHASE`
+    pub(crate) fn full_phase(&self) -> i32 {
+        let [knight, bishop, rook, queen] = self.phase_weights;
+        4 * knight + 4 * bishop + 4 * rook + 2 * queen
+    }
+
+    /// Checks the weights can be evaluated with: none negative, and a full set of pieces with a phase
+    pub(crate) fn validate(&self) -> Result<(), EvalParamsError> {
+        for (name, values) in self.fields() {
+            if let ParamValues::Weights(weights) = values {
+                if weights.iter().any(|&weight| weight < 0) {
+                    return Err(EvalParamsError::NegativeWeight(name));
+                }
+            }
+        }
+        match self.full_phase() {
+            0 => Err(EvalParamsError::ZeroPhase),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parses a parameter file, checking every expected parameter is given once with the right number of values
+    pub(crate) fn parse(text: &str) -> Result<Self, EvalParamsError> {
+        let mut entries: Vec<Entry> = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let values = match line.split_once('=') {
+                Some((name, values)) => {
+                    entries.push((name.trim(), line_number, Vec::new()));
+                    values
+                }
+                None => line,
+            };
+            let Some((_, _, entry_values)) = entries.last_mut() else {
+                return Err(EvalParamsError::ValuesWithoutName(line_number));
+            };
+            let values = values.split(',').map(str::trim).filter(|value| !value.is_empty());
+            entry_values.extend(values.map(|value| (line_number, value)));
+        }
+
+        let mut params = Self::DEFAULT;
+        let mut seen = [false; 28];
+        for (name, line, values) in entries {
+            let mut fields = params.fields_mut();
+            let Some(position) = fields.iter().position(|(field, _)| *field == name) else {
+                return Err(EvalParamsError::UnknownParameter {
+                    line,
+                    name: name.to_string(),
+                });
+            };
+            if seen[position] {
+                return Err(EvalParamsError::DuplicateParameter {
+                    line,
+                    name: name.to_string(),
+                });
+            }
+            seen[position] = true;
+
+            let (field, target) = &mut fields[position];
+            if target.len() != values.len() {
+                return Err(EvalParamsError::WrongLength {
+                    name: field,
+                    expected: target.len(),
+                    found: values.len(),
+                });
+            }
+            match target {
+                ParamValuesMut::Scores(scores) => {
+                    for (score, &(line, value)) in scores.iter_mut().zip(&values) {
+                        *score = parse_score(value).ok_or_else(|| EvalParamsError::InvalidValue {
+                            line,
+                            value: value.to_string(),
+                        })?;
+                    }
+                }
+                ParamValuesMut::Weights(weights) => {
+                    for (weight, &(line, value)) in weights.iter_mut().zip(&values) {
+                        *weight = value.parse().map_err(|_| EvalParamsError::InvalidWeight {
+                            line,
+                            value: value.to_string(),
+                        })?;
+                    }
+                }
+            }
+        }
+
+        if let Some(((name, _), _)) = params.fields().iter().zip(seen).find(|(_, seen)| !seen) {
+            return Err(EvalParamsError::MissingParameter(name));
+        }
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Reads and parses a parameter file
+    pub(crate) fn load(path: impl AsRef<Path>) -> Result<Self, EvalParamsError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Parses a single `mg eg` pair
+fn parse_score(value: &str) -> Option<Score> {
+    let mut halves = value.split_whitespace().map(str::parse::<i32>);
+    match (halves.next(), halves.next(), halves.next()) {
+        (Some(Ok(mg)), Some(Ok(eg)), None) => Some(Score::new(mg, eg)),
+        _ => None,
+    }
+}
+
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# evaluation parameters, as middlegame/endgame pairs")?;
+        writeln!(f, "# piece-square tables run from h1 to a8, one rank per line")?;
+        writeln!(f, "# weights are plain integers, for a knight, bishop, rook and queen")?;
+        for (name, values) in self.fields() {
+            let pairs: Vec<String> = match values {
+                ParamValues::Scores(scores) => {
+                    scores.iter().map(|score| format!("{} {}", score.mg, score.eg)).collect()
+                }
+                ParamValues::Weights(weights) => weights.iter().map(i32::to_string).collect(),
+            };
+            match pairs.len() {
+                64 => {
+                    writeln!(f, "{} =", name)?;
+                    for rank in pairs.chunks(8) {
+                        writeln!(f, "    {},", rank.join(", "))?;
+                    }
+                }
+                _ => writeln!(f, "{} = {}", name, pairs.join(", "))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod parse {
+        use crate::evaluation_engine::{
+            params::{EvalParams, EvalParamsError},
+            score::Score,
+        };
+
+        #[test]
+        fn reads_back_dumped_parameters() {
+            // arrange
+            let mut params = EvalParams {
+                knight_value: Score::new(333, 311),
+                ..EvalParams::default()
+            };
+            params.king_table[5] = Score::new(-7, 12);
+            params.phase_weights[3] = 5;
+            params.king_attack_weights = [1, 2, 4, 6];
+            // act
+            let output = EvalParams::parse(&params.to_string());
+            // assert
+            assert_eq!(output.expect("valid parameters"), params)
+        }
+
+        #[test]
+        fn ignores_comments_and_blank_lines() {
+            // arrange
+            let text = format!("# tuned\n\n{}", EvalParams::default()).replace(
+                "pawn_value = 100 120",
+                "pawn_value = 90 130 # a little lower",
+            );
+            // act
+            let output = EvalParams::parse(&text).expect("valid parameters");
+            // assert
+            assert_eq!(output.pawn_value, Score::new(90, 130))
+        }
+
+        #[test]
+        fn returns_error_when_parameter_is_unknown_missing_or_repeated() {
+            // arrange
+            let defaults = EvalParams::default().to_string();
+            let unknown = format!("{}bogus_value = 1 2\n", defaults);
+            let missing = defaults.replace("queen_value = 900 950\n", "");
+            let repeated = format!("{}queen_value = 900 950\n", defaults);
+            // act + assert
+            assert!(matches!(
+                EvalParams::parse(&unknown),
+                Err(EvalParamsError::UnknownParameter { name, .. }) if name == "bogus_value"
+            ));
+            assert!(matches!(
+                EvalParams::parse(&missing),
+                Err(EvalParamsError::MissingParameter("queen_value"))
+            ));
+            assert!(matches!(
+                EvalParams::parse(&repeated),
+                Err(EvalParamsError::DuplicateParameter { name, .. }) if name == "queen_value"
+            ))
+        }
+
+        #[test]
+        fn returns_error_when_values_are_malformed_or_wrong_length() {
+            // arrange
+            let defaults = EvalParams::default().to_string();
+            let short = defaults.replace("pawn_shield_bonus = 15 0, 8 0", "pawn_shield_bonus = 15 0");
+            let malformed = defaults.replace("pawn_value = 100 120", "pawn_value = 100");
+            // act + assert
+            assert!(matches!(
+                EvalParams::parse(&short),
+                Err(EvalParamsError::WrongLength {
+                    name: "pawn_shield_bonus",
+                    expected: 2,
+                    found: 1
+                })
+            ));
+            assert!(matches!(
+                EvalParams::parse(&malformed),
+                Err(EvalParamsError::InvalidValue { value, .. }) if value == "100"
+            ));
+            assert!(matches!(
+                EvalParams::parse("1 2\n"),
+                Err(EvalParamsError::ValuesWithoutName(1))
+            ))
+        }
+
+        #[test]
+        fn returns_error_when_weights_are_malformed_or_unusable() {
+            // arrange
+            let defaults = EvalParams::default().to_string();
+            let pair = defaults.replace("phase_weights = 1, 1, 2, 4", "phase_weights = 1 1, 1, 2, 4");
+            let negative = defaults.replace("king_attack_weights = 2, 2, 3, 5", "king_attack_weights = 2, -2, 3, 5");
+            let no_phase = defaults.replace("phase_weights = 1, 1, 2, 4", "phase_weights = 0, 0, 0, 0");
+            // act + assert
+            assert!(matches!(
+                EvalParams::parse(&pair),
+                Err(EvalParamsError::InvalidWeight { value, .. }) if value == "1 1"
+            ));
+            assert!(matches!(
+                EvalParams::parse(&negative),
+                Err(EvalParamsError::NegativeWeight("king_attack_weights"))
+            ));
+            assert!(matches!(EvalParams::parse(&no_phase), Err(EvalParamsError::ZeroPhase)))
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::evaluation_engine::{
+    params::EvalParams,
+    pawn_structure::{evaluate_pawns, PawnEvaluation},
+};
+
+/// The default number of entries in a `PawnHashTable`
+pub(crate) const DEFAULT_PAWN_HASH_ENTRIES: usize = 1 << 14;
+
+/// How many probes pass between each debug log of the hit and miss counters
+const STATISTICS_LOG_INTERVAL: u64 = 1 << 16;
+
+/// Scrambles the bits of `value` so that nearby bitmasks land far apart in the table (the splitmix64 finaliser)
+fn mix(value: u64) -> u64 {
+    let value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+/// A hash of both sides' pawn positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PawnKey(u64);
+
+impl PawnKey {
+    /// Builds a key from both sides' pawn bitmasks
+    pub(crate) fn new(white_pawns: u64, black_pawns: u64) -> Self {
+        Self(mix(mix(white_pawns) ^ black_pawns))
+    }
+}
+
+/// A cached evaluation, along with the pawns it was worked out for, since two pawn structures can share a key
+#[derive(Debug, Clone, Copy)]
+struct PawnHashEntry {
+    key: PawnKey,
+    white_pawns: u64,
+    black_pawns: u64,
+    evaluation: PawnEvaluation,
+}
+
+/// A fixed-size cache of pawn structure evaluations, indexed by `PawnKey`. Each slot holds one entry, and a new
+/// entry always replaces whatever was in its slot, since pawn structures seen recently are the most likely to
+/// come up again. Entries are only returned for exactly the pawns they were stored with.
+#[derive(Debug, Clone)]
+pub(crate) struct PawnHashTable {
+    entries: Vec<Option<PawnHashEntry>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl PawnHashTable {
+    /// Creates an empty table, rounding `entry_count` up to a power of two so slots can be found with a mask
+    pub(crate) fn new(entry_count: usize) -> Self {
+        Self {
+            entries: vec![None; entry_count.max(1).next_power_of_two()],
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn index(&self, key: PawnKey) -> usize {
+        key.0 as usize & (self.entries.len() - 1)
+    }
+
+    /// Looks up the cached evaluation of these pawns, counting the probe as a hit or a miss
+    pub(crate) fn probe(&mut self, white_pawns: u64, black_pawns: u64) -> Option<PawnEvaluation> {
+        let key = PawnKey::new(white_pawns, black_pawns);
+        let found = self.entries[self.index(key)]
+            .filter(|entry| entry.key == key && entry.white_pawns == white_pawns && entry.black_pawns == black_pawns)
+            .map(|entry| entry.evaluation);
+        match found {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        if (self.hits + self.misses).is_multiple_of(STATISTICS_LOG_INTERVAL) {
+            self.log_statistics();
+        }
+        found
+    }
+
+    /// Stores the evaluation of these pawns, replacing anything already in its slot
+    pub(crate) fn store(&mut self, white_pawns: u64, black_pawns: u64, evaluation: PawnEvaluation) {
+        let key = PawnKey::new(white_pawns, black_pawns);
+        let index = self.index(key);
+        self.entries[index] = Some(PawnHashEntry {
+            key,
+            white_pawns,
+            black_pawns,
+            evaluation,
+        });
+    }
+
+    /// Returns the cached pawn structure evaluation for these pawns, working it out and storing it on a miss.
+    /// Entries are not keyed on `params`, so the table must be cleared whenever the parameters change.
+    pub(crate) fn get_or_evaluate(
+        &mut self,
+        white_pawns: u64,
+        black_pawns: u64,
+        params: &EvalParams,
+    ) -> PawnEvaluation {
+        if let Some(evaluation) = self.probe(white_pawns, black_pawns) {
+            return evaluation;
+        }
+        let evaluation = evaluate_pawns(white_pawns, black_pawns, params, &mut ());
+        self.store(white_pawns, black_pawns, evaluation);
+        evaluation
+    }
+
+    /// Empties the table and resets the counters
+    #[cfg(test)]
+    pub(crate) fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    #[cfg(test)]
+    pub(crate) fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Writes the hit and miss counters to the debug log
+    pub(crate) fn log_statistics(&self) {
+        let probes = self.hits + self.misses;
+        let hit_rate = match probes {
+            0 => 0.0,
+            _ => self.hits as f64 / probes as f64 * 100.0,
+        };
+        log::debug!(
+            "Pawn hash table: {} hits, {} misses ({:.1}% hit rate) over {} entries",
+            self.hits,
+            self.misses,
+            hit_rate,
+            self.entries.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod pawn_key {
+        use crate::evaluation_engine::pawn_hash::PawnKey;
+
+        #[test]
+        fn gives_different_keys_when_pawns_differ() {
+            // arrange
+            let white_pawns = 0x00_00_00_00_00_00_FF_00;
+            let black_pawns = 0x00_FF_00_00_00_00_00_00;
+            // act
+            let key = PawnKey::new(white_pawns, black_pawns);
+            let swapped = PawnKey::new(black_pawns, white_pawns);
+            let moved_pawn = PawnKey::new(white_pawns ^ 0x00_00_00_00_00_01_01_00, black_pawns);
+            // assert
+            assert_eq!(key, PawnKey::new(white_pawns, black_pawns));
+            assert_ne!(key, swapped);
+            assert_ne!(key, moved_pawn)
+        }
+    }
+
+    mod pawn_hash_table {
+        use crate::evaluation_engine::{
+            params::DEFAULT_EVAL_PARAMS,
+            pawn_hash::{PawnHashEntry, PawnHashTable, PawnKey},
+            pawn_structure::evaluate_pawns,
+        };
+
+        #[test]
+        fn rounds_size_up_to_power_of_two() {
+            // act + assert
+            assert_eq!(PawnHashTable::new(1000).entries.len(), 1024);
+            assert_eq!(PawnHashTable::new(0).entries.len(), 1)
+        }
+
+        #[test]
+        fn counts_hits_and_misses_when_probed() {
+            // arrange
+            let mut table = PawnHashTable::new(64);
+            let (white_pawns, black_pawns) = (0x00_00_00_00_10_00_EF_00, 0x00_EF_00_10_00_00_00_00);
+            // act
+            let first = table.get_or_evaluate(white_pawns, black_pawns, &DEFAULT_EVAL_PARAMS);
+            let second = table.get_or_evaluate(white_pawns, black_pawns, &DEFAULT_EVAL_PARAMS);
+            // assert
+            assert_eq!(first, evaluate_pawns(white_pawns, black_pawns, &DEFAULT_EVAL_PARAMS, &mut ()));
+            assert_eq!(second, first);
+            assert_eq!((table.hits(), table.misses()), (1, 1))
+        }
+
+        #[test]
+        fn misses_when_slot_has_been_replaced_by_other_pawns() {
+            // arrange
+            // a single slot, so every key shares it
+            let mut table = PawnHashTable::new(1);
+            table.store(0xFF00, 0, evaluate_pawns(0xFF00, 0, &DEFAULT_EVAL_PARAMS, &mut ()));
+            table.store(0xFF0000, 0, evaluate_pawns(0xFF0000, 0, &DEFAULT_EVAL_PARAMS, &mut ()));
+            // act + assert
+            assert_eq!(table.probe(0xFF00, 0), None);
+            assert_eq!(
+                table.probe(0xFF0000, 0),
+                Some(evaluate_pawns(0xFF0000, 0, &DEFAULT_EVAL_PARAMS, &mut ()))
+            )
+        }
+
+        #[test]
+        fn misses_when_other_pawns_share_the_key() {
+            // arrange
+            let mut table = PawnHashTable::new(1);
+            // a colliding entry: stored under the key of the probed pawns, but for a different structure
+            table.entries[0] = Some(PawnHashEntry {
+                key: PawnKey::new(0xFF00, 0),
+                white_pawns: 0xFF0000,
+                black_pawns: 0,
+                evaluation: evaluate_pawns(0xFF0000, 0, &DEFAULT_EVAL_PARAMS, &mut ()),
+            });
+            // act + assert
+            assert_eq!(table.probe(0xFF00, 0), None);
+            assert_eq!(
+                table.get_or_evaluate(0xFF00, 0, &DEFAULT_EVAL_PARAMS),
+                evaluate_pawns(0xFF00, 0, &DEFAULT_EVAL_PARAMS, &mut ())
+            )
+        }
+
+        #[test]
+        fn forgets_entries_and_counters_when_cleared() {
+            // arrange
+            let mut table = PawnHashTable::new(8);
+            table.get_or_evaluate(0xFF00, 0, &DEFAULT_EVAL_PARAMS);
+            // act
+            table.clear();
+            // assert
+            assert_eq!((table.hits(), table.misses()), (0, 0));
+            assert_eq!(table.probe(0xFF00, 0), None)
+        }
+    }
+}
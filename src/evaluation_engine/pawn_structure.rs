@@ -0,0 +1,274 @@
+use crate::{
+    chess_state::{
+        coordinates::XCoordinate,
+        moves::{
+            chess_flip::ChessFlip,
+            chess_move::{ChessDirection, ChessShiftMove},
+        },
+    },
+    evaluation_engine::{params::EvalParams, score::Score, trace::CoefficientSink},
+};
+
+/// Spreads every set bit up the board to the eighth rank
+pub(crate) fn north_fill(bitmask: u64) -> u64 {
+    let bitmask = bitmask | bitmask << 8;
+    let bitmask = bitmask | bitmask << 16;
+    bitmask | bitmask << 32
+}
+
+/// Spreads every set bit down the board to the first rank
+pub(crate) fn south_fill(bitmask: u64) -> u64 {
+    let bitmask = bitmask | bitmask >> 8;
+    let bitmask = bitmask | bitmask >> 16;
+    bitmask | bitmask >> 32
+}
+
+/// Spreads every set bit along its whole file
+pub(crate) fn file_fill(bitmask: u64) -> u64 {
+    north_fill(bitmask) | south_fill(bitmask)
+}
+
+/// Returns the files either side of the set bits, without the bits' own files
+pub(crate) fn adjacent_files(bitmask: u64) -> u64 {
+    let files = file_fill(bitmask);
+    files.shift_move(ChessDirection::Left) | files.shift_move(ChessDirection::Right)
+}
+
+/// The pawn structure scores of both sides, along with each side's passed pawns, which later terms reuse
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PawnEvaluation {
+    pub white: Score,
+    pub black: Score,
+    pub white_passed: u64,
+    pub black_passed: u64,
+}
+
+impl PawnEvaluation {
+    /// Returns the pawn structure score from white's point of view
+    pub(crate) fn score(&self) -> Score {
+        self.white - self.black
+    }
+}
+
+/// Scores the pawn structure of both sides. Only pawn bitmasks are needed, so the result can be cached against
+/// the pawn positions alone.
+pub(crate) fn evaluate_pawns(
+    white_pawns: u64,
+    black_pawns: u64,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> PawnEvaluation {
+    let (white, white_passed) = evaluate_side(white_pawns, black_pawns, true, params, coefficients);
+    // black's pawns are mirrored onto white's side of the board, so "up" is always forwards
+    let (black, black_passed) = evaluate_side(
+        black_pawns.flip_vertical(),
+        white_pawns.flip_vertical(),
+        false,
+        params,
+        coefficients,
+    );
+    PawnEvaluation {
+        white,
+        black,
+        white_passed,
+        black_passed: black_passed.flip_vertical(),
+    }
+}
+
+/// Returns the rank of a single bit bitmask as an index, where `0` is the first rank
+fn rank_index(bitmask: u64) -> usize {
+    bitmask.trailing_zeros() as usize / 8
+}
+
+/// Scores one side's pawns as if they were white, moving up the board, returning the score and passed pawns.
+/// `white` only says which side the parameters used are recorded for.
+fn evaluate_side(
+    own: u64,
+    enemy: u64,
+    white: bool,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> (Score, u64) {
+    let mut score = Score::ZERO;
+
+    // doubled: every pawn after the first on a file
+    for file in XCoordinate::ALL {
+        let pawns_on_file = (own & file as u64).count_ones() as i32;
+        if pawns_on_file > 1 {
+            score += params.doubled_pawn_penalty * (pawns_on_file - 1);
+            coefficients.add(white, "doubled_pawn_penalty", 0, pawns_on_file - 1);
+        }
+    }
+
+    // isolated: no friendly pawns on either neighbouring file
+    let isolated = own & !adjacent_files(own);
+    score += params.isolated_pawn_penalty * isolated.count_ones() as i32;
+    coefficients.add(white, "isolated_pawn_penalty", 0, isolated.count_ones() as i32);
+
+    let own_attacks = own.shift_move(ChessDirection::UpLeft) | own.shift_move(ChessDirection::UpRight);
+    let enemy_attacks =
+        enemy.shift_move(ChessDirection::DownLeft) | enemy.shift_move(ChessDirection::DownRight);
+
+    // connected: defended by a pawn, or standing beside one
+    let phalanx = own & (own.shift_move(ChessDirection::Left) | own.shift_move(ChessDirection::Right));
+    let connected = own & (own_attacks | phalanx);
+    score += params.connected_pawn_bonus * connected.count_ones() as i32;
+    coefficients.add(white, "connected_pawn_bonus", 0, connected.count_ones() as i32);
+
+    // backward: the square in front is covered by an enemy pawn, and no friendly pawn can ever come up to defend
+    // it, as every neighbour is already further up the board
+    let stops = own.shift_move(ChessDirection::Up);
+    let backward = (stops & enemy_attacks & !north_fill(own_attacks)).shift_move(ChessDirection::Down)
+        & !isolated;
+    score += params.backward_pawn_penalty * backward.count_ones() as i32;
+    coefficients.add(white, "backward_pawn_penalty", 0, backward.count_ones() as i32);
+
+    // passed: no enemy pawns ahead on the same or neighbouring files, and not stuck behind a friendly pawn
+    let enemy_front_spans = south_fill(enemy.shift_move(ChessDirection::Down));
+    let enemy_blockers = enemy_front_spans
+        | enemy_front_spans.shift_move(ChessDirection::Left)
+        | enemy_front_spans.shift_move(ChessDirection::Right);
+    let passed = own & !enemy_blockers & !south_fill(own.shift_move(ChessDirection::Down));
+
+    // candidates: not yet passed, but the file ahead is clear and friendly pawns on the neighbouring files, level
+    // or behind, outnumber the enemy pawns ahead that could stop it
+    let mut candidates = own & !passed & !enemy_front_spans & !south_fill(own.shift_move(ChessDirection::Down));
+    while candidates != 0 {
+        let pawn = 1u64 << candidates.trailing_zeros();
+        candidates &= candidates - 1;
+
+        let neighbours = adjacent_files(pawn);
+        // every rank further up the board than the pawn; candidates are never on the eighth rank
+        let ahead = !((1u64 << (8 * (rank_index(pawn) + 1))) - 1);
+        let helpers = (own & neighbours & !ahead).count_ones();
+        let sentries = (enemy & neighbours & ahead).count_ones();
+        if helpers >= sentries {
+            score += params.candidate_passer_bonus[rank_index(pawn)];
+            coefficients.add(white, "candidate_passer_bonus", rank_index(pawn), 1);
+        }
+    }
+
+    let mut remaining = passed;
+    while remaining != 0 {
+        let pawn = 1u64 << remaining.trailing_zeros();
+        remaining &= remaining - 1;
+        score += params.passed_pawn_bonus[rank_index(pawn)];
+        coefficients.add(white, "passed_pawn_bonus", rank_index(pawn), 1);
+    }
+
+    (score, passed)
+}
+
+#[cfg(test)]
+mod tests {
+    mod fills {
+        use crate::{
+            chess_state::coordinates::{XCoordinate::*, YCoordinate::*},
+            evaluation_engine::pawn_structure::{adjacent_files, file_fill, north_fill, south_fill},
+        };
+
+        #[test]
+        fn spreads_bits_along_files_when_filled() {
+            // arrange
+            let d4 = D as u64 & Four as u64;
+            // act + assert
+            assert_eq!(north_fill(d4), D as u64 & !(One as u64 | Two as u64 | Three as u64));
+            assert_eq!(south_fill(d4), D as u64 & (One as u64 | Two as u64 | Three as u64 | Four as u64));
+            assert_eq!(file_fill(d4), D as u64);
+            assert_eq!(adjacent_files(d4), C as u64 | E as u64);
+            assert_eq!(adjacent_files(A as u64 & Two as u64), B as u64)
+        }
+    }
+
+    mod evaluate_pawns {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{
+                consts::{
+                    BACKWARD_PAWN_PENALTY, CANDIDATE_PASSER_BONUS, CONNECTED_PAWN_BONUS,
+                    DOUBLED_PAWN_PENALTY, ISOLATED_PAWN_PENALTY, PASSED_PAWN_BONUS,
+                },
+                params::DEFAULT_EVAL_PARAMS,
+                pawn_structure::{evaluate_pawns, PawnEvaluation},
+                score::Score,
+            },
+        };
+
+        fn evaluate_fen(fen: &str) -> PawnEvaluation {
+            let game = GameState::from_fen(fen).expect("valid fen");
+            evaluate_pawns(game.board.white_pawns.mask, game.board.black_pawns.mask, &DEFAULT_EVAL_PARAMS, &mut ())
+        }
+
+        #[test]
+        fn scores_level_when_in_starting_position() {
+            // act
+            let output = evaluate_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+            // assert
+            assert_eq!(output.score(), Score::ZERO);
+            assert_eq!(output.white_passed, 0);
+            assert_eq!(output.black_passed, 0)
+        }
+
+        #[test]
+        fn penalises_doubled_isolated_pawns() {
+            // act
+            // two white pawns on the c file, with nothing beside them and no black pawns to stop them
+            let output = evaluate_fen("4k3/8/8/8/2P5/2P5/8/4K3 w - - 0 1");
+            // assert
+            // the front pawn is passed, the rear pawn is stuck behind it
+            let expected = DOUBLED_PAWN_PENALTY
+                + ISOLATED_PAWN_PENALTY * 2
+                + PASSED_PAWN_BONUS[3];
+            assert_eq!(output.white, expected);
+            assert_eq!(output.black, Score::ZERO)
+        }
+
+        #[test]
+        fn rewards_passed_pawns_more_the_further_they_have_advanced() {
+            // act
+            // white's pawn is on the sixth rank and black's on the fourth (black's own fifth)
+            let output = evaluate_fen("4k3/8/1P6/8/6p1/8/8/4K3 w - - 0 1");
+            // assert
+            assert_eq!(output.white, ISOLATED_PAWN_PENALTY + PASSED_PAWN_BONUS[5]);
+            assert_eq!(output.black, ISOLATED_PAWN_PENALTY + PASSED_PAWN_BONUS[4]);
+            assert_eq!(output.white_passed.count_ones(), 1);
+            assert_eq!(output.black_passed.count_ones(), 1)
+        }
+
+        #[test]
+        fn finds_backward_and_connected_pawns() {
+            // act
+            // the white d3 pawn has been left behind by c4 and e4, and the black pawn on e5 covers d4
+            let output = evaluate_fen("4k3/8/8/4p3/2P1P3/3P4/8/4K3 w - - 0 1");
+            // assert
+            // d3 defends c4 and e4 but cannot safely step to d4, while c4 has no black pawns left to stop it
+            assert_eq!(
+                output.white,
+                CONNECTED_PAWN_BONUS * 2 + BACKWARD_PAWN_PENALTY + PASSED_PAWN_BONUS[3]
+            )
+        }
+
+        #[test]
+        fn rewards_candidate_passer_when_helpers_outnumber_sentries() {
+            // act
+            // the c4 pawn faces one sentry on b5, but has support from the pawns on b3 and d4
+            let output = evaluate_fen("4k3/8/8/1p6/2PP4/1P6/8/4K3 w - - 0 1");
+            // assert
+            // b3 defends c4, and c4 and d4 stand side by side; d4 is passed
+            assert_eq!(
+                output.white,
+                CONNECTED_PAWN_BONUS * 2 + CANDIDATE_PASSER_BONUS[3] + PASSED_PAWN_BONUS[3]
+            )
+        }
+
+        #[test]
+        fn mirrors_scores_when_colours_are_swapped() {
+            // act
+            let output = evaluate_fen("4k3/pp3p2/2p5/3P2p1/8/8/PP3PPP/4K3 w - - 0 1");
+            let mirrored = evaluate_fen("4k3/pp3ppp/8/8/3p2P1/2P5/PP3P2/4K3 w - - 0 1");
+            // assert
+            assert_eq!(output.white, mirrored.black);
+            assert_eq!(output.black, mirrored.white)
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    chess_state::board_bitmask::BoardBitmasks,
+    evaluation_engine::{consts::MAX_PHASE, params::EvalParams},
+};
+
+/// Counts the knights, bishops, rooks and queens of both colours left on the board
+pub(crate) fn piece_counts(board: &BoardBitmasks) -> [i32; 4] {
+    [
+        board.white_knights.count() + board.black_knights.count(),
+        board.white_bishops.count() + board.black_bishops.count(),
+        board.white_rooks.count() + board.black_rooks.count(),
+        board.white_queens.count() + board.black_queens.count(),
+    ]
+    .map(|count| count as i32)
+}
+
+/// Calculates the game phase from counts of the knights, bishops, rooks and queens left, as given by
+/// `piece_counts`, with the phase weights in `params`
+pub(crate) fn phase_from_counts(counts: [i32; 4], params: &EvalParams) -> i32 {
+    let phase: i32 = counts.iter().zip(params.phase_weights).map(|(count, weight)| count * weight).sum();
+    (phase * MAX_PHASE / params.full_phase()).min(MAX_PHASE)
+}
+
+/// Calculates the game phase from the non-pawn material left on the board with the phase weights in `params`,
+/// from `0` when only kings and pawns remain up to `MAX_PHASE` for a full set of pieces. Extra pieces from
+/// promotions are capped at `MAX_PHASE`.
+pub(crate) fn game_phase(board: &BoardBitmasks, params: &EvalParams) -> i32 {
+    phase_from_counts(piece_counts(board), params)
+}
+
+#[cfg(test)]
+mod tests {
+    mod game_phase {
+        use crate::{
+            chess_state::{board_bitmask::BoardBitmasks, game_state::GameState},
+            evaluation_engine::{
+                consts::MAX_PHASE,
+                params::{EvalParams, DEFAULT_EVAL_PARAMS},
+                phase::game_phase,
+            },
+        };
+
+        #[test]
+        fn returns_maximum_phase_when_in_starting_position() {
+            // act + assert
+            assert_eq!(game_phase(&BoardBitmasks::default(), &DEFAULT_EVAL_PARAMS), MAX_PHASE)
+        }
+
+        #[test]
+        fn returns_zero_when_only_kings_and_pawns_remain() {
+            // arrange
+            let game = GameState::from_fen("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1").expect("valid fen");
+            // act + assert
+            assert_eq!(game_phase(&game.board, &DEFAULT_EVAL_PARAMS), 0)
+        }
+
+        #[test]
+        fn weights_pieces_when_some_material_remains() {
+            // arrange
+            // a rook and knight each: 2 * (2 + 1)
+            let game = GameState::from_fen("3rk1n1/8/8/8/8/8/8/3RK1N1 w - - 0 1").expect("valid fen");
+            // act + assert
+            assert_eq!(game_phase(&game.board, &DEFAULT_EVAL_PARAMS), 6)
+        }
+
+        #[test]
+        fn scales_phase_to_maximum_when_weights_are_changed() {
+            // arrange
+            let params = EvalParams {
+                phase_weights: [1, 1, 1, 1],
+                ..EvalParams::default()
+            };
+            // a rook and knight each: 4 of the 14 a full set weighs, scaled to the maximum
+            let game = GameState::from_fen("3rk1n1/8/8/8/8/8/8/3RK1N1 w - - 0 1").expect("valid fen");
+            // act + assert
+            assert_eq!(game_phase(&BoardBitmasks::default(), &params), MAX_PHASE);
+            assert_eq!(game_phase(&game.board, &params), 4 * MAX_PHASE / 14)
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    chess_state::{board_bitmask::BoardBitmasks, moves::chess_flip::ChessFlip},
+    evaluation_engine::{params::EvalParams, score::Score, trace::CoefficientSink},
+};
+
+/// Sums the values of the table called `name` for every square set in `bitmask`
+fn table_score(
+    bitmask: u64,
+    (name, table): (&'static str, &[Score; 64]),
+    white: bool,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    let mut score = Score::ZERO;
+    let mut bitmask = bitmask;
+    while bitmask != 0 {
+        let square = bitmask.trailing_zeros() as usize;
+        score += table[square];
+        coefficients.add(white, name, square, 1);
+        bitmask &= bitmask - 1;
+    }
+    score
+}
+
+/// Scores where the white pieces (`white = true`) or black pieces (`white = false`) stand using the
+/// piece-square tables. Black pieces are mirrored onto white's side of the board first.
+pub(crate) fn piece_square_score(
+    board: &BoardBitmasks,
+    white: bool,
+    params: &EvalParams,
+    coefficients: &mut impl CoefficientSink,
+) -> Score {
+    let pieces = match white {
+        true => [
+            board.white_pawns.mask,
+            board.white_knights.mask,
+            board.white_bishops.mask,
+            board.white_rooks.mask,
+            board.white_queens.mask,
+            board.white_kings.mask,
+        ],
+        false => [
+            board.black_pawns.mask,
+            board.black_knights.mask,
+            board.black_bishops.mask,
+            board.black_rooks.mask,
+            board.black_queens.mask,
+            board.black_kings.mask,
+        ]
+        .map(ChessFlip::flip_vertical),
+    };
+    let tables = [
+        ("pawn_table", &params.pawn_table),
+        ("knight_table", &params.knight_table),
+        ("bishop_table", &params.bishop_table),
+        ("rook_table", &params.rook_table),
+        ("queen_table", &params.queen_table),
+        ("king_table", &params.king_table),
+    ];
+
+    pieces
+        .into_iter()
+        .zip(tables)
+        .map(|(pieces, table)| table_score(pieces, table, white, coefficients))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    mod piece_square_score {
+        use crate::{
+            chess_state::{board_bitmask::BoardBitmasks, game_state::GameState},
+            evaluation_engine::{
+                params::DEFAULT_EVAL_PARAMS, piece_square_tables::piece_square_score, score::Score,
+            },
+        };
+
+        #[test]
+        fn scores_zero_when_position_is_mirrored() {
+            // arrange
+            let board = BoardBitmasks::default();
+            // act + assert
+            assert_eq!(
+                piece_square_score(&board, true, &DEFAULT_EVAL_PARAMS, &mut ()),
+                piece_square_score(&board, false, &DEFAULT_EVAL_PARAMS, &mut ())
+            )
+        }
+
+        #[test]
+        fn rewards_central_knight_over_rim_knight() {
+            // arrange
+            // the white knight is on e4 and the black knight on a6, its rim square
+            let game = GameState::from_fen("4k3/8/n7/8/4N3/8/8/4K3 w - - 0 1").expect("valid fen");
+            // act
+            let white = piece_square_score(&game.board, true, &DEFAULT_EVAL_PARAMS, &mut ());
+            let black = piece_square_score(&game.board, false, &DEFAULT_EVAL_PARAMS, &mut ());
+            // assert
+            // e4 is worth 20 in both tables, while a3 (a6 mirrored) is worth -30 and -20; the kings cancel out
+            assert_eq!(white - black, Score::new(20 - -30, 20 - -20))
+        }
+
+        #[test]
+        fn prefers_sheltered_king_in_middlegame_and_central_king_in_endgame() {
+            // arrange
+            // the white king is tucked away on g1, the black king stands in the centre on e5
+            let game = GameState::from_fen("8/8/8/4k3/8/8/8/6K1 w - - 0 1").expect("valid fen");
+            // act
+            let output = piece_square_score(&game.board, true, &DEFAULT_EVAL_PARAMS, &mut ())
+                - piece_square_score(&game.board, false, &DEFAULT_EVAL_PARAMS, &mut ());
+            // assert
+            assert!(output.mg > 0);
+            assert!(output.eg < 0)
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use crate::evaluation_engine::consts::MAX_PHASE;
+
+/// A pair of middlegame and endgame scores in centipawns. Every evaluation term is worked out as a `Score`, and
+/// the two halves are only blended together at the end by `taper`, according to how much material is left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub(crate) const ZERO: Score = Score::new(0, 0);
+
+    pub(crate) const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Blends the middlegame and endgame scores, where `phase` runs from `0` (only kings and pawns left, pure
+    /// endgame) up to `MAX_PHASE` (every piece still on the board, pure middlegame).
+    pub(crate) fn taper(self, phase: i32) -> i32 {
+        let phase = phase.clamp(0, MAX_PHASE);
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl Sub for Score {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl Neg for Score {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self::new(self.mg * rhs, self.eg * rhs)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl Sum for Score {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Score::ZERO, Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod taper {
+        use crate::evaluation_engine::{consts::MAX_PHASE, score::Score};
+
+        #[test]
+        fn uses_one_half_when_phase_is_at_either_end() {
+            // arrange
+            let score = Score::new(100, -40);
+            // act + assert
+            assert_eq!(score.taper(MAX_PHASE), 100);
+            assert_eq!(score.taper(0), -40);
+        }
+
+        #[test]
+        fn blends_linearly_when_phase_is_in_between() {
+            // arrange
+            let score = Score::new(120, 0);
+            // act + assert
+            assert_eq!(score.taper(MAX_PHASE / 2), 60);
+            assert_eq!(score.taper(MAX_PHASE / 4), 30);
+        }
+
+        #[test]
+        fn clamps_phase_when_promotions_push_it_past_the_maximum() {
+            // arrange
+            let score = Score::new(100, -40);
+            // act + assert
+            assert_eq!(score.taper(MAX_PHASE + 4), 100)
+        }
+    }
+}
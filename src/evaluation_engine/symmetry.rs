@@ -0,0 +1,150 @@
+//! Checks that the evaluation treats both colours alike. Every position in the corpus is swapped onto the other
+//! side with `GameState::colour_flipped`, which must give an equal score for the side to move, an opposite score
+//! from white's point of view, and the same terms with white and black swapped.
+
+use crate::{
+    chess_state::{game_state::GameState, moves::chess_flip::ChessFlip},
+    evaluation_engine::{evaluate, evaluate_with_trace, params::DEFAULT_EVAL_PARAMS, pawn_structure::evaluate_pawns},
+};
+
+/// Varied positions from every stage of the game, most of them lopsided so that a term scoring one colour
+/// differently from the other cannot cancel itself out
+const CORPUS: [&str; 32] = [
+    // openings
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+    "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "rnbqk2r/ppp1bppp/4pn2/3p2B1/2PP4/2N5/PP2PPPP/R2QKBNR w KQkq - 4 5",
+    "rnbqkb1r/pp3ppp/4pn2/2pp4/3P4/2PBPN2/PP3PPP/RNBQK2R b KQkq - 1 5",
+    "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1",
+    // middlegames
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "r2q1rk1/pp2bppp/2n1bn2/3p4/3N4/2N1B1P1/PP2PPBP/R2Q1RK1 w - - 2 11",
+    "2r2rk1/pp1bqpp1/2n1p2p/3pP3/3P4/P1PB1N2/5PPP/R2Q1RK1 b - - 0 16",
+    "r1b2rk1/2q1bppp/p2p1n2/np2p3/3PP3/5N1P/PPBN1PP1/R1BQR1K1 b - - 0 12",
+    "6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1",
+    "r1bq1rk1/pppp1Npp/2n2n2/2b1p3/2B1P3/8/PPPP1PPP/RNBQK2R b KQ - 0 6",
+    "2kr3r/ppp2ppp/2n1bn2/2b1p3/4P3/2NP1N2/PPP1BPPP/R1B2RK1 w - - 5 9",
+    "r3kb1r/1p3ppp/p1n1pn2/q2p4/3P1B2/2NBPN2/PP3PPP/R2QK2R w KQkq - 0 10",
+    "3q1rk1/p4pp1/1p2p2p/3nP3/3P4/P2Q1N2/5PPP/2R3K1 w - - 0 22",
+    "r1bqr1k1/pp3pbp/2np1np1/2p1p3/2P1P3/2NPBNP1/PP3PBP/R2QR1K1 w - - 4 11",
+    // endgames
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "4k3/8/8/8/8/2N5/8/4K3 w - - 0 1",
+    "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+    "8/8/1P6/8/6p1/8/8/4K1k1 b - - 0 1",
+    "8/5pk1/6p1/7p/7P/6P1/5PK1/8 w - - 0 40",
+    "8/8/4k3/3p4/2pP4/2P5/4K3/8 w - - 0 50",
+    "1r4k1/5ppp/8/8/8/8/5PPP/2R3K1 w - - 0 30",
+    "8/8/3bk3/8/2P5/2K1B3/8/8 w - - 0 45",
+    "8/1q6/8/3k4/8/4K3/6Q1/8 b - - 0 60",
+    "4k3/1P6/8/8/8/8/6p1/4K3 w - - 0 70",
+    // awkward pawn structures
+    "4k3/pp3p2/2p5/3P2p1/8/8/PP3PPP/4K3 w - - 0 1",
+    "4k3/8/8/1p6/2PP4/1P6/8/4K3 w - - 0 1",
+    "4k3/8/8/4p3/2P1P3/3P4/8/4K3 w - - 0 1",
+    "r3k2r/1pp2pp1/p1p4p/8/8/P1P4P/1P3PP1/R3K2R b KQkq - 0 15",
+];
+
+/// Asserts that the evaluation of the position in `fen` mirrors exactly onto its colour-flipped twin
+fn assert_colour_symmetric(fen: &str) {
+    let game = GameState::from_fen(fen).expect("valid fen");
+    let flipped = game.colour_flipped();
+
+    let trace = evaluate_with_trace(&game.board);
+    let flipped_trace = evaluate_with_trace(&flipped.board);
+    assert_eq!(trace.phase, flipped_trace.phase, "phase differs for {}", fen);
+    for (term, flipped_term) in trace.terms.iter().zip(flipped_trace.terms) {
+        assert_eq!(
+            (term.white, term.black),
+            (flipped_term.black, flipped_term.white),
+            "{} is not colour symmetric for {}",
+            term.term.name(),
+            fen
+        );
+    }
+
+    let pawns = evaluate_pawns(
+        game.board.white_pawns.mask,
+        game.board.black_pawns.mask,
+        &DEFAULT_EVAL_PARAMS,
+        &mut (),
+    );
+    let flipped_pawns = evaluate_pawns(
+        flipped.board.white_pawns.mask,
+        flipped.board.black_pawns.mask,
+        &DEFAULT_EVAL_PARAMS,
+        &mut (),
+    );
+    assert_eq!(
+        (pawns.white_passed, pawns.black_passed),
+        (
+            flipped_pawns.black_passed.flip_vertical(),
+            flipped_pawns.white_passed.flip_vertical()
+        ),
+        "passed pawns are not colour symmetric for {}",
+        fen
+    );
+
+    assert_eq!(trace.total(), -flipped_trace.total(), "white's score is not opposite for {}", fen);
+    assert_eq!(evaluate(&game), evaluate(&flipped), "side to move's score differs for {}", fen);
+}
+
+#[cfg(test)]
+mod tests {
+    mod evaluate {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{
+                evaluate,
+                symmetry::{assert_colour_symmetric, CORPUS},
+            },
+        };
+
+        #[test]
+        fn evaluates_colours_alike_when_given_corpus() {
+            for fen in CORPUS {
+                assert_colour_symmetric(fen);
+            }
+        }
+
+        #[test]
+        fn scores_zero_when_position_is_its_own_colour_flip() {
+            // arrange
+            // both the start position and this one are the same for either colour, apart from the side to move
+            for fen in [CORPUS[0], "r3k2r/pp3ppp/8/8/8/8/PP3PPP/R3K2R w KQkq - 0 1"] {
+                let game = GameState::from_fen(fen).expect("valid fen");
+                // act + assert
+                assert_eq!(game.colour_flipped().board, game.board);
+                assert_eq!(evaluate(&game), 0, "{}", fen);
+            }
+        }
+    }
+
+    mod evaluate_with_trace {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{evaluate_tapered, evaluate_with_trace, symmetry::CORPUS, Evaluator},
+        };
+
+        #[test]
+        fn traces_sum_exactly_to_fast_evaluation_when_given_corpus() {
+            let mut evaluator = Evaluator::new();
+            for fen in CORPUS {
+                // arrange
+                let game = GameState::from_fen(fen).expect("valid fen");
+                for board in [game.board, game.colour_flipped().board] {
+                    // act
+                    let trace = evaluate_with_trace(&board);
+                    let fast = evaluate_tapered(&board);
+                    // assert
+                    assert_eq!((trace.phase, trace.total()), (fast.phase, fast.score), "{}", fen);
+                    assert_eq!(evaluator.evaluate_tapered(&board), fast, "{}", fen);
+                    assert_eq!(evaluator.evaluate_with_trace(&board), trace, "{}", fen);
+                }
+            }
+        }
+    }
+}
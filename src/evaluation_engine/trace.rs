@@ -0,0 +1,236 @@
+use std::fmt;
+
+use crate::{
+    chess_state::board_bitmask::BoardBitmasks,
+    evaluation_engine::{
+        consts::MAX_PHASE, king_safety::king_safety, material::material_score, mobility::mobility,
+        params::EvalParams,
+        pawn_structure::PawnEvaluation,
+        phase::game_phase,
+        piece_square_tables::piece_square_score, score::Score,
+    },
+};
+
+/// Collects how often each parameter is used in an evaluation, so the tuner can rebuild the evaluation for any
+/// parameters without going back to the board
+pub(crate) trait CoefficientSink {
+    /// Records that the white pieces (`white = true`) or black pieces (`white = false`) use the value at `element`
+    /// of the parameter called `name`, as in a parameter file, `count` times
+    fn add(&mut self, white: bool, name: &'static str, element: usize, count: i32);
+}
+
+/// Records nothing, for evaluations that only need the score
+impl CoefficientSink for () {
+    fn add(&mut self, _white: bool, _name: &'static str, _element: usize, _count: i32) {}
+}
+
+/// One of the terms that make up an evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EvaluationTerm {
+    Material,
+    PieceSquareTables,
+    PawnStructure,
+    KingSafety,
+    Mobility,
+}
+
+impl EvaluationTerm {
+    pub(crate) const ALL: [EvaluationTerm; 5] = [
+        EvaluationTerm::Material,
+        EvaluationTerm::PieceSquareTables,
+        EvaluationTerm::PawnStructure,
+        EvaluationTerm::KingSafety,
+        EvaluationTerm::Mobility,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            EvaluationTerm::Material => "Material",
+            EvaluationTerm::PieceSquareTables => "Piece-square tables",
+            EvaluationTerm::PawnStructure => "Pawn structure",
+            EvaluationTerm::KingSafety => "King safety",
+            EvaluationTerm::Mobility => "Mobility",
+        }
+    }
+}
+
+/// What a single term scored for each side, where a higher score is better for that side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TermTrace {
+    pub term: EvaluationTerm,
+    pub white: Score,
+    pub black: Score,
+}
+
+impl TermTrace {
+    /// Returns the term's contribution from white's point of view
+    pub(crate) fn total(&self) -> Score {
+        self.white - self.black
+    }
+}
+
+/// Every term of an evaluation, split by colour and by middlegame and endgame, along with the game phase. The
+/// fast evaluation sums the same terms without keeping them apart, and the two are checked to agree exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EvaluationTrace {
+    /// The game phase, from `0` (pure endgame) up to `consts::MAX_PHASE` (pure middlegame)
+    pub phase: i32,
+    pub terms: [TermTrace; 5],
+}
+
+impl EvaluationTrace {
+    /// Works out every term for the board, taking the pawn structure from an already computed evaluation and
+    /// recording every other parameter used in `coefficients`
+    pub(crate) fn new(
+        board: &BoardBitmasks,
+        pawns: &PawnEvaluation,
+        params: &EvalParams,
+        coefficients: &mut impl CoefficientSink,
+    ) -> Self {
+        let term = |term: EvaluationTerm| {
+            let (white, black) = match term {
+                EvaluationTerm::Material => (
+                    material_score(board, true, params, coefficients),
+                    material_score(board, false, params, coefficients),
+                ),
+                EvaluationTerm::PieceSquareTables => (
+                    piece_square_score(board, true, params, coefficients),
+                    piece_square_score(board, false, params, coefficients),
+                ),
+                EvaluationTerm::PawnStructure => (pawns.white, pawns.black),
+                EvaluationTerm::KingSafety => (
+                    king_safety(board, true, params, coefficients),
+                    king_safety(board, false, params, coefficients),
+                ),
+                EvaluationTerm::Mobility => (
+                    mobility(board, true, params, coefficients),
+                    mobility(board, false, params, coefficients),
+                ),
+            };
+            TermTrace { term, white, black }
+        };
+
+        Self {
+            phase: game_phase(board, params),
+            terms: EvaluationTerm::ALL.map(term),
+        }
+    }
+
+    /// Returns the trace of a single term
+    #[cfg(test)]
+    pub(crate) fn term(&self, term: EvaluationTerm) -> TermTrace {
+        self.terms[term as usize]
+    }
+
+    /// Sums every term from white's point of view, before tapering
+    pub(crate) fn total(&self) -> Score {
+        self.terms.iter().map(TermTrace::total).sum()
+    }
+}
+
+impl fmt::Display for EvaluationTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<20} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}",
+            "Term", "White", "", "Black", "", "Total", ""
+        )?;
+        writeln!(
+            f,
+            "{:<20} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}",
+            "", "mg", "eg", "mg", "eg", "mg", "eg"
+        )?;
+        writeln!(f, "{}", "-".repeat(65))?;
+        for trace in self.terms {
+            let total = trace.total();
+            writeln!(
+                f,
+                "{:<20} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}",
+                trace.term.name(),
+                trace.white.mg,
+                trace.white.eg,
+                trace.black.mg,
+                trace.black.eg,
+                total.mg,
+                total.eg
+            )?;
+        }
+        writeln!(f, "{}", "-".repeat(65))?;
+        let total = self.total();
+        writeln!(
+            f,
+            "{:<20} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}",
+            "Total", "", "", "", "", total.mg, total.eg
+        )?;
+        writeln!(f)?;
+        writeln!(f, "Phase: {} / {}", self.phase, MAX_PHASE)?;
+        write!(f, "Final score from white's point of view: {}", total.taper(self.phase))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod evaluation_trace {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{
+                evaluate_tapered, evaluate_with_trace, material::material_score, params::DEFAULT_EVAL_PARAMS,
+                score::Score, trace::EvaluationTerm,
+            },
+        };
+
+        const FENS: [&str; 5] = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 4 4",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+
+        #[test]
+        fn sums_exactly_to_fast_evaluation() {
+            for fen in FENS {
+                // arrange
+                let board = GameState::from_fen(fen).expect("valid fen").board;
+                // act
+                let trace = evaluate_with_trace(&board);
+                // assert
+                let summed: Score = trace.terms.iter().map(|term| term.white - term.black).sum();
+                assert_eq!(summed, trace.total());
+                let fast = evaluate_tapered(&board);
+                assert_eq!((trace.phase, trace.total()), (fast.phase, fast.score));
+                assert_eq!(
+                    trace.total().taper(trace.phase),
+                    evaluate_tapered(&board).tapered_score()
+                );
+            }
+        }
+
+        #[test]
+        fn splits_terms_by_colour() {
+            // arrange
+            // white is a knight up
+            let board = GameState::from_fen("4k3/8/8/8/8/2N5/8/4K3 w - - 0 1").expect("valid fen").board;
+            // act
+            let trace = evaluate_with_trace(&board);
+            // assert
+            let material = trace.term(EvaluationTerm::Material);
+            assert_eq!(material.term, EvaluationTerm::Material);
+            assert_eq!(material.white, material_score(&board, true, &DEFAULT_EVAL_PARAMS, &mut ()));
+            assert_eq!(material.black, Score::ZERO)
+        }
+
+        #[test]
+        fn lists_every_term_when_displayed() {
+            // arrange
+            let board = GameState::default().board;
+            // act
+            let output = evaluate_with_trace(&board).to_string();
+            // assert
+            for term in EvaluationTerm::ALL {
+                assert!(output.contains(term.name()), "missing {}", term.name());
+            }
+            assert!(output.contains("Final score from white's point of view: 0"))
+        }
+    }
+}
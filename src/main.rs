@@ -1,11 +1,19 @@
 use chess_state::chess_pieces::{piece_structs::*, PieceEnum};
 use env_logger::Builder;
-use std::io::Write;
+use evaluation_engine::params::EvalParams;
+use std::io::{BufReader, Write};
 
 mod bitmask;
 mod chess_state;
+mod cli;
 mod evaluation_engine;
+mod search_engine;
 mod shared;
+// built into the `tune` binary, and only declared here so its tests run with the rest, which leaves its entry
+// points unused
+#[cfg(test)]
+#[allow(dead_code)]
+mod tuner;
 
 fn main() {
     let mut builder = Builder::from_default_env();
@@ -22,5 +30,49 @@ fn main() {
         .init();
     log::info!("Initialised logger");
 
-    println!("Hello, world!");
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // `--params <file>` swaps the default evaluation parameters for ones read from a file
+    let params = match args.iter().position(|arg| arg == "--params") {
+        Some(index) if index + 1 < args.len() => {
+            let path: Vec<String> = args.drain(index..=index + 1).collect();
+            match EvalParams::load(&path[1]) {
+                Ok(params) => {
+                    log::info!("Loaded evaluation parameters from {}", path[1]);
+                    params
+                }
+                Err(error) => {
+                    log::error!("Could not load evaluation parameters from {}: {}", path[1], error);
+                    return;
+                }
+            }
+        }
+        Some(_) => {
+            log::error!("usage: chess-engine --params <file> <command>, but no file was given after --params");
+            return;
+        }
+        None => EvalParams::default(),
+    };
+
+    match args.first().map(String::as_str) {
+        Some("eval") if args.len() > 1 => {
+            if let Err(error) = cli::evaluate::run(&args[1..].join(" "), &params, &mut std::io::stdout()) {
+                log::error!("Could not evaluate position: {}", error);
+            }
+        }
+        Some("params") => print!("{}", params),
+        Some("search") => {
+            let input = BufReader::new(std::io::stdin());
+            if let Err(error) = cli::search::run(&args[1..], &params, input, &mut std::io::stdout()) {
+                log::error!("Could not search position: {}", error);
+            }
+        }
+        Some("edit") => {
+            if let Err(error) = cli::position_editor::run(std::io::stdin().lock(), &mut std::io::stdout()) {
+                log::error!("Position editor stopped: {}", error);
+            }
+        }
+        _ => println!(
+            "usage: chess-engine [--params <file>] edit | eval <fen> | params | search <depth> <fen> [options]"
+        ),
+    }
 }
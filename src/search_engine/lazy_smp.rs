@@ -0,0 +1,212 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use crate::search_engine::{limits::SearchLimits, time_manager::TimeManager};
+
+/// How many nodes a thread visits between publishing its node count to the other threads and checking whether
+/// the search has reached its limits
+pub(super) const NODE_PUBLISH_INTERVAL: u64 = 1024;
+
+/// How many depths in a row each helper thread searches before skipping as many, indexed by helper. Helpers
+/// with the same pattern start at a different phase, so at any time the threads are spread over nearby depths
+/// instead of all searching the same one.
+const SKIP_SIZE: [u32; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+
+/// Where in its pattern of searched and skipped depths each helper thread starts, indexed by helper
+const SKIP_PHASE: [u32; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+/// What the threads of one search share besides the transposition table
+#[derive(Debug)]
+pub(super) struct SharedSearchState {
+    stop: Arc<AtomicBool>,
+    /// The nodes each thread has visited, indexed by thread
+    nodes: Vec<AtomicU64>,
+    node_limit: Option<u64>,
+    deadline: Option<Instant>,
+}
+
+impl SharedSearchState {
+    /// Sets up a search on `threads` threads, starting now, that stops when `stop` is set, it reaches the node
+    /// or time limit in `limits`, or it reaches the hard limit of `time_manager`
+    pub(super) fn new(
+        threads: usize,
+        stop: Arc<AtomicBool>,
+        limits: &SearchLimits,
+        time_manager: Option<&TimeManager>,
+    ) -> Self {
+        let (node_limit, move_time) = match limits.infinite {
+            true => (None, None),
+            false => (limits.nodes, limits.move_time),
+        };
+        let move_time_deadline = move_time.map(|move_time| Instant::now() + move_time);
+        let clock_deadline = time_manager.filter(|_| !limits.infinite).map(TimeManager::deadline);
+        Self {
+            stop,
+            nodes: (0..threads).map(|_| AtomicU64::new(0)).collect(),
+            node_limit,
+            deadline: move_time_deadline.into_iter().chain(clock_deadline).min(),
+        }
+    }
+
+    pub(super) fn threads(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub(super) fn node_limit(&self) -> Option<u64> {
+        self.node_limit
+    }
+
+    /// Tells every thread to give up the depth it is on and return
+    pub(super) fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed)
+    }
+
+    pub(super) fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn publish_nodes(&self, thread_index: usize, nodes: u64) {
+        self.nodes[thread_index].store(nodes, Ordering::Relaxed)
+    }
+
+    /// The nodes visited by every thread, as of when each last published its count
+    pub(super) fn total_nodes(&self) -> u64 {
+        self.nodes.iter().map(|nodes| nodes.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Stops the search if the threads have visited as many nodes as allowed or the time is up
+    pub(super) fn check_limits(&self) {
+        let out_of_nodes = self.node_limit.is_some_and(|limit| self.total_nodes() >= limit);
+        let out_of_time = self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        if out_of_nodes || out_of_time {
+            self.stop()
+        }
+    }
+}
+
+/// Whether the thread with `thread_index` leaves out `depth` of its iterative deepening. The main thread, index
+/// zero, searches every depth.
+pub(super) fn skips_depth(thread_index: usize, depth: u32) -> bool {
+    if thread_index == 0 {
+        return false;
+    }
+    let helper = (thread_index - 1) % SKIP_SIZE.len();
+    (depth + SKIP_PHASE[helper]) / SKIP_SIZE[helper] % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    mod skips_depth {
+        use crate::search_engine::lazy_smp::skips_depth;
+
+        fn searched_depths(thread_index: usize) -> Vec<u32> {
+            (1..=8).filter(|&depth| !skips_depth(thread_index, depth)).collect()
+        }
+
+        #[test]
+        fn spreads_helper_threads_over_different_depths() {
+            // act + assert
+            assert_eq!(searched_depths(0), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+            assert_eq!(searched_depths(1), vec![2, 4, 6, 8]);
+            assert_eq!(searched_depths(2), vec![1, 3, 5, 7]);
+            assert_eq!(searched_depths(3), vec![1, 4, 5, 8]);
+            assert_eq!(searched_depths(4), vec![3, 4, 7, 8])
+        }
+    }
+
+    mod shared_search_state {
+        use std::{
+            sync::{atomic::AtomicBool, Arc},
+            time::Duration,
+        };
+
+        use crate::search_engine::{
+            lazy_smp::SharedSearchState,
+            limits::SearchLimits,
+            time_manager::{Clock, TimeManager},
+        };
+
+        fn shared_search_state(threads: usize, limits: &SearchLimits) -> SharedSearchState {
+            SharedSearchState::new(threads, Arc::new(AtomicBool::new(false)), limits, None)
+        }
+
+        #[test]
+        fn adds_up_nodes_published_by_every_thread() {
+            // arrange
+            let shared = shared_search_state(3, &SearchLimits::default());
+            // act
+            shared.publish_nodes(0, 100);
+            shared.publish_nodes(2, 50);
+            shared.publish_nodes(0, 120);
+            // assert
+            assert_eq!(shared.total_nodes(), 170);
+            assert!(!shared.is_stopped());
+            shared.stop();
+            assert!(shared.is_stopped())
+        }
+
+        #[test]
+        fn stops_once_nodes_or_time_run_out_unless_infinite() {
+            // arrange
+            let node_limited = shared_search_state(
+                2,
+                &SearchLimits {
+                    nodes: Some(1000),
+                    ..SearchLimits::default()
+                },
+            );
+            let time_limited = shared_search_state(
+                1,
+                &SearchLimits {
+                    move_time: Some(Duration::ZERO),
+                    ..SearchLimits::default()
+                },
+            );
+            let infinite = shared_search_state(
+                1,
+                &SearchLimits {
+                    move_time: Some(Duration::ZERO),
+                    infinite: true,
+                    ..SearchLimits::default()
+                },
+            );
+            // act
+            node_limited.publish_nodes(0, 600);
+            node_limited.check_limits();
+            let stopped_early = node_limited.is_stopped();
+            node_limited.publish_nodes(1, 400);
+            node_limited.check_limits();
+            time_limited.check_limits();
+            infinite.check_limits();
+            // assert
+            assert!(!stopped_early);
+            assert!(node_limited.is_stopped());
+            assert!(time_limited.is_stopped());
+            assert!(!infinite.is_stopped())
+        }
+
+        #[test]
+        fn stops_at_the_earlier_of_the_move_time_and_the_clock() {
+            // arrange
+            let clock = Clock {
+                white_time: Duration::from_millis(20),
+                ..Clock::default()
+            };
+            let time_manager = TimeManager::new(&clock, true);
+            let limits = SearchLimits {
+                move_time: Some(Duration::from_secs(60)),
+                ..SearchLimits::default()
+            };
+            let shared = SharedSearchState::new(1, Arc::new(AtomicBool::new(false)), &limits, Some(&time_manager));
+            // act
+            shared.check_limits();
+            // assert
+            assert!(shared.is_stopped())
+        }
+    }
+}
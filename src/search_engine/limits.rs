@@ -0,0 +1,59 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{chess_state::moves::shared::Move, search_engine::time_manager::Clock};
+
+/// When a search should stop, and which root moves it considers. The search stops at whichever limit it reaches
+/// first, or after the deepest depth there is if it reaches none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SearchLimits {
+    /// The deepest depth to search
+    pub depth: Option<u32>,
+    /// How many nodes to visit at most, counting every thread. A search on one thread stops at exactly this
+    /// many, so the result is the same every time.
+    pub nodes: Option<u64>,
+    /// How long to search for
+    pub move_time: Option<Duration>,
+    /// The time left on the clocks, which a time manager shares out between the moves still to play
+    pub clock: Option<Clock>,
+    /// Searches until stopped through a `StopHandle`, ignoring every other limit. The search keeps waiting even
+    /// after it has searched as deep as it can go.
+    pub infinite: bool,
+    /// Stops as soon as the side to move is found to mate in this many moves or fewer
+    pub mate: Option<u32>,
+    /// The root moves to choose between, or every legal move if empty
+    pub search_moves: Vec<Move>,
+}
+
+impl SearchLimits {
+    /// Limits that only stop the search after `depth`
+    #[cfg(test)]
+    pub(crate) fn with_depth(depth: u32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+}
+
+/// Stops a search from another thread, e.g. when a GUI sends `stop` during an infinite search. A search already
+/// running returns with the result of the last depth it completed.
+#[derive(Debug, Clone)]
+pub(crate) struct StopHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl StopHandle {
+    pub(super) fn new(stop: Arc<AtomicBool>) -> Self {
+        Self { stop }
+    }
+
+    pub(crate) fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,1252 @@
+//! Finds the best move in a position with a negamax alpha-beta search. The search deepens one ply at a time, so
+//! there is always a finished result from the last completed depth to fall back on.
+
+use std::{
+    cmp::Reverse,
+    mem,
+    sync::{atomic::AtomicBool, Arc},
+    thread,
+    time::Duration,
+};
+
+mod lazy_smp;
+pub(crate) mod limits;
+pub(crate) mod move_ordering;
+pub(crate) mod principal_variation;
+mod quiescence;
+pub(crate) mod score;
+pub(crate) mod see;
+pub(crate) mod selectivity;
+pub(crate) mod time_manager;
+pub(crate) mod transposition_table;
+
+use crate::{
+    chess_state::{game_state::GameState, moves::shared::Move},
+    evaluation_engine::{params::EvalParams, Evaluator},
+    search_engine::{
+        lazy_smp::{skips_depth, SharedSearchState, NODE_PUBLISH_INTERVAL},
+        limits::{SearchLimits, StopHandle},
+        move_ordering::{is_quiet, MoveOrdering, MovePicker, PreviousMove},
+        principal_variation::PvTable,
+        score::{SearchScore, INFINITY, MATE_SCORE, MATE_THRESHOLD, MAX_PLY},
+        selectivity::{
+            futility_margin, late_move_pruning_count, late_move_reduction, null_move_reduction,
+            reverse_futility_margin, SearchFeatures, FUTILITY_MAX_DEPTH, LATE_MOVE_REDUCTION_MIN_DEPTH,
+            LATE_MOVE_REDUCTION_MIN_INDEX, NULL_MOVE_MIN_DEPTH, REVERSE_FUTILITY_MAX_DEPTH,
+        },
+        time_manager::{TimeManager, EASY_MOVE_MARGIN},
+        transposition_table::{Bound, TranspositionTable, DEFAULT_TRANSPOSITION_TABLE_MEGABYTES},
+    },
+};
+
+/// The least depth searched with an aspiration window rather than a full one. The first few depths are too
+/// shallow for their scores to say much about the next.
+const ASPIRATION_MIN_DEPTH: u32 = 4;
+
+/// How far either side of the last depth's score the first aspiration window reaches. The window doubles every
+/// time the score falls outside it.
+const ASPIRATION_WINDOW: i32 = 25;
+
+/// How often an infinite search that has run out of depths checks whether it has been stopped yet
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The outcome of searching one line of a position to one depth
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SearchResult {
+    /// How many plies deep the search went
+    pub depth: u32,
+    /// Where the line ranks among the lines searched, counting from 1 for the best
+    pub multi_pv: usize,
+    /// The move the line starts with, which for the best line is the move the search would play, or `None`
+    /// when there are no legal moves
+    pub best_move: Option<Move>,
+    /// The score of the line from the point of view of the side to move
+    pub score: SearchScore,
+    /// The line of play the search expects, starting with `best_move`
+    pub principal_variation: Vec<Move>,
+    /// How many positions were visited to reach this depth, counting every earlier depth too
+    pub nodes: u64,
+}
+
+/// Searches positions for the best move, holding on to the evaluator, the transposition table, and the move
+/// ordering heuristics between searches. With more than one thread, helper threads search the same position
+/// alongside the main one, each with a searcher of its own but all sharing the transposition table, so they
+/// fill it with results the others can use.
+#[derive(Debug, Clone)]
+pub(crate) struct Searcher {
+    evaluator: Evaluator,
+    transposition_table: Arc<TranspositionTable>,
+    ordering: MoveOrdering,
+    features: SearchFeatures,
+    pv_table: PvTable,
+    /// How many of the best root moves get a line of their own
+    multi_pv: usize,
+    /// The root moves the search is limited to, or empty for every legal move
+    search_moves: Vec<Move>,
+    /// Root moves left out of the search because an earlier line of the same depth already starts with them
+    excluded_root_moves: Vec<Move>,
+    /// The move played at each ply of the line being searched, for the countermove heuristic. A null move is
+    /// recorded as `None`.
+    moves_played: Vec<Option<PreviousMove>>,
+    nodes: u64,
+    threads: usize,
+    /// The searchers of the helper threads, kept between searches along with what they have learned
+    helpers: Vec<Searcher>,
+    /// Which thread of the search this searcher runs on, zero for the main thread
+    thread_index: usize,
+    /// Set to stop the search, either by the searcher itself or through a `StopHandle`
+    stop: Arc<AtomicBool>,
+    shared: Arc<SharedSearchState>,
+    /// Decides when a search on a clock stops, only ever set on the main thread
+    time_manager: Option<TimeManager>,
+}
+
+impl Searcher {
+    #[cfg(test)]
+    pub(crate) fn new() -> Self {
+        Self::with_params(EvalParams::default())
+    }
+
+    /// Creates a searcher that evaluates positions with `params` instead of the defaults
+    pub(crate) fn with_params(params: EvalParams) -> Self {
+        let transposition_table = Arc::new(TranspositionTable::new(DEFAULT_TRANSPOSITION_TABLE_MEGABYTES));
+        Self::with_transposition_table(params, transposition_table)
+    }
+
+    fn with_transposition_table(params: EvalParams, transposition_table: Arc<TranspositionTable>) -> Self {
+        Self {
+            evaluator: Evaluator::with_params(params),
+            transposition_table,
+            ordering: MoveOrdering::new(),
+            features: SearchFeatures::default(),
+            pv_table: PvTable::new(),
+            multi_pv: 1,
+            search_moves: Vec::new(),
+            excluded_root_moves: Vec::new(),
+            moves_played: vec![None; MAX_PLY + 1],
+            nodes: 0,
+            threads: 1,
+            helpers: Vec::new(),
+            thread_index: 0,
+            stop: Arc::new(AtomicBool::new(false)),
+            shared: Arc::new(SharedSearchState::new(
+                1,
+                Arc::new(AtomicBool::new(false)),
+                &SearchLimits::default(),
+                None,
+            )),
+            time_manager: None,
+        }
+    }
+
+    /// Swaps the transposition table for an empty one taking up at most `megabytes` of memory
+    pub(crate) fn set_hash_size(&mut self, megabytes: usize) {
+        self.transposition_table = Arc::new(TranspositionTable::new(megabytes))
+    }
+
+    /// Chooses which of the selective search techniques later searches use
+    #[cfg(test)]
+    pub(crate) fn set_features(&mut self, features: SearchFeatures) {
+        self.features = features
+    }
+
+    /// Sets how many of the best root moves later searches find a line for, at least one
+    pub(crate) fn set_multi_pv(&mut self, lines: usize) {
+        self.multi_pv = lines.max(1)
+    }
+
+    /// Sets how many threads later searches run on, at least one
+    pub(crate) fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1)
+    }
+
+    /// Returns a handle that stops the next search this searcher runs, from another thread. A stop sent before the
+    /// search starts makes it return at once, and once the search has returned the handle no longer does anything.
+    pub(crate) fn stop_handle(&self) -> StopHandle {
+        StopHandle::new(Arc::clone(&self.stop))
+    }
+
+    /// Forgets everything learned from earlier searches, e.g. before starting a new game
+    #[cfg(test)]
+    pub(crate) fn clear(&mut self) {
+        self.evaluator.clear();
+        self.transposition_table.clear();
+        self.ordering.clear();
+        self.helpers.clear()
+    }
+
+    /// Makes sure there is a helper searcher for every thread but the main one, set up to share this search
+    fn prepare_helpers(&mut self) {
+        self.helpers.truncate(self.threads - 1);
+        while self.helpers.len() < self.threads - 1 {
+            let params = self.evaluator.params().clone();
+            let helper = Self::with_transposition_table(params, Arc::clone(&self.transposition_table));
+            self.helpers.push(helper);
+        }
+        for (index, helper) in self.helpers.iter_mut().enumerate() {
+            helper.transposition_table = Arc::clone(&self.transposition_table);
+            helper.features = self.features;
+            helper.search_moves = self.search_moves.clone();
+            helper.thread_index = index + 1;
+            helper.shared = Arc::clone(&self.shared);
+        }
+    }
+
+    /// Counts a visited node, publishing the count to the other threads and checking the limits every so often.
+    /// On one thread the node limit is checked at every node, so the search stops at exactly the limit.
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        if self.shared.threads() == 1 && self.shared.node_limit().is_some_and(|limit| self.nodes >= limit) {
+            self.shared.stop();
+        }
+        if self.nodes.is_multiple_of(NODE_PUBLISH_INTERVAL) {
+            self.shared.publish_nodes(self.thread_index, self.nodes);
+            self.shared.check_limits();
+        }
+    }
+
+    /// Whether the search leaves out some of the legal root moves, so that the score at the root is not the score
+    /// of the position
+    fn root_moves_restricted(&self) -> bool {
+        !self.search_moves.is_empty() || !self.excluded_root_moves.is_empty()
+    }
+
+    /// Whether the root move `chess_move` is searched for the line being searched now
+    fn searches_root_move(&self, chess_move: &Move) -> bool {
+        (self.search_moves.is_empty() || self.search_moves.contains(chess_move))
+            && !self.excluded_root_moves.contains(chess_move)
+    }
+
+    /// The result to fall back on when the search is stopped before it completes a single depth: the first root
+    /// move it would have searched, scored by the static evaluation
+    fn unsearched_result(&mut self, game: &GameState) -> SearchResult {
+        let moves: Vec<Move> = game
+            .legal_moves()
+            .into_iter()
+            .filter(|chess_move| self.searches_root_move(chess_move))
+            .collect();
+        let score = match (moves.is_empty(), game.is_in_check()) {
+            (true, true) => -MATE_SCORE,
+            (true, false) => 0,
+            (false, _) => self.evaluator.evaluate(game),
+        };
+        SearchResult {
+            depth: 0,
+            multi_pv: 1,
+            best_move: moves.first().copied(),
+            score: SearchScore::from_search_score(score),
+            principal_variation: moves.into_iter().take(1).collect(),
+            nodes: self.shared.total_nodes(),
+        }
+    }
+
+    /// Searches `game` a depth at a time, from one until it reaches one of `limits`, calling `on_depth` with
+    /// every line of every depth, best first, as the depth completes. Each depth searches the root once per
+    /// line, leaving out the moves earlier lines start with. Returns the best line of the deepest depth
+    /// completed, or `None` if the depth limit is zero. A search stopped before it completes a depth returns a
+    /// depth zero result with the first move it would have searched.
+    ///
+    /// Helper threads only search for the best line, and only the main thread reports depths. Once the main
+    /// thread finishes, the helpers are stopped, and the deepest result any thread completed is returned, the
+    /// best scoring one if several are as deep. Node counts add up the nodes of every thread.
+    pub(crate) fn search(
+        &mut self,
+        game: &GameState,
+        limits: &SearchLimits,
+        on_depth: impl FnMut(&SearchResult),
+    ) -> Option<SearchResult> {
+        self.transposition_table.new_search();
+        self.time_manager = limits
+            .clock
+            .filter(|_| !limits.infinite)
+            .map(|clock| TimeManager::new(&clock, game.white_to_move));
+        self.shared = Arc::new(SharedSearchState::new(
+            self.threads,
+            Arc::clone(&self.stop),
+            limits,
+            self.time_manager.as_ref(),
+        ));
+        // search moves that are not legal here are dropped, and if none are left every legal move is searched
+        let legal_moves = game.legal_moves();
+        self.search_moves = limits
+            .search_moves
+            .iter()
+            .filter(|chess_move| legal_moves.contains(chess_move))
+            .copied()
+            .collect();
+        self.prepare_helpers();
+        let mut helpers = mem::take(&mut self.helpers);
+
+        let (result, helper_results) = thread::scope(|scope| {
+            let handles: Vec<_> = helpers
+                .iter_mut()
+                .map(|helper| scope.spawn(|| helper.iterative_deepening(game, limits, |_| {})))
+                .collect();
+            let result = self.iterative_deepening(game, limits, on_depth);
+            // an infinite search only returns once it is told to, however deep it has got
+            while limits.infinite && !self.shared.is_stopped() {
+                thread::sleep(STOP_POLL_INTERVAL);
+            }
+            self.shared.stop();
+            let helper_results: Vec<_> = handles
+                .into_iter()
+                .map(|handle| handle.join().expect("search threads do not panic"))
+                .collect();
+            (result, helper_results)
+        });
+        self.helpers = helpers;
+        // the search stopped itself through the flag, so later searches and their handles get a fresh one
+        self.stop = Arc::new(AtomicBool::new(false));
+
+        if limits.depth == Some(0) && !limits.infinite {
+            return None;
+        }
+        // on a tie the main thread's result comes last, and so wins
+        let mut best = helper_results
+            .into_iter()
+            .chain([result])
+            .flatten()
+            .max_by_key(|result| (result.depth, result.score))
+            .unwrap_or_else(|| self.unsearched_result(game));
+        best.nodes = self.shared.total_nodes();
+        Some(best)
+    }
+
+    /// Runs the iterative deepening of one thread, stopping once it reaches the limits or as soon as the search
+    /// is stopped, in which case the depth it was on is thrown away
+    fn iterative_deepening(
+        &mut self,
+        game: &GameState,
+        limits: &SearchLimits,
+        mut on_depth: impl FnMut(&SearchResult),
+    ) -> Option<SearchResult> {
+        self.nodes = 0;
+        self.ordering.new_search();
+        let max_depth = match limits.infinite {
+            true => MAX_PLY as u32,
+            false => limits.depth.map_or(MAX_PLY as u32, |depth| depth.min(MAX_PLY as u32)),
+        };
+        let root_moves = game
+            .legal_moves()
+            .iter()
+            .filter(|chess_move| self.searches_root_move(chess_move))
+            .count();
+        // with no legal moves there is still one line to report, scoring the mate or stalemate
+        let line_count = self.multi_pv.min(root_moves).max(1);
+        let mut result = None;
+        let mut previous_scores: Vec<i32> = Vec::new();
+        for depth in 1..=max_depth {
+            if skips_depth(self.thread_index, depth) {
+                continue;
+            }
+            let mut lines = Vec::with_capacity(line_count);
+            for line in 0..line_count {
+                let score = match previous_scores.get(line) {
+                    Some(&previous_score) if depth >= ASPIRATION_MIN_DEPTH => {
+                        self.aspiration_search(game, depth, previous_score)
+                    }
+                    _ => self.negamax(game, depth, 0, -INFINITY, INFINITY),
+                };
+                if self.shared.is_stopped() {
+                    break;
+                }
+                let principal_variation = self.complete_principal_variation(game, depth);
+                self.excluded_root_moves.extend(principal_variation.first());
+                lines.push((score, principal_variation));
+            }
+            self.excluded_root_moves.clear();
+            if self.shared.is_stopped() {
+                break;
+            }
+            // a later line can come out ahead of an earlier one once the moves ordered before it are gone
+            lines.sort_by_key(|&(score, _)| Reverse(score));
+            previous_scores = lines.iter().map(|&(score, _)| score).collect();
+
+            self.shared.publish_nodes(self.thread_index, self.nodes);
+            let nodes = self.shared.total_nodes();
+            let results: Vec<SearchResult> = lines
+                .into_iter()
+                .enumerate()
+                .map(|(index, (score, principal_variation))| SearchResult {
+                    depth,
+                    multi_pv: index + 1,
+                    best_move: principal_variation.first().copied(),
+                    score: SearchScore::from_search_score(score),
+                    principal_variation,
+                    nodes,
+                })
+                .collect();
+            results.iter().for_each(&mut on_depth);
+            result = results.into_iter().next();
+
+            let mate_found = match (result.as_ref().map(|result| result.score), limits.mate) {
+                (Some(SearchScore::Mate(moves)), Some(mate)) => moves > 0 && moves as u32 <= mate,
+                _ => false,
+            };
+            if mate_found && !limits.infinite {
+                break;
+            }
+            let best_score = previous_scores.first().copied().unwrap_or(0);
+            let best_move = result.as_ref().and_then(|result| result.best_move);
+            if self.out_of_time(game, depth, root_moves, best_move, best_score) {
+                break;
+            }
+        }
+        self.shared.publish_nodes(self.thread_index, self.nodes);
+        result
+    }
+
+    /// Whether a search on a clock should stop after completing `depth` with `best_move` scoring `best_score`.
+    /// It stops once the soft limit of the time manager is up, straight away when there is only one root move
+    /// to choose from, and early when every other root move scores well below the best one.
+    fn out_of_time(
+        &mut self,
+        game: &GameState,
+        depth: u32,
+        root_moves: usize,
+        best_move: Option<Move>,
+        best_score: i32,
+    ) -> bool {
+        let Some(time_manager) = self.time_manager.as_mut() else {
+            return false;
+        };
+        time_manager.update(best_move, best_score);
+        if root_moves <= 1 || time_manager.elapsed() >= time_manager.soft_limit() {
+            return true;
+        }
+        let easy_move_check = time_manager.wants_easy_move_check(depth) && best_score.abs() < MATE_THRESHOLD;
+        match best_move {
+            Some(best_move) if easy_move_check => self.is_clearly_best(game, depth, best_move, best_score),
+            _ => false,
+        }
+    }
+
+    /// Whether every root move other than `best_move` scores at least `EASY_MOVE_MARGIN` below `best_score`,
+    /// checked with a null window search of the other moves at half of `depth`
+    fn is_clearly_best(&mut self, game: &GameState, depth: u32, best_move: Move, best_score: i32) -> bool {
+        let threshold = best_score - EASY_MOVE_MARGIN;
+        self.excluded_root_moves.push(best_move);
+        let score = self.negamax(game, depth / 2, 0, threshold - 1, threshold);
+        self.excluded_root_moves.clear();
+        !self.shared.is_stopped() && score < threshold
+    }
+
+    /// Searches the root to `depth` in a narrow window around `previous_score`, the score of the last depth,
+    /// since a narrow window cuts off far more often. When the score falls outside the window it is only a
+    /// bound, so the window is widened on that side and the depth searched again.
+    fn aspiration_search(&mut self, game: &GameState, depth: u32, previous_score: i32) -> i32 {
+        // mate scores change by whole plies between depths, so a window around one is no use
+        if previous_score.abs() >= MATE_THRESHOLD {
+            return self.negamax(game, depth, 0, -INFINITY, INFINITY);
+        }
+        let mut window = ASPIRATION_WINDOW;
+        let mut alpha = previous_score - window;
+        let mut beta = previous_score + window;
+        loop {
+            let score = self.negamax(game, depth, 0, alpha, beta);
+            window *= 2;
+            match score {
+                _ if self.shared.is_stopped() => return score,
+                _ if score <= alpha => alpha = (score - window).max(-INFINITY),
+                _ if score >= beta => beta = (score + window).min(INFINITY),
+                _ => return score,
+            }
+        }
+    }
+
+    /// Scores `game` from the point of view of the side to move by searching `depth` more plies, with `ply`
+    /// plies already played from the root. Scores at or below `alpha` or at or above `beta` are only bounds,
+    /// since the search stops looking once it knows the score falls outside the window. The best line found is
+    /// written into the PV table.
+    fn negamax(&mut self, game: &GameState, depth: u32, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.pv_table.clear_line(ply);
+        // the score of a stopped search is thrown away, so any will do
+        if self.shared.is_stopped() {
+            return 0;
+        }
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(game, ply, alpha, beta);
+        }
+        self.count_node();
+
+        // a null window means a scout search that only needs to know which side of beta the score falls, so it
+        // is safe to be selective; a wider one may become part of the principal variation
+        let pv_node = beta - alpha > 1;
+        // a root with moves left out has a score of its own, which neither the table nor the static evaluation
+        // knows
+        let restricted_root = ply == 0 && self.root_moves_restricted();
+        let key = game.zobrist_hash();
+        let mut hash_move = None;
+        if let Some(entry) = self.transposition_table.probe(key, ply) {
+            hash_move = entry.best_move;
+            // nodes that may become part of the principal variation always search, so that the line does not
+            // stop at them, and so that the root always has a best move to report
+            if !pv_node && !restricted_root && entry.depth >= depth {
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+                if cutoff {
+                    return entry.score;
+                }
+            }
+        }
+
+        let mut moves = game.legal_moves();
+        let hash_move = hash_move.and_then(|packed_move| packed_move.find_in(&moves));
+        let in_check = game.is_in_check();
+        if moves.is_empty() {
+            return match in_check {
+                true => -MATE_SCORE + ply as i32,
+                false => 0,
+            };
+        }
+        // the fifty-move rule is only a draw once it is certain no checkmate was delivered on the last move
+        if ply > 0 && game.halfmove_clock >= 100 {
+            return 0;
+        }
+        if ply == 0 {
+            moves.retain(|chess_move| self.searches_root_move(chess_move));
+        }
+
+        let static_evaluation = match in_check {
+            true => None,
+            false => Some(self.evaluator.evaluate(game)),
+        };
+        if let Some(evaluation) = static_evaluation.filter(|_| !pv_node && !restricted_root) {
+            if self.features.reverse_futility_pruning
+                && depth <= REVERSE_FUTILITY_MAX_DEPTH
+                && beta.abs() < MATE_THRESHOLD
+                && evaluation - reverse_futility_margin(depth) >= beta
+            {
+                return evaluation;
+            }
+            // never two null moves in a row, which would only search the same position again
+            let previous_was_null = ply > 0 && self.moves_played[ply - 1].is_none();
+            if self.features.null_move_pruning
+                && depth >= NULL_MOVE_MIN_DEPTH
+                && evaluation >= beta
+                && !previous_was_null
+                && game.has_non_pawn_material()
+            {
+                self.moves_played[ply] = None;
+                let reduced_depth = depth.saturating_sub(1 + null_move_reduction(depth));
+                let null_game = game.make_null_move();
+                let score = -self.negamax(&null_game, reduced_depth, ply + 1, -beta, -beta + 1);
+                if self.shared.is_stopped() {
+                    return 0;
+                }
+                // a mate found after passing is not a real mate, so only the bound is trusted
+                if score >= beta {
+                    return match score >= MATE_THRESHOLD {
+                        true => beta,
+                        false => score,
+                    };
+                }
+            }
+        }
+        // quiet moves that cannot lift the score up to alpha are not worth searching near the horizon
+        let futile = self.features.futility_pruning
+            && !pv_node
+            && depth <= FUTILITY_MAX_DEPTH
+            && alpha.abs() < MATE_THRESHOLD
+            && static_evaluation.is_some_and(|evaluation| evaluation + futility_margin(depth) <= alpha);
+        let late_move_pruning = self.features.late_move_pruning && !pv_node && !in_check && depth <= FUTILITY_MAX_DEPTH;
+
+        let previous_move = ply
+            .checked_sub(1)
+            .and_then(|previous_ply| self.moves_played[previous_ply]);
+        let params = self.evaluator.params();
+        let picker = MovePicker::new(moves, |chess_move| {
+            self.ordering
+                .score_move(game, chess_move, hash_move, ply, previous_move, params)
+        });
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        let mut quiets_tried = Vec::new();
+        let mut moves_searched = 0;
+        for (move_index, chess_move) in picker.enumerate() {
+            let next = game.make_move(&chess_move);
+            let quiet = is_quiet(&chess_move);
+            // moves that give check are never pruned or reduced, and nothing is pruned until a move has been
+            // searched, so that pruning every move is not mistaken for being mated
+            let prunable = quiet && best_score > -MATE_THRESHOLD && !next.is_in_check();
+            let late = late_move_pruning && quiets_tried.len() >= late_move_pruning_count(depth);
+            if prunable && (futile || late) {
+                continue;
+            }
+
+            self.moves_played[ply] = Some(PreviousMove::new(game, &chess_move));
+            let reducible = self.features.late_move_reductions
+                && prunable
+                && !in_check
+                && depth >= LATE_MOVE_REDUCTION_MIN_DEPTH
+                && move_index >= LATE_MOVE_REDUCTION_MIN_INDEX;
+            let reduction = match reducible {
+                true => late_move_reduction(depth, move_index, self.ordering.history(game, &chess_move)),
+                false => 0,
+            };
+            // the first move is expected to be the best, so the rest are only searched with a null window to
+            // prove they are worse. One that is not, or a reduced one that is better than it looked, is searched
+            // again at full depth and, at a principal variation node, with the full window.
+            let score = match moves_searched {
+                0 => -self.negamax(&next, depth - 1, ply + 1, -beta, -alpha),
+                _ => {
+                    let scout_depth = depth - 1 - reduction;
+                    let mut score = -self.negamax(&next, scout_depth, ply + 1, -alpha - 1, -alpha);
+                    if score > alpha && reduction > 0 {
+                        score = -self.negamax(&next, depth - 1, ply + 1, -alpha - 1, -alpha);
+                    }
+                    if score > alpha && score < beta {
+                        score = -self.negamax(&next, depth - 1, ply + 1, -beta, -alpha);
+                    }
+                    score
+                }
+            };
+            // a stopped child's score is made up, so nothing may be learned from it
+            if self.shared.is_stopped() {
+                return 0;
+            }
+            moves_searched += 1;
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    alpha = score;
+                    best_move = Some(chess_move);
+                    self.pv_table.update(ply, chess_move);
+                }
+            }
+            if alpha >= beta {
+                if quiet {
+                    self.ordering
+                        .record_cutoff(game, &chess_move, ply, depth, previous_move, &quiets_tried);
+                }
+                break;
+            }
+            if quiet {
+                quiets_tried.push(chess_move);
+            }
+        }
+
+        let bound = match best_score {
+            _ if best_score >= beta => Bound::Lower,
+            _ if best_score > original_alpha => Bound::Exact,
+            _ => Bound::Upper,
+        };
+        if !restricted_root {
+            self.transposition_table
+                .store(key, best_move, depth, bound, best_score, ply);
+        }
+        best_score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod search {
+        use std::{
+            thread,
+            time::{Duration, Instant},
+        };
+
+        use crate::{
+            chess_state::game_state::GameState,
+            search_engine::{
+                limits::SearchLimits, score::SearchScore, selectivity::SearchFeatures, time_manager::Clock,
+                SearchResult, Searcher,
+            },
+        };
+
+        fn search(fen: &str, depth: u32) -> (GameState, SearchResult) {
+            let game = GameState::from_fen(fen).expect("valid fen");
+            let result = Searcher::new()
+                .search(&game, &SearchLimits::with_depth(depth), |_| {})
+                .expect("searched at least one depth");
+            (game, result)
+        }
+
+        #[test]
+        fn finds_mate_in_one() {
+            // act
+            let (game, output) = search("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", 3);
+            // assert
+            let best_move = output.best_move.expect("has legal moves");
+            assert_eq!(game.move_to_uci(&best_move), "a1a8");
+            assert_eq!(output.score, SearchScore::Mate(1))
+        }
+
+        #[test]
+        fn reports_negative_mate_when_side_to_move_is_getting_mated() {
+            // act
+            // black's only move is Kh7, and Rh1 mates
+            let (_, output) = search("7k/5K2/8/8/8/8/8/6R1 b - - 0 1", 4);
+            // assert
+            assert_eq!(output.score, SearchScore::Mate(-1));
+            assert_eq!(output.principal_variation.len(), 2)
+        }
+
+        #[test]
+        fn wins_hanging_queen() {
+            // act
+            let (game, output) = search("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 2);
+            // assert
+            let best_move = output.best_move.expect("has legal moves");
+            assert_eq!(game.move_to_uci(&best_move), "d2d5");
+            assert!(matches!(output.score, SearchScore::Centipawns(score) if score > 300))
+        }
+
+        #[test]
+        fn looks_past_the_horizon_for_recaptures() {
+            // act
+            // a one ply search would take the pawn if it did not see the pawn on c6 take back
+            let (game, output) = search("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1", 1);
+            // assert
+            let best_move = output.best_move.expect("has legal moves");
+            assert_ne!(game.move_to_uci(&best_move), "d2d5");
+            assert!(matches!(output.score, SearchScore::Centipawns(score) if score > 0))
+        }
+
+        #[test]
+        fn reports_every_depth_with_a_principal_variation_as_long_as_the_depth() {
+            // arrange
+            let game = GameState::default();
+            let mut results = Vec::new();
+            // act
+            let output = Searcher::new().search(&game, &SearchLimits::with_depth(3), |result| {
+                results.push(result.clone())
+            });
+            // assert
+            assert_eq!(
+                results.iter().map(|result| result.depth).collect::<Vec<_>>(),
+                vec![1, 2, 3]
+            );
+            for result in &results {
+                assert_eq!(result.principal_variation.len(), result.depth as usize);
+                assert_eq!(result.best_move, result.principal_variation.first().copied());
+            }
+            assert!(results.windows(2).all(|pair| pair[0].nodes < pair[1].nodes));
+            assert_eq!(output.as_ref(), results.last())
+        }
+
+        #[test]
+        fn reports_a_legal_principal_variation_at_every_depth() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let mut results = Vec::new();
+            // act
+            Searcher::new().search(&game, &SearchLimits::with_depth(5), |result| {
+                results.push(result.clone())
+            });
+            // assert
+            for result in results {
+                assert_eq!(result.principal_variation.len(), result.depth as usize);
+                result.principal_variation.iter().fold(game, |position, chess_move| {
+                    assert!(position.legal_moves().contains(chess_move));
+                    position.make_move(chess_move)
+                });
+            }
+        }
+
+        #[test]
+        fn reports_the_whole_mating_line() {
+            // act
+            // the king steps up to leave black one move, and the rook mates
+            let (game, output) = search("k7/8/2K5/8/8/8/8/6R1 w - - 0 1", 5);
+            // assert
+            assert_eq!(output.score, SearchScore::Mate(2));
+            assert_eq!(output.principal_variation.len(), 3);
+            let mated = output
+                .principal_variation
+                .iter()
+                .fold(game, |position, chess_move| position.make_move(chess_move));
+            assert!(mated.is_in_check() && mated.legal_moves().is_empty())
+        }
+
+        #[test]
+        fn reports_each_depth_with_one_line_per_root_move_asked_for() {
+            // arrange
+            let game = GameState::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid fen");
+            let mut searcher = Searcher::new();
+            searcher.set_multi_pv(3);
+            let mut results = Vec::new();
+            // act
+            let output = searcher.search(&game, &SearchLimits::with_depth(3), |result| {
+                results.push(result.clone())
+            });
+            // assert
+            assert_eq!(results.len(), 9);
+            for (depth, lines) in (1..).zip(results.chunks(3)) {
+                assert!(lines.iter().all(|line| line.depth == depth));
+                assert_eq!(
+                    lines.iter().map(|line| line.multi_pv).collect::<Vec<_>>(),
+                    vec![1, 2, 3]
+                );
+                assert!(lines[0].best_move != lines[1].best_move && lines[1].best_move != lines[2].best_move);
+                assert!(lines[0].best_move != lines[2].best_move);
+                assert!(lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+            }
+            let best_move = results[6].best_move.expect("has legal moves");
+            assert_eq!(game.move_to_uci(&best_move), "d2d5");
+            assert_eq!(output.as_ref(), results.get(6))
+        }
+
+        #[test]
+        fn reports_only_as_many_lines_as_there_are_legal_moves() {
+            // arrange
+            // black's only move is Kh7
+            let game = GameState::from_fen("7k/5K2/8/8/8/8/8/6R1 b - - 0 1").expect("valid fen");
+            let mut searcher = Searcher::new();
+            searcher.set_multi_pv(4);
+            let mut results = Vec::new();
+            // act
+            searcher.search(&game, &SearchLimits::with_depth(2), |result| {
+                results.push(result.clone())
+            });
+            // assert
+            assert_eq!(
+                results.iter().map(|result| result.multi_pv).collect::<Vec<_>>(),
+                vec![1, 1]
+            )
+        }
+
+        #[test]
+        fn finds_the_same_tactics_on_several_threads() {
+            // arrange
+            let mut searcher = Searcher::new();
+            searcher.set_threads(4);
+            let mate = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").expect("valid fen");
+            let queen = GameState::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid fen");
+            // act
+            let mate_result = searcher
+                .search(&mate, &SearchLimits::with_depth(4), |_| {})
+                .expect("searched at least one depth");
+            let queen_result = searcher
+                .search(&queen, &SearchLimits::with_depth(4), |_| {})
+                .expect("searched at least one depth");
+            // assert
+            assert_eq!((mate_result.depth, mate_result.score), (4, SearchScore::Mate(1)));
+            let best_move = queen_result.best_move.expect("has legal moves");
+            assert_eq!(queen.move_to_uci(&best_move), "d2d5")
+        }
+
+        #[test]
+        fn adds_up_nodes_of_every_thread() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let mut searcher = Searcher::new();
+            searcher.set_threads(3);
+            let mut main_thread_nodes = 0;
+            // act
+            let output = searcher
+                .search(&game, &SearchLimits::with_depth(4), |result| {
+                    main_thread_nodes = result.nodes
+                })
+                .expect("searched at least one depth");
+            // assert
+            assert!(output.nodes >= main_thread_nodes);
+            assert_eq!(output.principal_variation.len(), 4);
+            output.principal_variation.iter().fold(game, |position, chess_move| {
+                assert!(position.legal_moves().contains(chess_move));
+                position.make_move(chess_move)
+            });
+        }
+
+        #[test]
+        fn stops_at_exactly_the_node_limit_with_the_same_result_every_time() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let limits = SearchLimits {
+                nodes: Some(5000),
+                ..SearchLimits::default()
+            };
+            // act
+            let first = Searcher::new().search(&game, &limits, |_| {}).expect("searched");
+            let second = Searcher::new().search(&game, &limits, |_| {}).expect("searched");
+            // assert
+            assert_eq!(first.nodes, 5000);
+            assert_eq!(first, second)
+        }
+
+        #[test]
+        fn learns_nothing_from_a_depth_cut_short_by_the_node_limit() {
+            // arrange
+            let game = GameState::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid fen");
+            let fresh = Searcher::new()
+                .search(&game, &SearchLimits::with_depth(5), |_| {})
+                .expect("searched");
+            for nodes in [450, 600, 800] {
+                let mut searcher = Searcher::new();
+                let limits = SearchLimits {
+                    nodes: Some(nodes),
+                    ..SearchLimits::default()
+                };
+                searcher.search(&game, &limits, |_| {});
+                // act
+                let output = searcher
+                    .search(&game, &SearchLimits::with_depth(5), |_| {})
+                    .expect("searched");
+                // assert
+                assert_eq!((output.best_move, output.score), (fresh.best_move, fresh.score));
+            }
+        }
+
+        #[test]
+        fn falls_back_to_a_move_when_stopped_before_the_first_depth() {
+            // arrange
+            let game = GameState::default();
+            let limits = SearchLimits {
+                nodes: Some(1),
+                ..SearchLimits::default()
+            };
+            // act
+            let output = Searcher::new().search(&game, &limits, |_| {}).expect("searched");
+            // assert
+            assert_eq!((output.depth, output.nodes), (0, 1));
+            assert!(output
+                .best_move
+                .is_some_and(|best_move| game.legal_moves().contains(&best_move)))
+        }
+
+        #[test]
+        fn stops_once_the_move_time_is_up() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let limits = SearchLimits {
+                move_time: Some(Duration::from_millis(50)),
+                ..SearchLimits::default()
+            };
+            let start = Instant::now();
+            // act
+            let output = Searcher::new().search(&game, &limits, |_| {}).expect("searched");
+            // assert
+            assert!(start.elapsed() < Duration::from_secs(5));
+            assert!(output.best_move.is_some())
+        }
+
+        #[test]
+        fn searches_until_stopped_when_infinite() {
+            // arrange
+            // a position so simple that every depth is over almost at once
+            let game = GameState::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1").expect("valid fen");
+            let mut searcher = Searcher::new();
+            let stop_handle = searcher.stop_handle();
+            let limits = SearchLimits {
+                depth: Some(1),
+                infinite: true,
+                ..SearchLimits::default()
+            };
+            let start = Instant::now();
+            // act
+            let output = thread::scope(|scope| {
+                scope.spawn(|| {
+                    thread::sleep(Duration::from_millis(100));
+                    stop_handle.stop()
+                });
+                searcher.search(&game, &limits, |_| {}).expect("searched")
+            });
+            // assert
+            assert!(start.elapsed() >= Duration::from_millis(100));
+            assert!(output.depth > 1)
+        }
+
+        #[test]
+        fn returns_at_once_when_stopped_before_it_starts() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let mut searcher = Searcher::new();
+            searcher.stop_handle().stop();
+            // act
+            let stopped = searcher.search(&game, &SearchLimits::with_depth(20), |_| {}).expect("searched");
+            let next = searcher.search(&game, &SearchLimits::with_depth(2), |_| {}).expect("searched");
+            // assert
+            assert_eq!(stopped.depth, 0);
+            assert!(stopped.best_move.is_some());
+            assert_eq!(next.depth, 2)
+        }
+
+        #[test]
+        fn stops_once_a_short_enough_mate_is_found() {
+            // arrange
+            let game = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").expect("valid fen");
+            let limits = SearchLimits {
+                depth: Some(10),
+                mate: Some(1),
+                ..SearchLimits::default()
+            };
+            let mut depths = Vec::new();
+            // act
+            let output = Searcher::new()
+                .search(&game, &limits, |result| depths.push(result.depth))
+                .expect("searched");
+            // assert
+            assert_eq!(output.score, SearchScore::Mate(1));
+            assert_eq!(depths, vec![1])
+        }
+
+        #[test]
+        fn stops_after_the_first_depth_on_a_clock_with_only_one_legal_move() {
+            // arrange
+            // the king's only move is to take the queen giving check
+            let game = GameState::from_fen("k7/8/8/8/8/8/1q6/K7 w - - 0 1").expect("valid fen");
+            let limits = SearchLimits {
+                depth: Some(20),
+                clock: Some(Clock {
+                    white_time: Duration::from_secs(60),
+                    black_time: Duration::from_secs(60),
+                    ..Clock::default()
+                }),
+                ..SearchLimits::default()
+            };
+            // act
+            let output = Searcher::new().search(&game, &limits, |_| {}).expect("searched");
+            // assert
+            assert_eq!(game.legal_moves().len(), 1);
+            assert_eq!(output.depth, 1);
+            assert_eq!(output.best_move.map(|best_move| game.move_to_uci(&best_move)), Some("a1b2".to_string()))
+        }
+
+        #[test]
+        fn stops_within_the_time_on_the_clock() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let limits = SearchLimits {
+                clock: Some(Clock {
+                    white_time: Duration::from_millis(1000),
+                    black_time: Duration::from_secs(60),
+                    ..Clock::default()
+                }),
+                ..SearchLimits::default()
+            };
+            let start = Instant::now();
+            // act
+            let output = Searcher::new().search(&game, &limits, |_| {}).expect("searched");
+            // assert
+            assert!(start.elapsed() < Duration::from_millis(1000));
+            assert!(output.best_move.is_some())
+        }
+
+        #[test]
+        fn chooses_only_between_the_search_moves() {
+            // arrange
+            let game = GameState::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid fen");
+            let search_moves = ["e1e2", "d2d3"].map(|uci| game.find_uci_move(uci).expect("legal move"));
+            let mut searcher = Searcher::new();
+            searcher.set_multi_pv(3);
+            let limits = SearchLimits {
+                depth: Some(3),
+                search_moves: search_moves.to_vec(),
+                ..SearchLimits::default()
+            };
+            let mut lines = Vec::new();
+            // act
+            let output = searcher
+                .search(&game, &limits, |result| lines.push(result.clone()))
+                .expect("searched");
+            // assert
+            assert!(search_moves.contains(&output.best_move.expect("has legal moves")));
+            assert_eq!(lines.iter().filter(|line| line.depth == 3).count(), 2)
+        }
+
+        #[test]
+        fn searches_every_move_when_no_search_move_is_legal() {
+            // arrange
+            let game = GameState::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid fen");
+            let other_game = GameState::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").expect("valid fen");
+            let illegal_move = other_game.find_uci_move("e2e4").expect("legal move");
+            let mut searcher = Searcher::new();
+            let limits = SearchLimits {
+                depth: Some(3),
+                search_moves: vec![illegal_move],
+                ..SearchLimits::default()
+            };
+            // act
+            let output = searcher.search(&game, &limits, |_| {}).expect("searched");
+            // assert
+            assert!(game.legal_moves().contains(&output.best_move.expect("has legal moves")));
+            assert!(matches!(output.score, SearchScore::Centipawns(_)))
+        }
+
+        #[test]
+        fn reuses_transposition_table_until_cleared() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let mut searcher = Searcher::new();
+            let first = searcher
+                .search(&game, &SearchLimits::with_depth(3), |_| {})
+                .expect("searched at least one depth");
+            // act
+            let second = searcher
+                .search(&game, &SearchLimits::with_depth(3), |_| {})
+                .expect("searched at least one depth");
+            searcher.clear();
+            let cleared = searcher
+                .search(&game, &SearchLimits::with_depth(3), |_| {})
+                .expect("searched at least one depth");
+            // assert
+            assert!(second.nodes < first.nodes);
+            assert_eq!(second.best_move, first.best_move);
+            assert_eq!(cleared, first)
+        }
+
+        #[test]
+        fn visits_fewer_nodes_with_selective_techniques_than_without() {
+            // arrange
+            let game = GameState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .expect("valid fen");
+            let mut plain = Searcher::new();
+            plain.set_features(SearchFeatures::NONE);
+            // act
+            let selective = Searcher::new()
+                .search(&game, &SearchLimits::with_depth(4), |_| {})
+                .expect("searched at least one depth");
+            let plain = plain
+                .search(&game, &SearchLimits::with_depth(4), |_| {})
+                .expect("searched at least one depth");
+            // assert
+            assert!(selective.nodes < plain.nodes)
+        }
+
+        #[test]
+        fn finds_tactics_with_each_selective_technique_switched_on_alone() {
+            // arrange
+            let only = [
+                SearchFeatures {
+                    null_move_pruning: true,
+                    ..SearchFeatures::NONE
+                },
+                SearchFeatures {
+                    late_move_reductions: true,
+                    ..SearchFeatures::NONE
+                },
+                SearchFeatures {
+                    futility_pruning: true,
+                    ..SearchFeatures::NONE
+                },
+                SearchFeatures {
+                    reverse_futility_pruning: true,
+                    ..SearchFeatures::NONE
+                },
+                SearchFeatures {
+                    late_move_pruning: true,
+                    ..SearchFeatures::NONE
+                },
+                SearchFeatures::default(),
+            ];
+            let mate = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").expect("valid fen");
+            let queen = GameState::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid fen");
+            for features in only {
+                let mut searcher = Searcher::new();
+                searcher.set_features(features);
+                // act
+                let mate_result = searcher
+                    .search(&mate, &SearchLimits::with_depth(4), |_| {})
+                    .expect("searched at least one depth");
+                let queen_result = searcher
+                    .search(&queen, &SearchLimits::with_depth(4), |_| {})
+                    .expect("searched at least one depth");
+                // assert
+                assert_eq!(mate_result.score, SearchScore::Mate(1));
+                let best_move = queen_result.best_move.expect("has legal moves");
+                assert_eq!(queen.move_to_uci(&best_move), "d2d5")
+            }
+        }
+
+        #[test]
+        fn scores_zero_with_no_move_when_stalemated() {
+            // act
+            let (_, output) = search("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 2);
+            // assert
+            assert_eq!(output.best_move, None);
+            assert_eq!(output.score, SearchScore::Centipawns(0))
+        }
+    }
+
+    mod is_clearly_best {
+        use std::sync::Arc;
+
+        use crate::{
+            chess_state::game_state::GameState,
+            search_engine::{lazy_smp::SharedSearchState, limits::SearchLimits, score::SearchScore, Searcher},
+        };
+
+        fn is_clearly_best(fen: &str) -> bool {
+            let game = GameState::from_fen(fen).expect("valid fen");
+            let mut searcher = Searcher::new();
+            let limits = SearchLimits::with_depth(6);
+            let result = searcher.search(&game, &limits, |_| {}).expect("searched");
+            let best_move = result.best_move.expect("has legal moves");
+            let SearchScore::Centipawns(score) = result.score else {
+                panic!("no mate in the position")
+            };
+            // a finished search leaves itself stopped
+            searcher.shared = Arc::new(SharedSearchState::new(1, Arc::clone(&searcher.stop), &limits, None));
+            searcher.is_clearly_best(&game, 6, best_move, score)
+        }
+
+        #[test]
+        fn is_true_only_when_every_other_move_is_far_worse() {
+            // act + assert
+            assert!(is_clearly_best("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1"));
+            assert!(!is_clearly_best("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"))
+        }
+    }
+
+    mod set_hash_size {
+        use crate::{
+            chess_state::game_state::GameState,
+            search_engine::{
+                limits::SearchLimits, transposition_table::DEFAULT_TRANSPOSITION_TABLE_MEGABYTES, Searcher,
+            },
+        };
+
+        #[test]
+        fn resizes_the_transposition_table_the_next_search_uses() {
+            // arrange
+            let game = GameState::default();
+            let mut searcher = Searcher::new();
+            let default_capacity = searcher.transposition_table.capacity();
+            // act
+            searcher.set_hash_size(DEFAULT_TRANSPOSITION_TABLE_MEGABYTES * 2);
+            let output = searcher.search(&game, &SearchLimits::with_depth(2), |_| {});
+            // assert
+            assert_eq!(searcher.transposition_table.capacity(), default_capacity * 2);
+            assert!(output.is_some_and(|result| result.best_move.is_some()))
+        }
+    }
+
+    mod aspiration_search {
+        use crate::{
+            chess_state::game_state::GameState,
+            search_engine::{score::INFINITY, selectivity::SearchFeatures, Searcher},
+        };
+
+        fn plain_searcher() -> Searcher {
+            let mut searcher = Searcher::new();
+            searcher.set_features(SearchFeatures::NONE);
+            searcher
+        }
+
+        #[test]
+        fn widens_window_until_score_falls_inside_it() {
+            // arrange
+            // pruning can make the score depend on the window, so selective techniques are switched off and there
+            // is nothing to capture
+            let game = GameState::from_fen("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1").expect("valid fen");
+            let expected = plain_searcher().negamax(&game, 4, 0, -INFINITY, INFINITY);
+            // act + assert
+            // windows far below and far above the true score both have to widen to reach it
+            assert_eq!(plain_searcher().aspiration_search(&game, 4, expected - 1000), expected);
+            assert_eq!(plain_searcher().aspiration_search(&game, 4, expected + 1000), expected)
+        }
+    }
+}
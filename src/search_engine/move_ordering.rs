@@ -0,0 +1,343 @@
+use crate::{
+    chess_state::{
+        chess_pieces::PieceEnum,
+        coordinates::YCoordinate,
+        game_state::GameState,
+        moves::{calculate_moves::king_moves::get_castling_destination_files, shared::Move},
+    },
+    evaluation_engine::params::EvalParams,
+    search_engine::{
+        score::MAX_PLY,
+        see::{exchange_value, static_exchange_evaluation},
+    },
+};
+
+/// Move scores for each stage of the ordering, far enough apart that the scores within a stage never overlap
+/// the next. Quiet moves without a killer or countermove slot fall in between on their history score.
+const HASH_MOVE_SCORE: i32 = 30_000_000;
+const GOOD_CAPTURE_SCORE: i32 = 20_000_000;
+const QUEEN_PROMOTION_SCORE: i32 = 19_000_000;
+const FIRST_KILLER_SCORE: i32 = 18_000_000;
+const SECOND_KILLER_SCORE: i32 = 17_000_000;
+const COUNTERMOVE_SCORE: i32 = 16_000_000;
+const UNDER_PROMOTION_SCORE: i32 = -19_000_000;
+const BAD_CAPTURE_SCORE: i32 = -20_000_000;
+
+/// History scores stay within plus or minus this, however often a move cuts off
+pub(crate) const MAX_HISTORY: i32 = 16_384;
+
+/// The squares a move starts and ends on, as bit indices. Castling counts as the king's move.
+fn move_squares(game: &GameState, chess_move: &Move) -> (usize, usize) {
+    match chess_move {
+        Move::StandardMove(standard_move) => (
+            standard_move.start_position.to_bitmask().trailing_zeros() as usize,
+            standard_move.end_position.to_bitmask().trailing_zeros() as usize,
+        ),
+        Move::Castle(castle_type) => {
+            let (king, back_rank) = match game.white_to_move {
+                true => (game.board.white_kings.mask, YCoordinate::One as u64),
+                false => (game.board.black_kings.mask, YCoordinate::Eight as u64),
+            };
+            let (king_file, _) = get_castling_destination_files(*castle_type);
+            (
+                (king & back_rank).trailing_zeros() as usize,
+                (king_file as u64 & back_rank).trailing_zeros() as usize,
+            )
+        }
+    }
+}
+
+/// The piece that makes a move. Castling counts as the king's move.
+fn moving_piece(white: bool, chess_move: &Move) -> PieceEnum {
+    match (chess_move, white) {
+        (Move::StandardMove(standard_move), _) => standard_move.piece,
+        (Move::Castle(_), true) => PieceEnum::WhiteKing,
+        (Move::Castle(_), false) => PieceEnum::BlackKing,
+    }
+}
+
+/// Checks if a move neither captures nor promotes
+pub(crate) fn is_quiet(chess_move: &Move) -> bool {
+    match chess_move {
+        Move::StandardMove(standard_move) => standard_move.takes.is_none() && standard_move.promotion.is_none(),
+        Move::Castle(_) => true,
+    }
+}
+
+/// Orders captures by most valuable victim first, then by least valuable attacker
+fn most_valuable_victim_least_valuable_attacker(victim: PieceEnum, attacker: PieceEnum, params: &EvalParams) -> i32 {
+    exchange_value(victim, params) * 16 - exchange_value(attacker, params).min(1_000) / 8
+}
+
+/// The move before the one being searched, which the countermove table is indexed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PreviousMove {
+    piece: PieceEnum,
+    end_square: usize,
+}
+
+impl PreviousMove {
+    /// Records `chess_move`, played in `game`, as the move to be answered
+    pub(crate) fn new(game: &GameState, chess_move: &Move) -> Self {
+        Self {
+            piece: moving_piece(game.white_to_move, chess_move),
+            end_square: move_squares(game, chess_move).1,
+        }
+    }
+}
+
+/// What the search has learned about which quiet moves tend to be good, used to try the likeliest moves first
+/// so that alpha-beta cuts off sooner:
+/// * killer moves, the last two quiet moves to cut off at each ply,
+/// * countermoves, the last quiet move to cut off in reply to each piece landing on each square,
+/// * butterfly history, how often each quiet move from one square to another has cut off for each side,
+///   weighted towards deeper searches.
+#[derive(Debug, Clone)]
+pub(crate) struct MoveOrdering {
+    killers: Vec<[Option<Move>; 2]>,
+    countermoves: Vec<[Option<Move>; 64]>,
+    history: Vec<[[i32; 64]; 64]>,
+}
+
+impl MoveOrdering {
+    pub(crate) fn new() -> Self {
+        Self {
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            countermoves: vec![[None; 64]; PieceEnum::ALL.len()],
+            history: vec![[[0; 64]; 64]; 2],
+        }
+    }
+
+    /// Forgets everything learned, e.g. before starting a new game
+    #[cfg(test)]
+    pub(crate) fn clear(&mut self) {
+        *self = Self::new()
+    }
+
+    /// Prepares for a new search. Killers belong to the positions of the last search and are dropped, while
+    /// history is halved, so it still guides the new search but soon gives way to what the new search learns.
+    pub(crate) fn new_search(&mut self) {
+        self.killers.iter_mut().for_each(|killers| *killers = [None; 2]);
+        for side in self.history.iter_mut() {
+            side.iter_mut().flatten().for_each(|score| *score /= 2);
+        }
+    }
+
+    pub(crate) fn history(&self, game: &GameState, chess_move: &Move) -> i32 {
+        let (start, end) = move_squares(game, chess_move);
+        self.history[game.white_to_move as usize][start][end]
+    }
+
+    #[cfg(test)]
+    pub(crate) fn killers(&self, ply: usize) -> [Option<Move>; 2] {
+        self.killers[ply]
+    }
+
+    pub(crate) fn countermove(&self, previous_move: Option<PreviousMove>) -> Option<Move> {
+        previous_move.and_then(|previous| self.countermoves[previous.piece as usize][previous.end_square])
+    }
+
+    /// Nudges the history score of a move towards `MAX_HISTORY` (or its negative, for a negative `bonus`), by
+    /// less the closer it already is
+    fn update_history(&mut self, game: &GameState, chess_move: &Move, bonus: i32) {
+        let (start, end) = move_squares(game, chess_move);
+        let score = &mut self.history[game.white_to_move as usize][start][end];
+        *score += bonus - *score * bonus.abs() / MAX_HISTORY;
+    }
+
+    /// Learns from a quiet move that caused a beta cutoff at `ply` with `depth` plies left, rewarding it and
+    /// penalising the quiet moves tried before it that did not cut off
+    pub(crate) fn record_cutoff(
+        &mut self,
+        game: &GameState,
+        chess_move: &Move,
+        ply: usize,
+        depth: u32,
+        previous_move: Option<PreviousMove>,
+        quiets_tried: &[Move],
+    ) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(*chess_move) {
+            killers[1] = killers[0];
+            killers[0] = Some(*chess_move);
+        }
+        if let Some(previous) = previous_move {
+            self.countermoves[previous.piece as usize][previous.end_square] = Some(*chess_move);
+        }
+
+        let bonus = (depth * depth).min(MAX_HISTORY as u32) as i32;
+        self.update_history(game, chess_move, bonus);
+        for tried in quiets_tried.iter().filter(|&tried| tried != chess_move) {
+            self.update_history(game, tried, -bonus);
+        }
+    }
+
+    /// Scores a move for ordering: the hash move first, then winning and even captures by MVV-LVA, queen
+    /// promotions, killers, the countermove, the other quiet moves by history, and last of all under-promotions
+    /// and captures that lose material by static exchange evaluation
+    pub(crate) fn score_move(
+        &self,
+        game: &GameState,
+        chess_move: &Move,
+        hash_move: Option<Move>,
+        ply: usize,
+        previous_move: Option<PreviousMove>,
+        params: &EvalParams,
+    ) -> i32 {
+        if hash_move == Some(*chess_move) {
+            return HASH_MOVE_SCORE;
+        }
+        if let Move::StandardMove(standard_move) = chess_move {
+            if let Some((_, victim)) = standard_move.takes {
+                let order = most_valuable_victim_least_valuable_attacker(victim, standard_move.piece, params);
+                // a capture with a piece worth no more than its victim can never lose material
+                let winning = exchange_value(standard_move.piece, params) <= exchange_value(victim, params)
+                    || static_exchange_evaluation(&game.board, chess_move, params) >= 0;
+                return match winning {
+                    true => GOOD_CAPTURE_SCORE + order,
+                    false => BAD_CAPTURE_SCORE + order,
+                };
+            }
+            if let Some(promotion) = standard_move.promotion {
+                return match promotion {
+                    PieceEnum::WhiteQueen | PieceEnum::BlackQueen => QUEEN_PROMOTION_SCORE,
+                    _ => UNDER_PROMOTION_SCORE + exchange_value(promotion, params),
+                };
+            }
+        }
+
+        let [first_killer, second_killer] = self.killers[ply];
+        match Some(*chess_move) {
+            killer if killer == first_killer => FIRST_KILLER_SCORE,
+            killer if killer == second_killer => SECOND_KILLER_SCORE,
+            countermove if countermove == self.countermove(previous_move) => COUNTERMOVE_SCORE,
+            _ => self.history(game, chess_move),
+        }
+    }
+}
+
+/// Hands out moves from the highest score to the lowest, picking each one only when it is asked for, since a
+/// cutoff usually comes before most of the list is needed
+#[derive(Debug, Clone)]
+pub(crate) struct MovePicker {
+    moves: Vec<(Move, i32)>,
+    next: usize,
+}
+
+impl MovePicker {
+    pub(crate) fn new(moves: Vec<Move>, score: impl Fn(&Move) -> i32) -> Self {
+        Self {
+            moves: moves
+                .into_iter()
+                .map(|chess_move| {
+                    let move_score = score(&chess_move);
+                    (chess_move, move_score)
+                })
+                .collect(),
+            next: 0,
+        }
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.moves.get(self.next..)?;
+        let best = (0..remaining.len()).max_by_key(|&index| remaining[index].1)?;
+        self.moves.swap(self.next, self.next + best);
+        self.next += 1;
+        Some(self.moves[self.next - 1].0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod move_ordering {
+        use crate::{
+            chess_state::{game_state::GameState, moves::shared::Move},
+            evaluation_engine::params::DEFAULT_EVAL_PARAMS,
+            search_engine::move_ordering::{MoveOrdering, MovePicker, PreviousMove, MAX_HISTORY},
+        };
+
+        // white can take the queen with the queen (and be taken back), a rook with a pawn, an undefended knight with
+        // the rook, or a defended pawn with the queen, and can promote a pawn
+        const FEN: &str = "3q2k1/1P6/4n1p1/2r4p/1P6/4R3/8/3Q2K1 w - - 0 1";
+
+        fn find(game: &GameState, uci: &str) -> Move {
+            game.find_uci_move(uci).expect("legal move")
+        }
+
+        fn order(game: &GameState, ordering: &MoveOrdering, hash_move: Option<Move>) -> Vec<String> {
+            let picker = MovePicker::new(game.legal_moves(), |chess_move| {
+                ordering.score_move(game, chess_move, hash_move, 0, None, &DEFAULT_EVAL_PARAMS)
+            });
+            picker.map(|chess_move| game.move_to_uci(&chess_move)).collect()
+        }
+
+        #[test]
+        fn orders_hash_move_then_captures_then_promotions_then_losing_captures_last() {
+            // arrange
+            let game = GameState::from_fen(FEN).expect("valid fen");
+            let ordering = MoveOrdering::new();
+            // act
+            let output = order(&game, &ordering, Some(find(&game, "g1h2")));
+            // assert
+            assert_eq!(output[..5], ["g1h2", "d1d8", "b4c5", "e3e6", "b7b8q"]);
+            assert_eq!(output[output.len() - 2..], ["b7b8n", "d1h5"]);
+            assert_eq!(output.len(), game.legal_moves().len())
+        }
+
+        #[test]
+        fn orders_killers_and_countermove_before_other_quiet_moves() {
+            // arrange
+            let game = GameState::default();
+            let previous = PreviousMove::new(&game, &find(&game, "g1f3"));
+            let mut ordering = MoveOrdering::new();
+            for uci in ["b1c3", "d2d4", "e2e4"] {
+                ordering.record_cutoff(&game, &find(&game, uci), 0, 1, None, &[]);
+            }
+            ordering.record_cutoff(&game, &find(&game, "a2a3"), 1, 1, Some(previous), &[]);
+            // act
+            let picker = MovePicker::new(game.legal_moves(), |chess_move| {
+                ordering.score_move(&game, chess_move, None, 0, Some(previous), &DEFAULT_EVAL_PARAMS)
+            });
+            let output: Vec<String> = picker.map(|chess_move| game.move_to_uci(&chess_move)).collect();
+            // assert
+            assert_eq!(output[..4], ["e2e4", "d2d4", "a2a3", "b1c3"])
+        }
+
+        #[test]
+        fn rewards_cutoffs_and_penalises_moves_tried_before_them_within_bounds() {
+            // arrange
+            let game = GameState::default();
+            let (cutoff, tried) = (find(&game, "e2e4"), find(&game, "a2a3"));
+            let mut ordering = MoveOrdering::new();
+            // act
+            for _ in 0..1_000 {
+                ordering.record_cutoff(&game, &cutoff, 3, 20, None, &[tried, cutoff]);
+            }
+            // assert
+            assert!(ordering.history(&game, &cutoff) > MAX_HISTORY / 2);
+            assert!(ordering.history(&game, &cutoff) <= MAX_HISTORY);
+            assert!(ordering.history(&game, &tried) < -MAX_HISTORY / 2);
+            assert!(ordering.history(&game, &tried) >= -MAX_HISTORY);
+            assert_eq!(ordering.killers(3), [Some(cutoff), None])
+        }
+
+        #[test]
+        fn halves_history_and_drops_killers_for_a_new_search() {
+            // arrange
+            let game = GameState::default();
+            let chess_move = find(&game, "d2d4");
+            let mut ordering = MoveOrdering::new();
+            ordering.record_cutoff(&game, &chess_move, 0, 10, None, &[]);
+            let before = ordering.history(&game, &chess_move);
+            // act
+            ordering.new_search();
+            // assert
+            assert_eq!(ordering.history(&game, &chess_move), before / 2);
+            assert_eq!(ordering.killers(0), [None, None])
+        }
+    }
+}
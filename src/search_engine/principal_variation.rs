@@ -0,0 +1,88 @@
+use crate::{
+    chess_state::{game_state::GameState, moves::shared::Move},
+    search_engine::{score::MAX_PLY, Searcher},
+};
+
+/// The best line found from every ply of the line being searched. A node builds its line from its best move
+/// and the line its child left behind, so once the root finishes it holds the whole principal variation.
+#[derive(Debug, Clone)]
+pub(crate) struct PvTable {
+    lines: Vec<Vec<Move>>,
+}
+
+impl PvTable {
+    pub(crate) fn new() -> Self {
+        Self {
+            lines: (0..=MAX_PLY).map(|_| Vec::with_capacity(MAX_PLY)).collect(),
+        }
+    }
+
+    /// Forgets the line from `ply`, for when a node starts searching or returns without one
+    pub(crate) fn clear_line(&mut self, ply: usize) {
+        self.lines[ply].clear()
+    }
+
+    /// Makes the line from `ply` start with `chess_move` and carry on with the line the next ply left behind
+    pub(crate) fn update(&mut self, ply: usize, chess_move: Move) {
+        let (parents, children) = self.lines.split_at_mut(ply + 1);
+        let line = &mut parents[ply];
+        line.clear();
+        line.push(chess_move);
+        line.extend_from_slice(&children[0]);
+    }
+
+    pub(crate) fn line(&self, ply: usize) -> &[Move] {
+        &self.lines[ply]
+    }
+}
+
+impl Searcher {
+    /// Returns the principal variation from the root, carried on with the best moves stored in the
+    /// transposition table until it is `depth` moves long or the game ends. Lines get cut short when a node
+    /// below the root returns a stored score instead of searching, and the table still remembers how they went
+    /// on. Stored moves are only followed while they match a legal move, since a different position can share a
+    /// hash.
+    pub(super) fn complete_principal_variation(&self, game: &GameState, depth: u32) -> Vec<Move> {
+        let mut principal_variation = self.pv_table.line(0).to_vec();
+        let mut position = principal_variation
+            .iter()
+            .fold(*game, |position, chess_move| position.make_move(chess_move));
+        while principal_variation.len() < depth as usize && position.halfmove_clock < 100 {
+            let Some(chess_move) = self
+                .transposition_table
+                .probe(position.zobrist_hash(), 0)
+                .and_then(|entry| entry.best_move)
+                .and_then(|packed_move| packed_move.find_in(&position.legal_moves()))
+            else {
+                break;
+            };
+            principal_variation.push(chess_move);
+            position = position.make_move(&chess_move);
+        }
+        principal_variation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod pv_table {
+        use crate::{chess_state::game_state::GameState, search_engine::principal_variation::PvTable};
+
+        #[test]
+        fn builds_line_from_best_move_and_line_of_next_ply() {
+            // arrange
+            let game = GameState::default();
+            let find = |uci| game.find_uci_move(uci).expect("legal move");
+            let mut table = PvTable::new();
+            table.update(2, find("e2e4"));
+            // act
+            table.update(1, find("d2d4"));
+            table.update(0, find("g1f3"));
+            // assert
+            assert_eq!(table.line(0), [find("g1f3"), find("d2d4"), find("e2e4")]);
+            table.clear_line(2);
+            table.update(1, find("c2c4"));
+            assert_eq!(table.line(1), [find("c2c4")])
+        }
+    }
+}
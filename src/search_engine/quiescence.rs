@@ -0,0 +1,137 @@
+use crate::{
+    chess_state::{game_state::GameState, moves::shared::Move},
+    evaluation_engine::material::piece_value,
+    search_engine::{
+        move_ordering::MovePicker,
+        score::{INFINITY, MATE_SCORE, MAX_PLY},
+        Searcher,
+    },
+};
+
+/// How far beyond the value of the captured piece a capture may still raise the score, to allow for the
+/// positional terms the capture changes. Captures that cannot reach alpha even with this much to spare are
+/// skipped.
+const DELTA_MARGIN: i32 = 200;
+
+impl Searcher {
+    /// How much a move gains in material at most: the captured piece plus whatever a promotion adds over the
+    /// pawn it replaces
+    fn material_gain(&self, chess_move: &Move) -> i32 {
+        let params = self.evaluator.params();
+        match chess_move {
+            Move::StandardMove(standard_move) => {
+                let captured = standard_move
+                    .takes
+                    .map_or(0, |(_, piece)| piece_value(piece, params).mg);
+                let promotion = standard_move.promotion.map_or(0, |piece| {
+                    piece_value(piece, params).mg - piece_value(standard_move.piece, params).mg
+                });
+                captured + promotion
+            }
+            Move::Castle(_) => 0,
+        }
+    }
+
+    /// Searches only the captures and promotions in `game` until the position is quiet, so that the main search
+    /// never stops in the middle of an exchange and misjudges it. The side to move may "stand pat" on the static
+    /// evaluation instead of capturing, except when in check, where every evasion is searched instead and
+    /// having none is mate.
+    pub(super) fn quiescence(&mut self, game: &GameState, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        if self.shared.is_stopped() {
+            return 0;
+        }
+        self.count_node();
+        if ply >= MAX_PLY {
+            return self.evaluator.evaluate(game);
+        }
+
+        let in_check = game.is_in_check();
+        let (moves, stand_pat) = match in_check {
+            true => (game.legal_moves(), None),
+            false => {
+                let stand_pat = self.evaluator.evaluate(game);
+                if stand_pat >= beta {
+                    return stand_pat;
+                }
+                alpha = alpha.max(stand_pat);
+                (game.legal_captures(), Some(stand_pat))
+            }
+        };
+        if in_check && moves.is_empty() {
+            return -MATE_SCORE + ply as i32;
+        }
+
+        let params = self.evaluator.params();
+        let picker = MovePicker::new(moves, |chess_move| {
+            self.ordering.score_move(game, chess_move, None, ply, None, params)
+        });
+
+        let mut best_score = stand_pat.unwrap_or(-INFINITY);
+        for chess_move in picker {
+            if let Some(stand_pat) = stand_pat {
+                if stand_pat + self.material_gain(&chess_move) + DELTA_MARGIN <= alpha {
+                    continue;
+                }
+            }
+            let score = -self.quiescence(&game.make_move(&chess_move), ply + 1, -beta, -alpha);
+            if self.shared.is_stopped() {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                alpha = alpha.max(score);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        best_score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod quiescence {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::evaluate,
+            search_engine::{
+                score::{INFINITY, MATE_SCORE},
+                Searcher,
+            },
+        };
+
+        fn quiescence(fen: &str) -> (GameState, i32) {
+            let game = GameState::from_fen(fen).expect("valid fen");
+            (game, Searcher::new().quiescence(&game, 0, -INFINITY, INFINITY))
+        }
+
+        #[test]
+        fn returns_static_evaluation_when_position_is_quiet() {
+            // act
+            let (game, output) = quiescence("4k3/8/8/8/8/2N5/8/4K3 w - - 0 1");
+            // assert
+            assert_eq!(output, evaluate(&game))
+        }
+
+        #[test]
+        fn plays_out_exchanges_and_stands_pat_on_losing_captures() {
+            // act
+            // the queen can take a pawn, but loses itself to the recapture, so white stands pat
+            let (game, defended) = quiescence("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1");
+            // the knight wins a rook outright
+            let (_, hanging) = quiescence("4k3/8/8/3r4/8/4N3/8/4K3 w - - 0 1");
+            // assert
+            assert_eq!(defended, evaluate(&game));
+            assert!(hanging > 0)
+        }
+
+        #[test]
+        fn finds_mate_when_in_check_with_no_evasions() {
+            // act
+            let (_, output) = quiescence("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1");
+            // assert
+            assert_eq!(output, -MATE_SCORE)
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::{cmp::Ordering, fmt};
+
+/// The score of being checkmated right now. Mates further away score closer to zero by one per ply, so the
+/// search prefers the quickest mate and the slowest loss.
+pub(crate) const MATE_SCORE: i32 = 30_000;
+/// Bounds every score the search can return, mates included
+pub(crate) const INFINITY: i32 = 32_000;
+/// The deepest the search will ever go, in plies from the root
+pub(crate) const MAX_PLY: usize = 128;
+/// Any score this far from zero or further is a mate
+pub(crate) const MATE_THRESHOLD: i32 = MATE_SCORE - MAX_PLY as i32;
+
+/// A search score in the form it is reported in: an ordinary evaluation, or a forced mate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SearchScore {
+    /// An evaluation in centipawns from the point of view of the side to move
+    Centipawns(i32),
+    /// A forced mate in this many moves, negative when the side to move is the one getting mated
+    Mate(i32),
+}
+
+impl SearchScore {
+    /// Converts a raw search score, where mates count down from `MATE_SCORE` by ply, into a reported score
+    pub(crate) fn from_search_score(score: i32) -> Self {
+        match score {
+            _ if score >= MATE_THRESHOLD => Self::Mate((MATE_SCORE - score + 1) / 2),
+            _ if score <= -MATE_THRESHOLD => Self::Mate(-(MATE_SCORE + score) / 2),
+            _ => Self::Centipawns(score),
+        }
+    }
+
+    /// Ranks the score against others: being mated sooner is worst, then being mated later, then evaluations,
+    /// then mating later, and mating sooner is best
+    fn rank(&self) -> (u8, i32) {
+        match *self {
+            Self::Mate(moves) if moves > 0 => (2, -moves),
+            Self::Centipawns(score) => (1, score),
+            Self::Mate(moves) => (0, -moves),
+        }
+    }
+}
+
+/// Orders scores from worst to best for the side to move
+impl Ord for SearchScore {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for SearchScore {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Writes the score the way UCI does, e.g. `cp 35` or `mate -2`
+impl fmt::Display for SearchScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Centipawns(score) => write!(f, "cp {}", score),
+            Self::Mate(moves) => write!(f, "mate {}", moves),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod from_search_score {
+        use crate::search_engine::score::{SearchScore, MATE_SCORE};
+
+        #[test]
+        fn counts_mates_in_moves_for_both_sides() {
+            // act + assert
+            // mating with the next move is one ply away
+            assert_eq!(SearchScore::from_search_score(MATE_SCORE - 1), SearchScore::Mate(1));
+            assert_eq!(SearchScore::from_search_score(MATE_SCORE - 3), SearchScore::Mate(2));
+            // being mated after our move and the reply is two plies away
+            assert_eq!(SearchScore::from_search_score(-MATE_SCORE + 2), SearchScore::Mate(-1));
+            assert_eq!(SearchScore::from_search_score(-MATE_SCORE), SearchScore::Mate(0));
+            assert_eq!(SearchScore::from_search_score(-250), SearchScore::Centipawns(-250))
+        }
+    }
+
+    mod cmp {
+        use crate::search_engine::score::SearchScore;
+
+        #[test]
+        fn orders_scores_from_worst_to_best_for_side_to_move() {
+            // arrange
+            let mut scores = vec![
+                SearchScore::Mate(3),
+                SearchScore::Centipawns(-50),
+                SearchScore::Mate(-1),
+                SearchScore::Mate(1),
+                SearchScore::Centipawns(120),
+                SearchScore::Mate(-4),
+            ];
+            // act
+            scores.sort();
+            // assert
+            assert_eq!(
+                scores,
+                vec![
+                    SearchScore::Mate(-1),
+                    SearchScore::Mate(-4),
+                    SearchScore::Centipawns(-50),
+                    SearchScore::Centipawns(120),
+                    SearchScore::Mate(3),
+                    SearchScore::Mate(1),
+                ]
+            )
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    chess_state::{
+        board_bitmask::BoardBitmasks,
+        chess_pieces::PieceEnum,
+        moves::{
+            attack_maps::{
+                BishopAttackMaps, BlackPawnAttackMaps, KingAttackMaps, KnightAttackMaps, RookAttackMaps,
+                WhitePawnAttackMaps,
+            },
+            shared::Move,
+        },
+    },
+    evaluation_engine::{material::piece_value, params::EvalParams},
+};
+
+/// What a king is worth to the exchange. It is more than everything else on the board put together, so the
+/// side that would have to recapture with a king into a defended square always prefers to stop.
+const KING_EXCHANGE_VALUE: i32 = 20_000;
+
+/// The most captures one exchange on a square can take
+const MAX_EXCHANGE_LENGTH: usize = 32;
+
+/// The middlegame value of a piece for weighing up exchanges
+pub(crate) fn exchange_value(piece: PieceEnum, params: &EvalParams) -> i32 {
+    match piece {
+        PieceEnum::WhiteKing | PieceEnum::BlackKing => KING_EXCHANGE_VALUE,
+        _ => piece_value(piece, params).mg,
+    }
+}
+
+impl BoardBitmasks {
+    /// Returns a bitmask of every piece of either colour attacking `target`, a single bit bitmask, with sliding
+    /// pieces blocked by `occupied`. Pieces not in `occupied` are treated as gone.
+    pub(crate) fn attackers_to(&self, target: u64, occupied: u64) -> u64 {
+        let diagonal_sliders = self.white_bishops.mask | self.black_bishops.mask | self.white_queens.mask
+            | self.black_queens.mask;
+        let straight_sliders =
+            self.white_rooks.mask | self.black_rooks.mask | self.white_queens.mask | self.black_queens.mask;
+        // a pawn attacks the target from the squares a pawn of the other colour on the target would attack
+        (target.calculate_unconstrained_black_pawn_attack_maps() & self.white_pawns.mask
+            | target.calculate_unconstrained_white_pawn_attack_maps() & self.black_pawns.mask
+            | target.calculate_unconstrained_knight_maps() & (self.white_knights.mask | self.black_knights.mask)
+            | target.calculate_bishop_attack_maps(occupied) & diagonal_sliders
+            | target.calculate_rook_attack_maps(occupied) & straight_sliders
+            | target.calculate_unconstrained_king_attack_maps() & (self.white_kings.mask | self.black_kings.mask))
+            & occupied
+    }
+}
+
+/// Works out the material a move wins or loses once every capture back and forth on its destination square
+/// has been played out, with each side always recapturing with its least valuable piece and free to stop
+/// whenever carrying on would lose more. Quiet moves and castling score zero.
+pub(crate) fn static_exchange_evaluation(board: &BoardBitmasks, chess_move: &Move, params: &EvalParams) -> i32 {
+    let Move::StandardMove(standard_move) = chess_move else {
+        return 0;
+    };
+    let target = standard_move.end_position.to_bitmask();
+    let mut occupied = board.all_pieces.mask ^ standard_move.start_position.to_bitmask();
+    if let Some((square, _)) = standard_move.takes {
+        // for en passant the captured pawn is not on the target square
+        occupied &= !square.to_bitmask();
+    }
+
+    let mut gains = [0; MAX_EXCHANGE_LENGTH];
+    gains[0] = standard_move.takes.map_or(0, |(_, piece)| exchange_value(piece, params));
+    // the piece now standing on the target, and so the next one to be taken
+    let mut on_target = standard_move.promotion.unwrap_or(standard_move.piece);
+    if let Some(promotion) = standard_move.promotion {
+        gains[0] += exchange_value(promotion, params) - exchange_value(standard_move.piece, params);
+    }
+    let mut white = !standard_move.piece.is_white();
+    let mut length = 1;
+
+    while length < MAX_EXCHANGE_LENGTH {
+        let attackers = board.attackers_to(target, occupied);
+        let side_pieces = match white {
+            true => PieceEnum::ALL[..6].iter(),
+            false => PieceEnum::ALL[6..].iter(),
+        };
+        let Some((attacker, attacker_bitmask)) = side_pieces
+            .map(|&piece| (piece, board.piece_enum_to_bitmask(piece) & attackers))
+            .find(|&(_, bitmask)| bitmask != 0)
+        else {
+            break;
+        };
+        gains[length] = exchange_value(on_target, params) - gains[length - 1];
+        occupied ^= attacker_bitmask & attacker_bitmask.wrapping_neg();
+        on_target = attacker;
+        white = !white;
+        length += 1;
+    }
+
+    // walk back from the end of the exchange, letting each side stop capturing if that is better for it
+    for index in (1..length).rev() {
+        gains[index - 1] = -(-gains[index - 1]).max(gains[index]);
+    }
+    gains[0]
+}
+
+#[cfg(test)]
+mod tests {
+    mod static_exchange_evaluation {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{
+                consts::{BISHOP_VALUE, KNIGHT_VALUE, PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE},
+                params::DEFAULT_EVAL_PARAMS,
+            },
+            search_engine::see::static_exchange_evaluation,
+        };
+
+        fn see(fen: &str, uci: &str) -> i32 {
+            let game = GameState::from_fen(fen).expect("valid fen");
+            let chess_move = game.find_uci_move(uci).expect("legal move");
+            static_exchange_evaluation(&game.board, &chess_move, &DEFAULT_EVAL_PARAMS)
+        }
+
+        #[test]
+        fn wins_the_piece_when_it_is_undefended() {
+            // act + assert
+            assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), PAWN_VALUE.mg)
+        }
+
+        #[test]
+        fn plays_out_exchanges_with_x_ray_attackers() {
+            // act + assert
+            // Nxe5 is met by Nxe5 and then only the rook and queen remain, stacked on the e-file
+            assert_eq!(
+                see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"),
+                PAWN_VALUE.mg - KNIGHT_VALUE.mg
+            );
+            // the queen takes the bishop back, but the bishop is worth less than the rook
+            assert_eq!(
+                see("3q2k1/8/8/3r4/8/8/6B1/6K1 w - - 0 1", "g2d5"),
+                ROOK_VALUE.mg - BISHOP_VALUE.mg
+            )
+        }
+
+        #[test]
+        fn stops_when_recapturing_would_lose_more() {
+            // act + assert
+            // the queen takes a defended pawn and is lost for it
+            assert_eq!(see("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1", "d2d5"), PAWN_VALUE.mg - QUEEN_VALUE.mg);
+            // a quiet move onto an attacked square loses the piece
+            assert_eq!(see("4k3/8/2p5/8/8/8/3Q4/4K3 w - - 0 1", "d2d5"), -QUEEN_VALUE.mg);
+            assert_eq!(see("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1", "d2d5"), 0)
+        }
+    }
+}
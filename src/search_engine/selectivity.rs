@@ -0,0 +1,156 @@
+use crate::{chess_state::game_state::GameState, search_engine::move_ordering::MAX_HISTORY};
+
+/// The least depth at which a null move is tried
+pub(super) const NULL_MOVE_MIN_DEPTH: u32 = 3;
+
+/// The deepest node at which reverse futility pruning may cut off
+pub(super) const REVERSE_FUTILITY_MAX_DEPTH: u32 = 6;
+
+/// How far the static evaluation must stay above beta for each remaining ply before reverse futility pruning
+/// trusts it to hold
+const REVERSE_FUTILITY_MARGIN: i32 = 80;
+
+/// The deepest node at which futility pruning and late move pruning skip quiet moves
+pub(super) const FUTILITY_MAX_DEPTH: u32 = 3;
+
+/// How much a quiet move may be hoped to raise the static evaluation for each remaining ply
+const FUTILITY_MARGIN: i32 = 120;
+
+/// The least depth at which late moves are reduced
+pub(super) const LATE_MOVE_REDUCTION_MIN_DEPTH: u32 = 3;
+
+/// How many moves are searched at full depth before later ones are reduced
+pub(super) const LATE_MOVE_REDUCTION_MIN_INDEX: usize = 3;
+
+/// Which of the selective search techniques are used. Each can be turned off on its own, to test it or to see
+/// what it is worth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SearchFeatures {
+    /// Lets the opponent move twice in a row at a reduced depth, and cuts off if the side to move is still
+    /// winning, since a real move would almost always do better than passing
+    pub null_move_pruning: bool,
+    /// Searches moves that come late in the ordering less deeply, searching again at full depth if one
+    /// turns out to be better than expected
+    pub late_move_reductions: bool,
+    /// Skips quiet moves near the horizon when the static evaluation is too far below alpha to catch up
+    pub futility_pruning: bool,
+    /// Cuts off near the horizon when the static evaluation is far enough above beta
+    pub reverse_futility_pruning: bool,
+    /// Skips the remaining quiet moves near the horizon once enough have been tried
+    pub late_move_pruning: bool,
+}
+
+impl SearchFeatures {
+    /// Every technique turned off, leaving a plain alpha-beta search
+    #[cfg(test)]
+    pub(crate) const NONE: SearchFeatures = SearchFeatures {
+        null_move_pruning: false,
+        late_move_reductions: false,
+        futility_pruning: false,
+        reverse_futility_pruning: false,
+        late_move_pruning: false,
+    };
+}
+
+impl Default for SearchFeatures {
+    fn default() -> Self {
+        Self {
+            null_move_pruning: true,
+            late_move_reductions: true,
+            futility_pruning: true,
+            reverse_futility_pruning: true,
+            late_move_pruning: true,
+        }
+    }
+}
+
+/// How many plies less a null move is searched with, `depth` plies from the horizon
+pub(super) fn null_move_reduction(depth: u32) -> u32 {
+    3 + depth / 6
+}
+
+pub(super) fn reverse_futility_margin(depth: u32) -> i32 {
+    REVERSE_FUTILITY_MARGIN * depth as i32
+}
+
+pub(super) fn futility_margin(depth: u32) -> i32 {
+    FUTILITY_MARGIN * depth as i32
+}
+
+/// How many quiet moves are tried `depth` plies from the horizon before the rest are pruned
+pub(super) fn late_move_pruning_count(depth: u32) -> usize {
+    3 + (depth * depth) as usize
+}
+
+/// How many plies less the move at `move_index` in the ordering is searched with, `depth` plies from the
+/// horizon. Later moves and deeper nodes are reduced more, and moves with a good history less. The reduction
+/// always leaves at least one ply.
+pub(super) fn late_move_reduction(depth: u32, move_index: usize, history: i32) -> u32 {
+    let base = (depth as f64).ln() * (move_index as f64).ln() / 2.0;
+    // a history of half the maximum either way is worth one ply
+    let adjusted = base.round() as i32 - history * 2 / MAX_HISTORY;
+    adjusted.clamp(0, depth as i32 - 2) as u32
+}
+
+impl GameState {
+    /// Whether the side to move has a piece other than its king and pawns. Without one, zugzwang is common
+    /// enough that passing the turn cannot be trusted to be worse than moving.
+    pub(crate) fn has_non_pawn_material(&self) -> bool {
+        let pieces = match self.white_to_move {
+            true => {
+                self.board.white_knights.mask
+                    | self.board.white_bishops.mask
+                    | self.board.white_rooks.mask
+                    | self.board.white_queens.mask
+            }
+            false => {
+                self.board.black_knights.mask
+                    | self.board.black_bishops.mask
+                    | self.board.black_rooks.mask
+                    | self.board.black_queens.mask
+            }
+        };
+        pieces != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod late_move_reduction {
+        use crate::search_engine::{move_ordering::MAX_HISTORY, selectivity::late_move_reduction};
+
+        #[test]
+        fn reduces_later_moves_and_deeper_nodes_more() {
+            // act + assert
+            assert!(late_move_reduction(8, 20, 0) > late_move_reduction(8, 4, 0));
+            assert!(late_move_reduction(12, 10, 0) > late_move_reduction(4, 10, 0))
+        }
+
+        #[test]
+        fn reduces_moves_with_good_history_less() {
+            // act + assert
+            assert!(late_move_reduction(10, 20, MAX_HISTORY) < late_move_reduction(10, 20, 0));
+            assert!(late_move_reduction(10, 20, -MAX_HISTORY) > late_move_reduction(10, 20, 0))
+        }
+
+        #[test]
+        fn always_leaves_at_least_one_ply() {
+            // act + assert
+            assert_eq!(late_move_reduction(3, 60, -MAX_HISTORY), 1);
+            assert_eq!(late_move_reduction(5, 3, MAX_HISTORY), 0)
+        }
+    }
+
+    mod has_non_pawn_material {
+        use crate::chess_state::game_state::GameState;
+
+        #[test]
+        fn is_false_for_side_with_only_king_and_pawns() {
+            // arrange
+            let fen = |side| format!("4k3/pp6/8/8/8/8/PP6/4K1N1 {side} - - 0 1");
+            // act + assert
+            assert!(GameState::from_fen(&fen("w")).expect("valid fen").has_non_pawn_material());
+            assert!(!GameState::from_fen(&fen("b")).expect("valid fen").has_non_pawn_material())
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::time::{Duration, Instant};
+
+use crate::chess_state::moves::shared::Move;
+
+/// How many moves the remaining time is shared between when the clock does not say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// Time kept back from every allocation for the delay between the engine sending a move and the clock stopping
+const MOVE_OVERHEAD: Duration = Duration::from_millis(20);
+
+/// How many times the soft limit the hard limit allows, before it is capped by the time left
+const HARD_LIMIT_FACTOR: u32 = 4;
+
+/// How much longer the soft limit grows, in percent, when the best move changes at a depth. Half of it carries
+/// over to the next depth, so a best move that keeps changing keeps the search going longer.
+const BEST_MOVE_CHANGE_EXTENSION: u32 = 60;
+
+/// How far in centipawns the score has to fall from the last depth before the soft limit grows
+const SCORE_DROP_MARGIN: i32 = 20;
+
+/// The most the soft limit grows, in percent, for a falling score. It grows by one percent per centipawn the
+/// score falls.
+const MAX_SCORE_DROP_EXTENSION: u32 = 100;
+
+/// The least depth at which the search checks whether the best move is so far ahead of the rest that it can
+/// stop early
+const EASY_MOVE_MIN_DEPTH: u32 = 6;
+
+/// How far in centipawns every other move must score below the best move for the best move to count as forced
+pub(super) const EASY_MOVE_MARGIN: i32 = 150;
+
+/// What fraction of the soft limit has to have passed before a forced best move stops the search, so that the
+/// search has had a fair look at the position first
+const EASY_MOVE_TIME_DIVISOR: u32 = 4;
+
+/// The time left on both sides' clocks, as a GUI reports it at the start of each search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Clock {
+    pub white_time: Duration,
+    pub black_time: Duration,
+    /// The time white gets back after each move
+    pub white_increment: Duration,
+    /// The time black gets back after each move
+    pub black_increment: Duration,
+    /// How many moves must be played before more time is added, or `None` when the time is for the whole game
+    pub moves_to_go: Option<u32>,
+}
+
+/// Decides how long a search playing on a clock takes. The hard limit is never passed. The soft limit is
+/// checked after every depth, and grows while the best move keeps changing or the score is falling, since those
+/// are the positions where thinking longer pays. A search with only one legal move, or a best move far ahead
+/// of all others, stops early instead.
+#[derive(Debug, Clone)]
+pub(crate) struct TimeManager {
+    start: Instant,
+    soft_limit: Duration,
+    hard_limit: Duration,
+    /// How far the soft limit currently grows, in percent, for best moves changing
+    instability_extension: u32,
+    /// How far the soft limit currently grows, in percent, for a falling score
+    score_drop_extension: u32,
+    previous_best_move: Option<Move>,
+    previous_score: Option<i32>,
+}
+
+impl TimeManager {
+    /// Shares out the time on the clock of the side to move, starting now
+    pub(crate) fn new(clock: &Clock, white_to_move: bool) -> Self {
+        let (time, increment) = match white_to_move {
+            true => (clock.white_time, clock.white_increment),
+            false => (clock.black_time, clock.black_increment),
+        };
+        let available = time.saturating_sub(MOVE_OVERHEAD);
+        let moves_to_go = clock.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let share = available / moves_to_go + increment * 3 / 4;
+        Self {
+            start: Instant::now(),
+            soft_limit: share.min(available / 2),
+            hard_limit: (share * HARD_LIMIT_FACTOR).min(available * 3 / 4),
+            instability_extension: 0,
+            score_drop_extension: 0,
+            previous_best_move: None,
+            previous_score: None,
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// When the search must stop, even in the middle of a depth
+    pub(crate) fn deadline(&self) -> Instant {
+        self.start + self.hard_limit
+    }
+
+    /// How long the search may go on starting new depths, grown for an unstable best move or a falling score
+    pub(crate) fn soft_limit(&self) -> Duration {
+        let percent = 100 + self.instability_extension + self.score_drop_extension;
+        (self.soft_limit * percent / 100).min(self.hard_limit)
+    }
+
+    /// Takes in the best move and score of a depth that has just completed
+    pub(crate) fn update(&mut self, best_move: Option<Move>, score: i32) {
+        let best_move_changed = self.previous_best_move.is_some() && self.previous_best_move != best_move;
+        self.instability_extension = self.instability_extension / 2
+            + match best_move_changed {
+                true => BEST_MOVE_CHANGE_EXTENSION,
+                false => 0,
+            };
+        let score_drop = self.previous_score.map_or(0, |previous_score| previous_score - score);
+        self.score_drop_extension = match score_drop >= SCORE_DROP_MARGIN {
+            true => (score_drop as u32).min(MAX_SCORE_DROP_EXTENSION),
+            false => 0,
+        };
+        self.previous_best_move = best_move;
+        self.previous_score = Some(score);
+    }
+
+    /// Whether the best move has held still for the last depth, and enough time has passed, for it to be worth
+    /// checking whether it is far enough ahead of the rest to stop early
+    pub(crate) fn wants_easy_move_check(&self, depth: u32) -> bool {
+        depth >= EASY_MOVE_MIN_DEPTH
+            && self.instability_extension == 0
+            && self.elapsed() >= self.soft_limit / EASY_MOVE_TIME_DIVISOR
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod time_manager {
+        use std::time::Duration;
+
+        use crate::{
+            chess_state::game_state::GameState,
+            search_engine::time_manager::{Clock, TimeManager, MOVE_OVERHEAD},
+        };
+
+        fn clock(time_ms: u64, increment_ms: u64, moves_to_go: Option<u32>) -> Clock {
+            Clock {
+                white_time: Duration::from_millis(time_ms),
+                black_time: Duration::from_millis(time_ms / 2),
+                white_increment: Duration::from_millis(increment_ms),
+                black_increment: Duration::ZERO,
+                moves_to_go,
+            }
+        }
+
+        #[test]
+        fn shares_time_between_moves_to_go_and_adds_most_of_the_increment() {
+            // act
+            let sudden_death = TimeManager::new(&clock(60_020, 0, None), true);
+            let with_increment = TimeManager::new(&clock(60_020, 1000, Some(10)), true);
+            let black = TimeManager::new(&clock(60_020, 1000, Some(10)), false);
+            // assert
+            assert_eq!(sudden_death.soft_limit(), Duration::from_millis(2000));
+            assert_eq!(with_increment.soft_limit(), Duration::from_millis(6750));
+            assert_eq!(with_increment.hard_limit, Duration::from_millis(27_000));
+            assert_eq!(black.soft_limit(), Duration::from_millis(2999))
+        }
+
+        #[test]
+        fn never_allows_more_time_than_is_left() {
+            // act
+            let last_move = TimeManager::new(&clock(1020, 5000, Some(1)), true);
+            let flagging = TimeManager::new(&clock(10, 0, None), true);
+            // assert
+            assert!(last_move.hard_limit <= Duration::from_millis(1020) - MOVE_OVERHEAD);
+            assert!(last_move.soft_limit() <= last_move.hard_limit);
+            assert_eq!(flagging.hard_limit, Duration::ZERO)
+        }
+
+        #[test]
+        fn extends_soft_limit_when_best_move_changes_or_score_drops() {
+            // arrange
+            let game = GameState::default();
+            let first = game.find_uci_move("e2e4");
+            let second = game.find_uci_move("d2d4");
+            let mut time_manager = TimeManager::new(&clock(60_020, 0, None), true);
+            let base = time_manager.soft_limit();
+            // act + assert
+            time_manager.update(first, 30);
+            time_manager.update(first, 25);
+            assert_eq!(time_manager.soft_limit(), base);
+            time_manager.update(second, 25);
+            assert_eq!(time_manager.soft_limit(), base * 160 / 100);
+            time_manager.update(second, -25);
+            assert_eq!(time_manager.soft_limit(), base * 180 / 100);
+            time_manager.update(second, -20);
+            assert_eq!(time_manager.soft_limit(), base * 115 / 100)
+        }
+    }
+}
@@ -0,0 +1,450 @@
+use std::{
+    mem,
+    sync::atomic::{AtomicU64, AtomicU8, Ordering},
+};
+
+use crate::{
+    chess_state::{
+        chess_pieces::PieceEnum,
+        moves::shared::{CastleType, Move},
+    },
+    search_engine::score::MATE_THRESHOLD,
+};
+
+/// The default size of a `TranspositionTable` in megabytes
+pub(crate) const DEFAULT_TRANSPOSITION_TABLE_MEGABYTES: usize = 16;
+
+/// How many entries share each bucket. A position can be stored in any slot of its bucket, so a few busy
+/// positions landing in the same bucket do not keep evicting each other. Four slots fill one cache line.
+const BUCKET_SIZE: usize = 4;
+
+/// How many plies of depth one search of age is worth when choosing which entry to evict, so entries left
+/// behind by earlier searches give way to fresh ones unless they were searched much deeper
+const AGE_WEIGHT: i32 = 4;
+
+/// Set in the data of every slot holding an entry, so an empty slot is never mistaken for one
+const OCCUPIED: u64 = 1 << 63;
+
+/// What a stored score says about the true score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bound {
+    /// The score is exact
+    Exact,
+    /// The search failed high, so the true score is at least the stored score
+    Lower,
+    /// The search failed low, so the true score is at most the stored score
+    Upper,
+}
+
+/// A move squeezed into 16 bits for the table: the start and end squares and any promotion, or just the type of
+/// a castle. It is turned back into a move by finding the legal move it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PackedMove(u16);
+
+impl PackedMove {
+    pub(crate) fn new(chess_move: &Move) -> Self {
+        match chess_move {
+            Move::StandardMove(standard_move) => {
+                let start = standard_move.start_position.to_bitmask().trailing_zeros() as u16;
+                let end = standard_move.end_position.to_bitmask().trailing_zeros() as u16;
+                let promotion = match standard_move.promotion {
+                    Some(PieceEnum::WhiteKnight | PieceEnum::BlackKnight) => 1,
+                    Some(PieceEnum::WhiteBishop | PieceEnum::BlackBishop) => 2,
+                    Some(PieceEnum::WhiteRook | PieceEnum::BlackRook) => 3,
+                    Some(_) => 4,
+                    None => 0,
+                };
+                Self(start | end << 6 | promotion << 12)
+            }
+            // a standard move never starts and ends on the same square, so castles cannot clash with one
+            Move::Castle(CastleType::ShortCastle) => Self(5 << 12),
+            Move::Castle(CastleType::LongCastle) => Self(6 << 12),
+        }
+    }
+
+    /// Finds the move among `moves` that this was packed from, if there is one
+    pub(crate) fn find_in(self, moves: &[Move]) -> Option<Move> {
+        moves.iter().find(|chess_move| Self::new(chess_move) == self).copied()
+    }
+}
+
+/// What the search found out about one position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TranspositionEntry {
+    /// The full hash of the position, to tell it apart from others sharing the bucket
+    pub key: u64,
+    /// The best move found, or `None` if no move beat alpha
+    pub best_move: Option<PackedMove>,
+    /// How many plies deep the position was searched
+    pub depth: u32,
+    pub bound: Bound,
+    /// The score from the point of view of the side to move. Mate scores are stored as the distance to mate
+    /// from this position rather than from the root, and converted back when probed.
+    pub score: i32,
+    /// The search the entry was stored in, counting up from when the table was last cleared
+    age: u8,
+}
+
+impl TranspositionEntry {
+    /// Packs everything but the key into 64 bits
+    fn to_data(self) -> u64 {
+        let best_move = self.best_move.map_or(0, |packed| packed.0);
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        OCCUPIED
+            | best_move as u64
+            | (self.score as i16 as u16 as u64) << 16
+            | (self.depth.min(u8::MAX as u32) as u64) << 32
+            | bound << 40
+            | (self.age as u64) << 42
+    }
+
+    fn from_data(key: u64, data: u64) -> Self {
+        Self {
+            key,
+            best_move: Some(PackedMove(data as u16)).filter(|packed| packed.0 != 0),
+            score: (data >> 16) as u16 as i16 as i32,
+            depth: (data >> 32) as u8 as u32,
+            bound: match (data >> 40) & 0b11 {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            age: (data >> 42) as u8,
+        }
+    }
+}
+
+/// One place an entry can be stored. The key is stored XORed with the data, so that if two threads write the
+/// slot at once and it ends up with the key of one and the data of the other, the key no longer matches and
+/// the torn entry is never used.
+#[derive(Debug, Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn load(&self) -> Option<TranspositionEntry> {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed) ^ data;
+        (data & OCCUPIED != 0).then(|| TranspositionEntry::from_data(key, data))
+    }
+
+    fn save(&self, entry: TranspositionEntry) {
+        let data = entry.to_data();
+        self.key.store(entry.key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+
+    #[cfg(test)]
+    fn empty(&self) {
+        self.key.store(0, Ordering::Relaxed);
+        self.data.store(0, Ordering::Relaxed);
+    }
+}
+
+type Bucket = [Slot; BUCKET_SIZE];
+
+/// Converts a score counted from the root into one counted from the position `ply` plies into the search
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    match score {
+        _ if score >= MATE_THRESHOLD => score + ply as i32,
+        _ if score <= -MATE_THRESHOLD => score - ply as i32,
+        _ => score,
+    }
+}
+
+/// Converts a stored score back into one counted from the root, for a position `ply` plies into the search
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    match score {
+        _ if score >= MATE_THRESHOLD => score - ply as i32,
+        _ if score <= -MATE_THRESHOLD => score + ply as i32,
+        _ => score,
+    }
+}
+
+/// Finds the largest power of two number of buckets that fits in `megabytes`, keeping at least one bucket
+fn bucket_count(megabytes: usize) -> usize {
+    let buckets = (megabytes * 1024 * 1024 / mem::size_of::<Bucket>()).max(1);
+    // round down to a power of two so buckets can be found with a mask
+    1 << buckets.ilog2()
+}
+
+/// A fixed-size cache of search results keyed by `GameState::zobrist_hash`, so positions reached by different
+/// move orders, or seen again at the next depth, are not searched from scratch. Entries are grouped into
+/// buckets, and when a bucket is full the shallowest and oldest entry is evicted. Every method takes `&self`
+/// and works without locks, so search threads can share one table.
+#[derive(Debug)]
+pub(crate) struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// Creates an empty table taking up at most `megabytes` of memory
+    pub(crate) fn new(megabytes: usize) -> Self {
+        Self {
+            buckets: (0..bucket_count(megabytes)).map(|_| Bucket::default()).collect(),
+            age: AtomicU8::new(0),
+        }
+    }
+
+    /// Empties the table, e.g. before starting a new game
+    #[cfg(test)]
+    pub(crate) fn clear(&self) {
+        self.buckets.iter().flatten().for_each(Slot::empty);
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// Marks the start of a new search, so entries from earlier searches are the first to be evicted
+    pub(crate) fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How many entries the table can hold
+    #[cfg(test)]
+    pub(crate) fn capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    fn bucket(&self, key: u64) -> &Bucket {
+        &self.buckets[key as usize & (self.buckets.len() - 1)]
+    }
+
+    /// Looks up the position with hash `key`, found `ply` plies into the search
+    pub(crate) fn probe(&self, key: u64, ply: usize) -> Option<TranspositionEntry> {
+        self.bucket(key)
+            .iter()
+            .filter_map(Slot::load)
+            .find(|entry| entry.key == key)
+            .map(|entry| TranspositionEntry {
+                score: score_from_table(entry.score, ply),
+                ..entry
+            })
+    }
+
+    /// Stores what the search found about the position with hash `key`, `ply` plies into the search. An entry
+    /// for the same position is always replaced, keeping its best move if the new result has none.
+    pub(crate) fn store(
+        &self,
+        key: u64,
+        best_move: Option<Move>,
+        depth: u32,
+        bound: Bound,
+        score: i32,
+        ply: usize,
+    ) {
+        let age = self.age.load(Ordering::Relaxed);
+        let bucket = self.bucket(key);
+        let entries = bucket.each_ref().map(Slot::load);
+
+        let slot = match entries.iter().position(|entry| entry.is_some_and(|entry| entry.key == key)) {
+            Some(slot) => slot,
+            None => match entries.iter().position(Option::is_none) {
+                Some(slot) => slot,
+                None => (0..BUCKET_SIZE)
+                    .min_by_key(|&slot| {
+                        let entry = entries[slot].expect("every slot in a full bucket holds an entry");
+                        entry.depth as i32 - AGE_WEIGHT * age.wrapping_sub(entry.age) as i32
+                    })
+                    .expect("buckets are never empty"),
+            },
+        };
+
+        let best_move = best_move
+            .as_ref()
+            .map(PackedMove::new)
+            .or_else(|| entries[slot].filter(|entry| entry.key == key)?.best_move);
+        bucket[slot].save(TranspositionEntry {
+            key,
+            best_move,
+            depth,
+            bound,
+            score: score_to_table(score, ply),
+            age,
+        });
+    }
+
+    /// Estimates how full the table is in parts per thousand from a sample of its buckets, counting only
+    /// entries from the current search
+    #[cfg(test)]
+    pub(crate) fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+        let sample = &self.buckets[..self.buckets.len().min(1000 / BUCKET_SIZE)];
+        let used = sample
+            .iter()
+            .flatten()
+            .filter_map(Slot::load)
+            .filter(|entry| entry.age == age)
+            .count();
+        used * 1000 / (sample.len() * BUCKET_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod transposition_table {
+        use std::{mem, thread};
+
+        use crate::{
+            chess_state::{game_state::GameState, moves::shared::Move},
+            search_engine::{
+                score::MATE_SCORE,
+                transposition_table::{Bound, Bucket, PackedMove, TranspositionTable, BUCKET_SIZE},
+            },
+        };
+
+        fn some_move() -> Move {
+            let game = GameState::default();
+            game.find_uci_move("e2e4").expect("legal move")
+        }
+
+        fn packed_move() -> Option<PackedMove> {
+            Some(PackedMove::new(&some_move()))
+        }
+
+        #[test]
+        fn rounds_size_down_to_a_power_of_two() {
+            // act
+            let table = TranspositionTable::new(3);
+            let doubled = TranspositionTable::new(6);
+            // assert
+            assert!(table.capacity().is_power_of_two());
+            assert!(table.capacity() / BUCKET_SIZE * mem::size_of::<Bucket>() <= 3 * 1024 * 1024);
+            assert_eq!(doubled.capacity(), table.capacity() * 2)
+        }
+
+        #[test]
+        fn returns_stored_entry_until_cleared() {
+            // arrange
+            let table = TranspositionTable::new(1);
+            // act
+            table.store(42, Some(some_move()), 5, Bound::Lower, 120, 3);
+            // assert
+            let entry = table.probe(42, 7).expect("stored entry");
+            assert_eq!(
+                (entry.best_move, entry.depth, entry.bound, entry.score),
+                (packed_move(), 5, Bound::Lower, 120)
+            );
+            assert_eq!(table.probe(43, 0), None);
+            table.clear();
+            assert_eq!(table.probe(42, 0), None)
+        }
+
+        #[test]
+        fn adjusts_mate_scores_to_the_ply_they_are_probed_at() {
+            // arrange
+            let table = TranspositionTable::new(1);
+            // mate in three plies from a position four plies into the search
+            table.store(1, None, 3, Bound::Exact, MATE_SCORE - 7, 4);
+            table.store(2, None, 3, Bound::Exact, -MATE_SCORE + 7, 4);
+            // act + assert
+            // reached two plies into a later search, the mate is still three plies away
+            assert_eq!(table.probe(1, 2).expect("stored entry").score, MATE_SCORE - 5);
+            assert_eq!(table.probe(2, 2).expect("stored entry").score, -MATE_SCORE + 5)
+        }
+
+        #[test]
+        fn keeps_best_move_when_same_position_is_stored_without_one() {
+            // arrange
+            let table = TranspositionTable::new(1);
+            table.store(42, Some(some_move()), 2, Bound::Exact, 10, 0);
+            // act
+            table.store(42, None, 4, Bound::Upper, -30, 0);
+            // assert
+            let entry = table.probe(42, 0).expect("stored entry");
+            assert_eq!((entry.best_move, entry.depth, entry.bound), (packed_move(), 4, Bound::Upper))
+        }
+
+        #[test]
+        fn evicts_shallowest_entry_then_oldest_entries_when_bucket_is_full() {
+            // arrange
+            let table = TranspositionTable::new(1);
+            let buckets = (table.capacity() / BUCKET_SIZE) as u64;
+            // keys that all land in bucket zero, with depths 10, 3, 8, 6
+            let keys: Vec<u64> = (1..=6).map(|index| index * buckets).collect();
+            for (&key, depth) in keys.iter().zip([10, 3, 8, 6]) {
+                table.store(key, None, depth, Bound::Exact, 0, 0);
+            }
+            // act
+            table.store(keys[4], None, 1, Bound::Exact, 0, 0);
+            // assert
+            assert_eq!(table.probe(keys[1], 0), None);
+            assert!(keys.iter().filter(|&&key| table.probe(key, 0).is_some()).count() == BUCKET_SIZE);
+
+            // arrange
+            // three searches later, the old entries give way before a fresh shallow one
+            for _ in 0..3 {
+                table.new_search();
+            }
+            table.store(keys[1], None, 1, Bound::Exact, 0, 0);
+            // act
+            table.store(keys[5], None, 1, Bound::Exact, 0, 0);
+            // assert
+            assert!(table.probe(keys[1], 0).is_some());
+            assert_eq!(table.probe(keys[3], 0), None);
+            assert!(table.probe(keys[0], 0).is_some())
+        }
+
+        #[test]
+        fn never_returns_torn_entries_when_threads_write_at_once() {
+            // arrange
+            // every thread writes the same keys, with depths and scores that depend on the thread
+            let table = TranspositionTable::new(1);
+            let keys: Vec<u64> = (1..=64u64).map(|index| index.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+            // act
+            thread::scope(|scope| {
+                for thread_index in 1..=4 {
+                    let (table, keys) = (&table, &keys);
+                    scope.spawn(move || {
+                        for _ in 0..200 {
+                            for &key in keys {
+                                table.store(key, None, thread_index, Bound::Exact, thread_index as i32 * 100, 0);
+                                if let Some(entry) = table.probe(key, 0) {
+                                    // assert
+                                    assert_eq!(entry.key, key);
+                                    assert_eq!(entry.score, entry.depth as i32 * 100);
+                                }
+                            }
+                        }
+                    });
+                }
+            })
+        }
+
+        #[test]
+        fn reports_fullness_of_current_search_only() {
+            // arrange
+            let table = TranspositionTable::new(1);
+            for key in 0..2000 {
+                table.store(key, None, 1, Bound::Exact, 0, 0);
+            }
+            // act + assert
+            assert!(table.hashfull() > 0);
+            table.new_search();
+            assert_eq!(table.hashfull(), 0)
+        }
+    }
+
+    mod packed_move {
+        use crate::{chess_state::game_state::GameState, search_engine::transposition_table::PackedMove};
+
+        #[test]
+        fn finds_the_same_move_among_legal_moves() {
+            // arrange
+            // promotions to every piece, an en passant capture, and castling both ways
+            let game = GameState::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").expect("valid fen");
+            let moves = game.legal_moves();
+            // act + assert
+            for chess_move in &moves {
+                assert_eq!(PackedMove::new(chess_move).find_in(&moves), Some(*chess_move));
+            }
+            let quiet = GameState::default().find_uci_move("e2e4").expect("legal move");
+            assert_eq!(PackedMove::new(&quiet).find_in(&moves), None)
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! The command line of the `tune` binary, which tunes the evaluation parameters apart from the engine itself
+
+use std::{num::NonZeroUsize, thread};
+
+use thiserror::Error;
+
+use crate::{
+    evaluation_engine::params::{EvalParams, EvalParamsError},
+    tuner::{
+        dataset::{load_positions, DatasetError},
+        Tuner,
+    },
+};
+
+/// Full passes over the positions when `--epochs` is not given
+const DEFAULT_EPOCHS: usize = 1000;
+/// Step size in centipawns when `--learning-rate` is not given
+const DEFAULT_LEARNING_RATE: f64 = 1.0;
+/// How many epochs pass between each round of phase and attack weight tuning, progress log and save of the
+/// parameters so far
+const SAVE_INTERVAL: usize = 50;
+
+#[derive(Debug, Error)]
+pub(crate) enum TuneCommandError {
+    #[error(
+        "usage: tune <positions file> <output file> [--params <file>] [--epochs <n>] [--threads <n>] \
+        [--learning-rate <x>]"
+    )]
+    Usage,
+
+    #[error("{value} is not a valid value for {option}")]
+    InvalidOption { option: String, value: String },
+
+    #[error("Unknown option {0}")]
+    UnknownOption(String),
+
+    #[error("No labelled positions found")]
+    NoPositions,
+
+    #[error("{0}")]
+    DatasetError(#[from] DatasetError),
+
+    #[error("{0}")]
+    EvalParamsError(#[from] EvalParamsError),
+}
+
+/// The settings for a tuning run, read from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TuneArguments {
+    pub positions_path: String,
+    pub output_path: String,
+    /// The parameter file tuning starts from, or `None` for the defaults
+    pub params_path: Option<String>,
+    pub epochs: usize,
+    pub threads: usize,
+    pub learning_rate: f64,
+}
+
+impl TuneArguments {
+    /// Parses the arguments after the program name
+    pub(crate) fn parse(args: &[String]) -> Result<Self, TuneCommandError> {
+        let [positions_path, output_path, options @ ..] = args else {
+            return Err(TuneCommandError::Usage);
+        };
+        let mut arguments = Self {
+            positions_path: positions_path.clone(),
+            output_path: output_path.clone(),
+            params_path: None,
+            epochs: DEFAULT_EPOCHS,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            learning_rate: DEFAULT_LEARNING_RATE,
+        };
+
+        for pair in options.chunks(2) {
+            let [option, value] = pair else {
+                return Err(TuneCommandError::Usage);
+            };
+            let invalid = || TuneCommandError::InvalidOption {
+                option: option.clone(),
+                value: value.clone(),
+            };
+            match option.as_str() {
+                "--params" => arguments.params_path = Some(value.clone()),
+                "--epochs" => arguments.epochs = value.parse().map_err(|_| invalid())?,
+                "--threads" => {
+                    arguments.threads = value
+                        .parse()
+                        .ok()
+                        .filter(|&threads| threads > 0)
+                        .ok_or_else(invalid)?
+                }
+                "--learning-rate" => {
+                    arguments.learning_rate = value
+                        .parse()
+                        .ok()
+                        .filter(|&rate: &f64| rate > 0.0)
+                        .ok_or_else(invalid)?
+                }
+                _ => return Err(TuneCommandError::UnknownOption(option.clone())),
+            }
+        }
+        Ok(arguments)
+    }
+}
+
+/// Tunes the evaluation parameters, starting from the parameter file if one is given, against a file of labelled
+/// positions, saving the parameters so far to the output file as it goes
+pub(crate) fn run(arguments: &TuneArguments) -> Result<EvalParams, TuneCommandError> {
+    let params = match &arguments.params_path {
+        Some(path) => {
+            let params = EvalParams::load(path)?;
+            log::info!("Loaded evaluation parameters from {}", path);
+            params
+        }
+        None => EvalParams::default(),
+    };
+    log::info!(
+        "Loading positions from {} over {} threads",
+        arguments.positions_path,
+        arguments.threads
+    );
+    let positions = load_positions(&arguments.positions_path, &params, arguments.threads)?;
+    if positions.is_empty() {
+        return Err(TuneCommandError::NoPositions);
+    }
+    log::info!("Loaded {} positions", positions.len());
+
+    let mut tuner = Tuner::new(positions, &params, arguments.threads, arguments.learning_rate);
+    let scaling_constant = tuner.fit_scaling_constant();
+    log::info!(
+        "Fitted scaling constant {:.4}, error {:.6}",
+        scaling_constant,
+        tuner.mean_squared_error(scaling_constant)
+    );
+
+    for epoch in 1..=arguments.epochs {
+        tuner.step();
+        if epoch % SAVE_INTERVAL == 0 || epoch == arguments.epochs {
+            log::info!("Epoch {}: error {:.6}", epoch, tuner.tune_weights());
+            tuner.params().save(&arguments.output_path)?;
+        }
+    }
+    Ok(tuner.params())
+}
+
+#[cfg(test)]
+mod tests {
+    mod tune_arguments {
+        use crate::tuner::command::{TuneArguments, TuneCommandError, DEFAULT_EPOCHS};
+
+        fn args(args: &[&str]) -> Vec<String> {
+            args.iter().map(|arg| arg.to_string()).collect()
+        }
+
+        #[test]
+        fn reads_paths_and_options() {
+            // act
+            let output = TuneArguments::parse(&args(&[
+                "positions.epd",
+                "tuned.txt",
+                "--threads",
+                "3",
+                "--params",
+                "start.txt",
+                "--learning-rate",
+                "0.5",
+            ]))
+            .expect("valid arguments");
+            // assert
+            assert_eq!(output.positions_path, "positions.epd");
+            assert_eq!(output.output_path, "tuned.txt");
+            assert_eq!(output.params_path.as_deref(), Some("start.txt"));
+            assert_eq!(output.epochs, DEFAULT_EPOCHS);
+            assert_eq!(output.threads, 3);
+            assert_eq!(output.learning_rate, 0.5)
+        }
+
+        #[test]
+        fn returns_error_when_arguments_are_missing_or_invalid() {
+            // act + assert
+            assert!(matches!(
+                TuneArguments::parse(&args(&["positions.epd"])),
+                Err(TuneCommandError::Usage)
+            ));
+            assert!(matches!(
+                TuneArguments::parse(&args(&["positions.epd", "tuned.txt", "--threads", "0"])),
+                Err(TuneCommandError::InvalidOption { .. })
+            ));
+            assert!(matches!(
+                TuneArguments::parse(&args(&["positions.epd", "tuned.txt", "--speed", "1"])),
+                Err(TuneCommandError::UnknownOption(_))
+            ))
+        }
+    }
+}
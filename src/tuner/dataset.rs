@@ -0,0 +1,341 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    thread,
+};
+
+use thiserror::Error;
+
+use crate::{
+    chess_state::{board_bitmask::BoardBitmasks, fen::FenError, game_state::GameState},
+    evaluation_engine::{
+        king_safety::{king_attack_index, king_zone_attackers},
+        params::{EvalParams, ParamValues},
+        pawn_structure::evaluate_pawns,
+        phase::{phase_from_counts, piece_counts},
+        trace::{CoefficientSink, EvaluationTrace},
+    },
+};
+
+#[derive(Debug, Error)]
+pub(crate) enum DatasetError {
+    #[error("Line {0}: expects a FEN followed by a game result")]
+    MissingResult(usize),
+
+    #[error("Line {line}: {result} is not a valid game result, expects one of 1-0, 1/2-1/2, 0-1, 1.0, 0.5, 0.0")]
+    InvalidResult { line: usize, result: String },
+
+    #[error("Line {line}: {error}")]
+    FenError { line: usize, error: FenError },
+
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// The parameter whose values are picked by the attack weights, so a position cannot keep fixed coefficients for it
+const ATTACK_PENALTY: &str = "king_zone_attack_penalty";
+
+/// A labelled position, boiled down to what the tuner needs. The evaluation is linear in its parameters, so it
+/// can be rebuilt for any parameters from how often each one is used, without going back to the board. The
+/// pieces left and the king attackers are kept too, so the phase and attack weights can be changed as well.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TuningPosition {
+    /// How many knights, bishops, rooks and queens are left, from which the phase weights give the game phase
+    pub piece_counts: [i32; 4],
+    /// The result of the game the position came from, from white's point of view: `1.0` for a white win,
+    /// `0.5` for a draw, and `0.0` for a black win
+    pub result: f64,
+    /// Every parameter the position uses, as its index in `EvalParams::values` along with how many more times
+    /// white uses it than black, apart from `king_zone_attack_penalty`, which comes from `attack_coefficients`
+    pub coefficients: Vec<(u32, i32)>,
+    /// The enemy knights, bishops, rooks and queens attacking the zone around the white king, then the black king
+    pub king_attackers: [[i32; 4]; 2],
+}
+
+impl TuningPosition {
+    /// The game phase with the phase weights in `params`
+    pub(crate) fn phase(&self, params: &EvalParams) -> i32 {
+        phase_from_counts(self.piece_counts, params)
+    }
+
+    /// The `king_zone_attack_penalty` values the position uses with the attack weights in `params`, as their
+    /// index in `EvalParams::values` along with `1` for the white king or `-1` for the black king. `offset` is
+    /// where the penalties start in `EvalParams::values`, as given by `attack_penalty_offset`.
+    pub(crate) fn attack_coefficients(&self, params: &EvalParams, offset: usize) -> impl Iterator<Item = (u32, i32)> {
+        let [white, black] = self.king_attackers.map(|attackers| king_attack_index(attackers, params));
+        let coefficient = move |index: usize, sign: i32| ((offset + index) as u32, sign);
+        white
+            .map(|index| coefficient(index, 1))
+            .into_iter()
+            .chain(black.map(|index| coefficient(index, -1)))
+    }
+}
+
+/// Parses a game result, accepting PGN results and scores, optionally wrapped in brackets or quotes
+fn parse_result(token: &str) -> Option<f64> {
+    match token.trim_matches(|c| matches!(c, '[' | ']' | '"' | ';' | '(' | ')')) {
+        "1-0" | "1" | "1.0" => Some(1.0),
+        "1/2-1/2" | "0.5" => Some(0.5),
+        "0-1" | "0" | "0.0" => Some(0.0),
+        _ => None,
+    }
+}
+
+/// Parses one line of a labelled positions file: a FEN with the game result as its last word. Blank lines and
+/// lines starting with `#` give `None`.
+fn parse_line(line: &str, line_number: usize) -> Result<Option<(GameState, f64)>, DatasetError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let Some((fen, result)) = line.rsplit_once(char::is_whitespace) else {
+        return Err(DatasetError::MissingResult(line_number));
+    };
+    let result = parse_result(result).ok_or_else(|| DatasetError::InvalidResult {
+        line: line_number,
+        result: result.to_string(),
+    })?;
+    let game = GameState::from_fen(fen.trim().trim_end_matches(';')).map_err(|error| DatasetError::FenError {
+        line: line_number,
+        error,
+    })?;
+    Ok(Some((game, result)))
+}
+
+/// Finds where each parameter's values start in `EvalParams::values`, by name
+fn value_offsets(params: &EvalParams) -> HashMap<&'static str, usize> {
+    let mut offset = 0;
+    let mut offsets = HashMap::new();
+    for (name, values) in params.fields() {
+        if let ParamValues::Scores(scores) = values {
+            offsets.insert(name, offset);
+            offset += scores.len();
+        }
+    }
+    offsets
+}
+
+/// Where the `king_zone_attack_penalty` values start in `EvalParams::values`
+pub(crate) fn attack_penalty_offset(params: &EvalParams) -> usize {
+    value_offsets(params)[ATTACK_PENALTY]
+}
+
+/// Counts how many more times white uses each parameter than black, by its index in `EvalParams::values`,
+/// leaving out `king_zone_attack_penalty`
+struct Coefficients<'a> {
+    offsets: &'a HashMap<&'static str, usize>,
+    counts: BTreeMap<u32, i32>,
+}
+
+impl CoefficientSink for Coefficients<'_> {
+    fn add(&mut self, white: bool, name: &'static str, element: usize, count: i32) {
+        if name == ATTACK_PENALTY {
+            return;
+        }
+        let index = self.offsets[name] + element;
+        *self.counts.entry(index as u32).or_default() += match white {
+            true => count,
+            false => -count,
+        };
+    }
+}
+
+/// Finds how often each parameter is used on the board from a single traced evaluation, leaving out those
+/// that white and black use equally often, and `king_zone_attack_penalty`
+fn extract_coefficients(
+    board: &BoardBitmasks,
+    params: &EvalParams,
+    offsets: &HashMap<&'static str, usize>,
+) -> Vec<(u32, i32)> {
+    let mut coefficients = Coefficients {
+        offsets,
+        counts: BTreeMap::new(),
+    };
+    let pawns = evaluate_pawns(board.white_pawns.mask, board.black_pawns.mask, params, &mut coefficients);
+    EvaluationTrace::new(board, &pawns, params, &mut coefficients);
+    coefficients
+        .counts
+        .into_iter()
+        .filter(|&(_, count)| count != 0)
+        .collect()
+}
+
+/// Reads a file of labelled positions, one FEN and game result per line, and works out each position's
+/// coefficients over `threads` threads
+pub(crate) fn load_positions(
+    path: impl AsRef<Path>,
+    params: &EvalParams,
+    threads: usize,
+) -> Result<Vec<TuningPosition>, DatasetError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut games = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        if let Some(game) = parse_line(&line?, index + 1)? {
+            games.push(game);
+        }
+    }
+    Ok(to_tuning_positions(&games, params, threads))
+}
+
+/// Works out the coefficients of every position, splitting the positions evenly over `threads` threads
+pub(crate) fn to_tuning_positions(
+    games: &[(GameState, f64)],
+    params: &EvalParams,
+    threads: usize,
+) -> Vec<TuningPosition> {
+    let offsets = value_offsets(params);
+    let offsets = &offsets;
+    let chunk_size = games.len().div_ceil(threads.max(1)).max(1);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = games
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(game, result)| TuningPosition {
+                            piece_counts: piece_counts(&game.board),
+                            result: *result,
+                            coefficients: extract_coefficients(&game.board, params, offsets),
+                            king_attackers: [true, false].map(|white| king_zone_attackers(&game.board, white)),
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("coefficient thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    mod parse_line {
+        use crate::tuner::dataset::{parse_line, DatasetError};
+
+        #[test]
+        fn reads_fen_and_result_in_any_common_format() {
+            // arrange
+            let lines = [
+                ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 [1.0]", 1.0),
+                ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1; 1/2-1/2", 0.5),
+                ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 \"0-1\"", 0.0),
+                ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 0.5", 0.5),
+            ];
+            for (line, expected) in lines {
+                // act
+                let output = parse_line(line, 1).expect("valid line").expect("not blank");
+                // assert
+                assert_eq!(output.1, expected, "{}", line);
+            }
+        }
+
+        #[test]
+        fn skips_blank_lines_and_comments() {
+            // act + assert
+            assert!(parse_line("   ", 1).expect("valid line").is_none());
+            assert!(parse_line("# positions from self-play", 2).expect("valid line").is_none())
+        }
+
+        #[test]
+        fn returns_error_when_result_or_fen_is_invalid() {
+            // act + assert
+            assert!(matches!(
+                parse_line("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 2-0", 3),
+                Err(DatasetError::InvalidResult { line: 3, result }) if result == "2-0"
+            ));
+            assert!(matches!(
+                parse_line("4k3/8/8 w - - 0 1 1-0", 4),
+                Err(DatasetError::FenError { line: 4, .. })
+            ));
+            assert!(matches!(parse_line("1-0", 5), Err(DatasetError::MissingResult(5))))
+        }
+    }
+
+    mod to_tuning_positions {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{evaluate_with_trace, params::EvalParams, score::Score, Evaluator},
+            tuner::dataset::{attack_penalty_offset, to_tuning_positions},
+        };
+
+        #[test]
+        fn rebuilds_evaluation_from_coefficients() {
+            // arrange
+            let fens = [
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1",
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            ];
+            let games: Vec<_> = fens
+                .iter()
+                .map(|fen| (GameState::from_fen(fen).expect("valid fen"), 0.5))
+                .collect();
+            let params = EvalParams::default();
+            let values = params.values();
+            // act
+            let positions = to_tuning_positions(&games, &params, 3);
+            // assert
+            assert_eq!(positions.len(), games.len());
+            for ((game, _), position) in games.iter().zip(positions) {
+                let rebuilt: Score = position
+                    .coefficients
+                    .iter()
+                    .copied()
+                    .chain(position.attack_coefficients(&params, attack_penalty_offset(&params)))
+                    .map(|(index, coefficient)| values[index as usize] * coefficient)
+                    .sum();
+                let trace = evaluate_with_trace(&game.board);
+                assert_eq!(rebuilt, trace.total());
+                assert_eq!(position.phase(&params), trace.phase);
+            }
+        }
+
+        #[test]
+        fn rebuilds_evaluation_when_parameters_change() {
+            // arrange
+            let games = [
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "r1b2rk1/2q1bppp/p2p1n2/np2p3/3PP3/5N1P/PPBN1PP1/R1BQR1K1 b - - 0 12",
+                "4k3/1P6/8/8/8/8/6p1/4K3 w - - 0 70",
+            ]
+            .map(|fen| (GameState::from_fen(fen).expect("valid fen"), 0.5));
+            // every value moves by a different amount, so a parameter missing from the coefficients shows up, and
+            // the weights move too
+            let mut params = EvalParams {
+                phase_weights: [2, 1, 3, 3],
+                king_attack_weights: [3, 1, 2, 6],
+                ..EvalParams::default()
+            };
+            let values: Vec<Score> = params
+                .values()
+                .iter()
+                .enumerate()
+                .map(|(index, &value)| value + Score::new(index as i32 % 7 + 1, index as i32 % 5 + 1))
+                .collect();
+            params.set_values(&values);
+            let mut evaluator = Evaluator::with_params(params.clone());
+            // act
+            let positions = to_tuning_positions(&games, &EvalParams::default(), 2);
+            // assert
+            for ((game, _), position) in games.iter().zip(positions) {
+                let rebuilt: Score = position
+                    .coefficients
+                    .iter()
+                    .copied()
+                    .chain(position.attack_coefficients(&params, attack_penalty_offset(&params)))
+                    .map(|(index, coefficient)| values[index as usize] * coefficient)
+                    .sum();
+                let trace = evaluator.evaluate_with_trace(&game.board);
+                assert_eq!(rebuilt, trace.total());
+                assert_eq!(position.phase(&params), trace.phase);
+            }
+        }
+    }
+}
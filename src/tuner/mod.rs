@@ -0,0 +1,346 @@
+//! A Texel-style tuner for the evaluation parameters. Each labelled position's evaluation is turned into a
+//! predicted result by a sigmoid, and the parameters are moved to shrink the mean squared error between the
+//! predicted and actual results over the whole set. The phase and attack weights are whole numbers that pick
+//! which values are used rather than scaling them, so they have no gradient and are tuned by trying each one a
+//! step up and down instead.
+
+pub(crate) mod command;
+pub(crate) mod dataset;
+mod params;
+
+use std::thread;
+
+use crate::{
+    evaluation_engine::{consts::MAX_PHASE, params::EvalParams, score::Score},
+    tuner::dataset::{attack_penalty_offset, TuningPosition},
+};
+
+/// The range searched for the sigmoid scaling constant
+const SCALING_CONSTANT_RANGE: (f64, f64) = (0.0, 10.0);
+/// How many times the search for the scaling constant narrows its range
+const SCALING_CONSTANT_ITERATIONS: usize = 100;
+
+/// Decay rates of the running gradient averages kept by the Adam optimiser
+const ADAM_BETA1: f64 = 0.9;
+const ADAM_BETA2: f64 = 0.999;
+const ADAM_EPSILON: f64 = 1e-8;
+
+/// Turns an evaluation in centipawns into an expected result from `0.0` (black wins) to `1.0` (white wins)
+fn sigmoid(scaling_constant: f64, evaluation: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-scaling_constant * evaluation / 400.0))
+}
+
+/// Fits the evaluation parameters to a set of labelled positions. Parameters are held as floats while tuning,
+/// as `[middlegame, endgame]` pairs, and only rounded back to centipawns by `params`.
+#[derive(Debug, Clone)]
+pub(crate) struct Tuner {
+    positions: Vec<TuningPosition>,
+    /// The parameters tuning started from, with the phase and attack weights tuned so far
+    params: EvalParams,
+    values: Vec<[f64; 2]>,
+    /// Where the `king_zone_attack_penalty` values start in `values`
+    attack_penalty_offset: usize,
+    scaling_constant: f64,
+    threads: usize,
+    learning_rate: f64,
+    // Adam's running averages of the gradient and the squared gradient, and the number of steps taken
+    first_moments: Vec<[f64; 2]>,
+    second_moments: Vec<[f64; 2]>,
+    steps: i32,
+}
+
+impl Tuner {
+    /// Starts tuning from `params`, spreading the work over `threads` threads
+    pub(crate) fn new(
+        positions: Vec<TuningPosition>,
+        params: &EvalParams,
+        threads: usize,
+        learning_rate: f64,
+    ) -> Self {
+        let values: Vec<[f64; 2]> = params
+            .values()
+            .iter()
+            .map(|score| [score.mg as f64, score.eg as f64])
+            .collect();
+        Self {
+            positions,
+            params: params.clone(),
+            attack_penalty_offset: attack_penalty_offset(params),
+            first_moments: vec![[0.0; 2]; values.len()],
+            second_moments: vec![[0.0; 2]; values.len()],
+            values,
+            scaling_constant: 1.0,
+            threads: threads.max(1),
+            learning_rate,
+            steps: 0,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn scaling_constant(&self) -> f64 {
+        self.scaling_constant
+    }
+
+    /// Returns the current parameters, rounded to whole centipawns
+    pub(crate) fn params(&self) -> EvalParams {
+        let mut params = self.params.clone();
+        let values: Vec<Score> = self
+            .values
+            .iter()
+            .map(|[mg, eg]| Score::new(mg.round() as i32, eg.round() as i32))
+            .collect();
+        params.set_values(&values);
+        params
+    }
+
+    /// Runs `work` over an even share of the positions on each thread, returning each thread's output
+    fn map_chunks<R: Send>(&self, work: impl Fn(&[TuningPosition]) -> R + Sync) -> Vec<R> {
+        let chunk_size = self.positions.len().div_ceil(self.threads).max(1);
+        let work = &work;
+        thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .positions
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || work(chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("tuner thread panicked"))
+                .collect()
+        })
+    }
+
+    /// Every parameter a position uses with the current weights, as its index in `values` along with how many
+    /// more times white uses it than black
+    fn coefficients<'a>(&self, position: &'a TuningPosition) -> impl Iterator<Item = (u32, i32)> + 'a {
+        let attack_coefficients = position.attack_coefficients(&self.params, self.attack_penalty_offset);
+        position.coefficients.iter().copied().chain(attack_coefficients)
+    }
+
+    /// How far a position is into the middlegame with the current weights, from `0.0` to `1.0`
+    fn phase(&self, position: &TuningPosition) -> f64 {
+        position.phase(&self.params) as f64 / MAX_PHASE as f64
+    }
+
+    /// Evaluates a position with the current parameters, in centipawns from white's point of view
+    fn evaluate(&self, position: &TuningPosition) -> f64 {
+        let [mg, eg] = self
+            .coefficients(position)
+            .fold([0.0, 0.0], |[mg, eg], (index, coefficient)| {
+                let [value_mg, value_eg] = self.values[index as usize];
+                [mg + value_mg * coefficient as f64, eg + value_eg * coefficient as f64]
+            });
+        let phase = self.phase(position);
+        mg * phase + eg * (1.0 - phase)
+    }
+
+    /// The mean squared error between the actual results and those predicted with `scaling_constant`
+    pub(crate) fn mean_squared_error(&self, scaling_constant: f64) -> f64 {
+        if self.positions.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self
+            .map_chunks(|chunk| {
+                chunk
+                    .iter()
+                    .map(|position| {
+                        let predicted = sigmoid(scaling_constant, self.evaluate(position));
+                        (position.result - predicted).powi(2)
+                    })
+                    .sum::<f64>()
+            })
+            .into_iter()
+            .sum();
+        total / self.positions.len() as f64
+    }
+
+    /// Finds the scaling constant that best predicts the results with the current parameters, with a golden
+    /// section search, and keeps it for the rest of the tuning run
+    pub(crate) fn fit_scaling_constant(&mut self) -> f64 {
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = SCALING_CONSTANT_RANGE;
+        for _ in 0..SCALING_CONSTANT_ITERATIONS {
+            let left = high - ratio * (high - low);
+            let right = low + ratio * (high - low);
+            match self.mean_squared_error(left) < self.mean_squared_error(right) {
+                true => high = right,
+                false => low = left,
+            }
+        }
+        self.scaling_constant = (low + high) / 2.0;
+        self.scaling_constant
+    }
+
+    /// The gradient of the mean squared error with respect to every parameter
+    fn gradient(&self) -> Vec<[f64; 2]> {
+        let scaling_constant = self.scaling_constant;
+        let mut gradient = vec![[0.0; 2]; self.values.len()];
+        let partials = self.map_chunks(|chunk| {
+            let mut gradient = vec![[0.0; 2]; self.values.len()];
+            for position in chunk {
+                let predicted = sigmoid(scaling_constant, self.evaluate(position));
+                // d(error)/d(evaluation), leaving out constant factors that the optimiser scales away
+                let slope = (predicted - position.result) * predicted * (1.0 - predicted);
+                let phase = self.phase(position);
+                for (index, coefficient) in self.coefficients(position) {
+                    let entry = &mut gradient[index as usize];
+                    entry[0] += slope * coefficient as f64 * phase;
+                    entry[1] += slope * coefficient as f64 * (1.0 - phase);
+                }
+            }
+            gradient
+        });
+        for partial in partials {
+            for (total, part) in gradient.iter_mut().zip(partial) {
+                total[0] += part[0];
+                total[1] += part[1];
+            }
+        }
+        gradient
+    }
+
+    /// Takes one Adam step over the whole set of positions
+    pub(crate) fn step(&mut self) {
+        let gradient = self.gradient();
+        self.steps += 1;
+        let first_correction = 1.0 - ADAM_BETA1.powi(self.steps);
+        let second_correction = 1.0 - ADAM_BETA2.powi(self.steps);
+
+        for (index, pair) in gradient.iter().enumerate() {
+            for (half, &gradient) in pair.iter().enumerate() {
+                let first = &mut self.first_moments[index][half];
+                *first = ADAM_BETA1 * *first + (1.0 - ADAM_BETA1) * gradient;
+                let second = &mut self.second_moments[index][half];
+                *second = ADAM_BETA2 * *second + (1.0 - ADAM_BETA2) * gradient.powi(2);
+
+                let first = self.first_moments[index][half] / first_correction;
+                let second = self.second_moments[index][half] / second_correction;
+                self.values[index][half] -= self.learning_rate * first / (second.sqrt() + ADAM_EPSILON);
+            }
+        }
+    }
+
+    /// Tries moving each phase and attack weight one step up and then down, keeping any step that lowers the
+    /// error and leaves the weights usable, and returns the error afterwards
+    pub(crate) fn tune_weights(&mut self) -> f64 {
+        let mut best_error = self.mean_squared_error(self.scaling_constant);
+        for index in 0..self.params.weights().len() {
+            for step in [1, -1] {
+                *self.params.weight_mut(index) += step;
+                if self.params.validate().is_ok() {
+                    let error = self.mean_squared_error(self.scaling_constant);
+                    if error < best_error {
+                        best_error = error;
+                        break;
+                    }
+                }
+                *self.params.weight_mut(index) -= step;
+            }
+        }
+        best_error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod tuner {
+        use crate::{
+            chess_state::game_state::GameState,
+            evaluation_engine::{params::EvalParams, Evaluator},
+            tuner::{dataset::to_tuning_positions, Tuner},
+        };
+
+        fn tuner() -> Tuner {
+            // whoever is a knight up wins most of the games, but not all of them
+            let games = [
+                ("4k3/8/8/8/8/2N5/8/4K3 w - - 0 1", 1.0),
+                ("4k3/8/8/8/8/5N2/8/4K3 w - - 0 1", 0.5),
+                ("4k3/8/8/8/8/8/8/1N2K3 b - - 0 1", 1.0),
+                ("4k3/8/2n5/8/8/8/8/4K3 w - - 0 1", 0.0),
+                ("1n2k3/8/8/8/8/8/8/4K3 b - - 0 1", 0.0),
+                ("4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1", 0.5),
+            ]
+            .map(|(fen, result)| (GameState::from_fen(fen).expect("valid fen"), result));
+            let params = EvalParams::default();
+            Tuner::new(to_tuning_positions(&games, &params, 2), &params, 2, 5.0)
+        }
+
+        #[test]
+        fn matches_default_parameters_before_any_step() {
+            // act + assert
+            assert_eq!(tuner().params(), EvalParams::default())
+        }
+
+        #[test]
+        fn fits_scaling_constant_to_the_lowest_error() {
+            // arrange
+            let mut tuner = tuner();
+            // act
+            let scaling_constant = tuner.fit_scaling_constant();
+            // assert
+            let error = tuner.mean_squared_error(scaling_constant);
+            assert!(error <= tuner.mean_squared_error(scaling_constant * 0.9));
+            assert!(error <= tuner.mean_squared_error(scaling_constant * 1.1))
+        }
+
+        #[test]
+        fn lowers_error_when_stepped() {
+            // arrange
+            let mut tuner = tuner();
+            // a deliberately poor fit, so the knight is worth too little to predict the wins
+            tuner.scaling_constant = 0.1;
+            let before = tuner.mean_squared_error(tuner.scaling_constant());
+            // act
+            for _ in 0..20 {
+                tuner.step();
+            }
+            // assert
+            assert!(tuner.mean_squared_error(tuner.scaling_constant()) < before);
+            assert!(tuner.params().knight_value.mg > EvalParams::default().knight_value.mg)
+        }
+
+        #[test]
+        fn evaluates_like_the_engine_when_started_from_other_weights() {
+            // arrange
+            let games = [
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "6k1/5ppp/8/8/6nq/8/5PPP/6K1 w - - 0 1",
+                "r1bq1rk1/pppp1Npp/2n2n2/2b1p3/2B1P3/8/PPPP1PPP/RNBQK2R b KQ - 0 6",
+            ]
+            .map(|fen| (GameState::from_fen(fen).expect("valid fen"), 0.5));
+            let params = EvalParams {
+                phase_weights: [2, 1, 3, 3],
+                king_attack_weights: [3, 1, 2, 6],
+                ..EvalParams::default()
+            };
+            let mut evaluator = Evaluator::with_params(params.clone());
+            // act
+            let tuner = Tuner::new(to_tuning_positions(&games, &EvalParams::default(), 1), &params, 1, 1.0);
+            // assert
+            assert_eq!(tuner.params(), params);
+            for ((game, _), position) in games.iter().zip(&tuner.positions) {
+                let expected = evaluator.evaluate_tapered(&game.board).tapered_score() as f64;
+                // the engine rounds towards zero when it blends the middlegame and endgame
+                assert!((tuner.evaluate(position) - expected).abs() < 1.0);
+            }
+        }
+
+        #[test]
+        fn lowers_error_and_keeps_weights_usable_when_weights_are_tuned() {
+            // arrange
+            let mut tuner = tuner();
+            tuner.params.phase_weights = [0, 0, 0, 1];
+            tuner.scaling_constant = 1.0;
+            let before = tuner.mean_squared_error(tuner.scaling_constant());
+            // act
+            let mut error = before;
+            for _ in 0..5 {
+                error = tuner.tune_weights();
+            }
+            // assert
+            assert!(error < before);
+            assert_eq!(error, tuner.mean_squared_error(tuner.scaling_constant()));
+            assert!(tuner.params().validate().is_ok())
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! The tuner's view of the evaluation parameters: every middlegame/endgame value and every weight laid out as a
+//! flat list, so the optimiser can step through them by index, and a way to write the result back out.
+
+use std::{fs, path::Path};
+
+use crate::evaluation_engine::{
+    params::{EvalParams, EvalParamsError, ParamValues, ParamValuesMut},
+    score::Score,
+};
+
+impl EvalParams {
+    /// Every middlegame/endgame value in one flat list, in the same order as `fields`, leaving out the weights
+    pub(crate) fn values(&self) -> Vec<Score> {
+        self.fields()
+            .into_iter()
+            .flat_map(|(_, values)| match values {
+                ParamValues::Scores(scores) => scores,
+                ParamValues::Weights(_) => &[],
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Overwrites every middlegame/endgame value from a flat list in the same order as `values`, which must be
+    /// the same length
+    pub(crate) fn set_values(&mut self, values: &[Score]) {
+        let mut values = values.iter();
+        for (_, field) in self.fields_mut() {
+            if let ParamValuesMut::Scores(scores) = field {
+                for score in scores {
+                    *score = *values.next().expect("one value for every parameter");
+                }
+            }
+        }
+        assert!(values.next().is_none(), "one parameter for every value");
+    }
+
+    /// Every weight in one flat list, in the same order as `fields`
+    pub(crate) fn weights(&self) -> Vec<i32> {
+        self.fields()
+            .into_iter()
+            .flat_map(|(_, values)| match values {
+                ParamValues::Scores(_) => &[],
+                ParamValues::Weights(weights) => weights,
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns the weight at `index` in the flat list given by `weights`
+    pub(crate) fn weight_mut(&mut self, index: usize) -> &mut i32 {
+        let mut index = index;
+        for (_, field) in self.fields_mut() {
+            if let ParamValuesMut::Weights(weights) = field {
+                if index < weights.len() {
+                    return &mut weights[index];
+                }
+                index -= weights.len();
+            }
+        }
+        panic!("weight index out of range")
+    }
+
+    /// Writes the parameters to a file that `load` can read back
+    pub(crate) fn save(&self, path: impl AsRef<Path>) -> Result<(), EvalParamsError> {
+        Ok(fs::write(path, self.to_string())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod values {
+        use crate::evaluation_engine::{params::EvalParams, score::Score};
+
+        #[test]
+        fn round_trips_through_flat_values() {
+            // arrange
+            let mut values = EvalParams::default().values();
+            values[3] = Score::new(1, 2);
+            let mut params = EvalParams::default();
+            // act
+            params.set_values(&values);
+            // assert
+            assert_eq!(params.rook_value, Score::new(1, 2));
+            assert_eq!(params.values(), values)
+        }
+
+        #[test]
+        fn lists_weights_apart_from_values() {
+            // arrange
+            let mut params = EvalParams::default();
+            // act
+            *params.weight_mut(5) = 7;
+            // assert
+            assert_eq!(params.weights(), vec![1, 1, 2, 4, 2, 7, 3, 5]);
+            assert_eq!(params.king_attack_weights, [2, 7, 3, 5]);
+            assert_eq!(params.values().len(), EvalParams::default().values().len())
+        }
+    }
+}